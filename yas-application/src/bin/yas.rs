@@ -1,7 +1,14 @@
-use clap::{command, Command};
+use anyhow::Result;
+use anyhow::anyhow;
+use clap::{command, Arg, Args, Command, FromArgMatches};
+use log::info;
+use yas::calibration::{CalibrationConfig, Calibrator};
+use yas::draw_capture_region::DrawWindowInfoConfig;
 use yas::utils::press_any_key_to_continue;
+use yas::window_info::WindowInfoConfig;
 use yas_genshin::application::ArtifactScannerApplication;
 use yas_starrail::application::RelicScannerApplication;
+use yas_wutheringwaves::application::WWEchoScannerApplication;
 
 fn get_genshin_command() -> Command {
     let cmd = ArtifactScannerApplication::build_command();
@@ -13,6 +20,55 @@ fn get_starrail_command() -> Command {
     cmd.name("starrail")
 }
 
+fn get_calibrate_command() -> Command {
+    let cmd = Command::new("calibrate")
+        .about("根据参考布局和新分辨率下的截图生成窗口布局文件");
+    <CalibrationConfig as Args>::augment_args(cmd)
+}
+
+fn run_calibrate(matches: &clap::ArgMatches) -> Result<()> {
+    let config = CalibrationConfig::from_arg_matches(matches)?;
+    let report = Calibrator::from_config(&config)?.calibrate();
+
+    println!("{}", report);
+    report.save(&config.output)?;
+    info!("窗口布局已保存到 {:?}", config.output);
+
+    Ok(())
+}
+
+fn get_draw_window_info_command() -> Command {
+    let mut cmd = Command::new("draw-window-info")
+        .about("在游戏截图上标注所有窗口信息，用于检查布局")
+        .arg(Arg::new("game")
+            .required(true)
+            .value_parser(["genshin", "starrail", "wutheringwaves"])
+            .help("游戏"));
+    cmd = <DrawWindowInfoConfig as Args>::augment_args_for_update(cmd);
+    cmd = <WindowInfoConfig as Args>::augment_args_for_update(cmd);
+    cmd
+}
+
+fn run_draw_window_info(matches: &clap::ArgMatches) -> Result<()> {
+    let config = DrawWindowInfoConfig::from_arg_matches(matches)?;
+    let window_info_config = WindowInfoConfig::from_arg_matches(matches)?;
+    let mut image = image::open(&config.screenshot)
+        .map_err(|e| anyhow!("无法读取截图 {:?}：{}", config.screenshot, e))?
+        .to_rgb8();
+
+    match matches.get_one::<String>("game").map(|s| s.as_str()) {
+        Some("genshin") => ArtifactScannerApplication::draw_window_info(&window_info_config, &mut image)?,
+        Some("starrail") => RelicScannerApplication::draw_window_info(&window_info_config, &mut image)?,
+        Some("wutheringwaves") => WWEchoScannerApplication::draw_window_info(&window_info_config, &mut image)?,
+        _ => unreachable!(),
+    }
+
+    image.save(&config.output)?;
+    info!("标注图片已保存到 {:?}", config.output);
+
+    Ok(())
+}
+
 fn init() {
     env_logger::Builder::new()
         .filter_level(log::LevelFilter::Info)
@@ -23,7 +79,9 @@ pub fn main() {
     init();
     let cmd = command!()
         .subcommand(get_genshin_command())
-        .subcommand(get_starrail_command());
+        .subcommand(get_starrail_command())
+        .subcommand(get_calibrate_command())
+        .subcommand(get_draw_window_info_command());
     let arg_matches = cmd.get_matches();
 
     let res = if let Some((subcommand_name, matches)) = arg_matches.subcommand() {
@@ -33,6 +91,10 @@ pub fn main() {
         } else if subcommand_name == "starrail" {
             let application = RelicScannerApplication::new(matches.clone());
             application.run()
+        } else if subcommand_name == "calibrate" {
+            run_calibrate(matches)
+        } else if subcommand_name == "draw-window-info" {
+            run_draw_window_info(matches)
         } else {
             Ok(())
         }
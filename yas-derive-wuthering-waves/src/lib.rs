@@ -38,6 +38,9 @@ fn echo_name_from_chs(data: &[EchoDataItem], echo_names: &[proc_macro2::TokenStr
                     _ => return None,
                 }
             }
+
+            /// Chinese names of all echoes
+            pub const ALL_CHS: &'static [&'static str] = &[#(#chs_names),*];
         }
     }
 }
@@ -8,6 +8,7 @@ pub fn yas_window_info(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
 
     if let syn::Data::Struct(data_struct) = &input.data {
         let mut fields = Vec::new();
+        let mut draw_fields = Vec::new();
         for field in data_struct.fields.iter() {
             let name = field.ident.as_ref().unwrap();
 
@@ -22,13 +23,21 @@ pub fn yas_window_info(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
             }
 
             fields.push(quote! {
-                #name: match repo.get_auto_scale(#window_info_key, window_size, ui, platform) {
+                #name: match repo.get_auto_scale_with_source(#window_info_key, window_size, ui, platform) {
                     None => {
                         return Err(anyhow::anyhow!("cannot find window info key \"{}\"", #window_info_key));
                     },
-                    Some(value) => value
+                    Some((value, source)) => {
+                        if source.platform != platform {
+                            fallback = Some(source);
+                        }
+                        value
+                    }
                 }
             });
+            draw_fields.push(quote! {
+                yas::draw_capture_region::DrawCaptureRegion::draw_capture_region_with_label(&self.#name, image, #window_info_key);
+            });
         }
 
         let trait_impl = quote! {
@@ -39,9 +48,22 @@ pub fn yas_window_info(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
                     platform: yas::game_info::Platform,
                     repo: &yas::window_info::WindowInfoRepository
                 ) -> anyhow::Result<Self> {
-                    Ok(Self {
+                    // an entry of another platform, reported once for the whole struct
+                    let mut fallback: Option<yas::window_info::WindowInfoSource> = None;
+                    let result = Self {
                         #(#fields),*
-                    })
+                    };
+
+                    if let Some(source) = fallback {
+                        source.report_platform_fallback(stringify!(#struct_name), platform);
+                    }
+                    Ok(result)
+                }
+            }
+
+            impl yas::draw_capture_region::DrawWindowInfo for #struct_name {
+                fn draw_window_info(&self, image: &mut image::RgbImage) {
+                    #(#draw_fields)*
                 }
             }
         };
@@ -1,16 +1,23 @@
 use anyhow::Result;
 use anyhow::anyhow;
-use clap::{command, ArgMatches, Args};
+use clap::{command, ArgMatches, Args, FromArgMatches};
+use image::RgbImage;
 use log::info;
 
 use yas::export::{AssetEmitter, ExportAssets};
-use yas::game_info::{GameInfo, GameInfoBuilder};
-use yas::window_info::{load_window_info_repo, WindowInfoRepository};
+use yas::capture::{CapturerConfig, ReplayCapturer};
+use yas::draw_capture_region::draw_window_info_from_repository;
+use yas::dump::DumpConfig;
+use yas::ocr::OCRModelConfig;
+use yas::scanner_controller::repository_layout::ScanCheckpointConfig;
+use yas::system_control::AbortKeyConfig;
+use yas::game_info::{GameInfo, GameInfoBuilder, Platform, UI};
+use yas::window_info::{load_window_info_repo, WindowInfoConfig, WindowInfoRepository};
 
 use crate::artifact::GenshinArtifact;
 use crate::export::artifact::{ExportArtifactConfig, GenshinArtifactExporter};
-use crate::scanner::{GenshinArtifactScanner, GenshinArtifactScannerConfig};
-use crate::scanner_controller::repository_layout::GenshinRepositoryScannerLogicConfig;
+use crate::scanner::{ArtifactScannerWindowInfo, GenshinArtifactScanner, GenshinArtifactScannerConfig};
+use crate::scanner_controller::repository_layout::{GenshinRepositoryScanControllerWindowInfo, GenshinRepositoryScannerLogicConfig};
 
 pub struct ArtifactScannerApplication {
     arg_matches: ArgMatches,
@@ -28,6 +35,12 @@ impl ArtifactScannerApplication {
         cmd = <ExportArtifactConfig as Args>::augment_args_for_update(cmd);
         cmd = <GenshinArtifactScannerConfig as Args>::augment_args_for_update(cmd);
         cmd = <GenshinRepositoryScannerLogicConfig as Args>::augment_args_for_update(cmd);
+        cmd = <CapturerConfig as Args>::augment_args_for_update(cmd);
+        cmd = <DumpConfig as Args>::augment_args_for_update(cmd);
+        cmd = <ScanCheckpointConfig as Args>::augment_args_for_update(cmd);
+        cmd = <OCRModelConfig as Args>::augment_args_for_update(cmd);
+        cmd = <AbortKeyConfig as Args>::augment_args_for_update(cmd);
+        cmd = <WindowInfoConfig as Args>::augment_args_for_update(cmd);
         cmd
     }
 
@@ -41,13 +54,28 @@ impl ArtifactScannerApplication {
         )
     }
 
+    /// Draw the window info used by the scanner onto a screenshot of the game window, labelled by key
+    pub fn draw_window_info(window_info_config: &WindowInfoConfig, image: &mut RgbImage) -> Result<()> {
+        let mut window_info_repository = Self::get_window_info_repository();
+        window_info_config.apply(&mut window_info_repository)?;
+
+        let platform = Platform::current();
+        draw_window_info_from_repository::<GenshinRepositoryScanControllerWindowInfo>(&window_info_repository, image, UI::Desktop, platform)?;
+        draw_window_info_from_repository::<ArtifactScannerWindowInfo>(&window_info_repository, image, UI::Desktop, platform)?;
+        Ok(())
+    }
+
     // fn init() {
     //     env_logger::Builder::new()
     //         .filter_level(log::LevelFilter::Info)
     //         .init();
     // }
 
-    fn get_game_info() -> Result<GameInfo> {
+    fn get_game_info(capturer_config: &CapturerConfig) -> Result<GameInfo> {
+        if let Some(dir) = &capturer_config.replay {
+            return ReplayCapturer::new(dir)?.game_info();
+        }
+
         let game_info = GameInfoBuilder::new()
             .add_local_window_name("原神")
             .add_local_window_name("Genshin Impact")
@@ -60,8 +88,11 @@ impl ArtifactScannerApplication {
 impl ArtifactScannerApplication {
     pub fn run(&self) -> Result<()> {
         let arg_matches = &self.arg_matches;
-        let window_info_repository = Self::get_window_info_repository();
-        let game_info = Self::get_game_info()?;
+        let mut window_info_repository = Self::get_window_info_repository();
+        WindowInfoConfig::from_arg_matches(arg_matches)?.apply(&mut window_info_repository)?;
+        AbortKeyConfig::from_arg_matches(arg_matches)?.apply();
+        let capturer_config = CapturerConfig::from_arg_matches(arg_matches)?;
+        let game_info = Self::get_game_info(&capturer_config)?;
 
         info!("window: {:?}", game_info.window);
         info!("ui: {:?}", game_info.ui);
@@ -69,7 +100,7 @@ impl ArtifactScannerApplication {
         info!("resolution family: {:?}", game_info.resolution_family);
 
         #[cfg(target_os = "windows")]
-        {
+        if capturer_config.replay.is_none() {
             // assure admin
             if !yas::utils::is_admin() {
                 return Err(anyhow!("请使用管理员运行"));
@@ -3,6 +3,7 @@ pub use artifact::ArtifactSlot;
 pub use artifact::ArtifactStat;
 pub use artifact::ArtifactStatName;
 pub use artifact::GenshinArtifact;
+pub use zh_cn::{ARTIFACT_NAMES_ZH_CN, ARTIFACT_STAT_NAMES_ZH_CN};
 
 mod artifact;
 mod zh_cn;
@@ -1,4 +1,4 @@
-use crate::artifact::ArtifactSlot;
+use crate::artifact::{ArtifactSetName, ArtifactSlot, ArtifactStatName};
 
 impl ArtifactSlot {
     pub fn to_zh_cn(&self) -> &'static str {
@@ -11,3 +11,335 @@ impl ArtifactSlot {
         }
     }
 }
+
+/// Lists the names for the lexicon of the artifact panel, and parses them in `from_zh_cn`
+macro_rules! artifact_names_zh_cn {
+    ($($name:literal => ($set:ident, $slot:ident),)*) => {
+        /// Names of all artifact pieces, which are the titles of the artifact panel
+        pub const ARTIFACT_NAMES_ZH_CN: &[&str] = &[$($name),*];
+
+        impl ArtifactSetName {
+            pub fn from_zh_cn(s: &str) -> Option<ArtifactSetName> {
+                match s {
+                    $($name => Some(ArtifactSetName::$set),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl ArtifactSlot {
+            pub fn from_zh_cn(s: &str) -> Option<ArtifactSlot> {
+                match s {
+                    $($name => Some(ArtifactSlot::$slot),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+/// Same for the stats, `flat / percentage` for a name shared by both
+macro_rules! artifact_stat_names_zh_cn {
+    (@stat $is_percentage:ident, $stat:ident) => {
+        ArtifactStatName::$stat
+    };
+    (@stat $is_percentage:ident, $stat:ident / $percentage:ident) => {
+        if $is_percentage { ArtifactStatName::$percentage } else { ArtifactStatName::$stat }
+    };
+    ($($name:literal => $stat:ident $(/ $percentage:ident)?,)*) => {
+        /// Names of all artifact stats, without the value
+        pub const ARTIFACT_STAT_NAMES_ZH_CN: &[&str] = &[$($name),*];
+
+        impl ArtifactStatName {
+            pub fn from_zh_cn(name: &str, is_percentage: bool) -> Option<ArtifactStatName> {
+                match name {
+                    $($name => Some(artifact_stat_names_zh_cn!(@stat is_percentage, $stat $(/ $percentage)?)),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+artifact_names_zh_cn! {
+    "磐陀裂生之花" => (ArchaicPetra, Flower),
+    "嵯峨群峰之翼" => (ArchaicPetra, Feather),
+    "星罗圭壁之晷" => (ArchaicPetra, Sand),
+    "星罗圭璧之晷" => (ArchaicPetra, Sand),
+    "巉岩琢塑之樽" => (ArchaicPetra, Goblet),
+    "不动玄石之相" => (ArchaicPetra, Head),
+    "历经风雪的思念" => (BlizzardStrayer, Flower),
+    "摧冰而行的执望" => (BlizzardStrayer, Feather),
+    "冰雪故园的终期" => (BlizzardStrayer, Sand),
+    "遍结寒霜的傲骨" => (BlizzardStrayer, Goblet),
+    "破冰踏雪的回音" => (BlizzardStrayer, Head),
+    "染血的铁之心" => (BloodstainedChivalry, Flower),
+    "染血的黑之羽" => (BloodstainedChivalry, Feather),
+    "骑士染血之时" => (BloodstainedChivalry, Sand),
+    "染血骑士之杯" => (BloodstainedChivalry, Goblet),
+    "染血的铁假面" => (BloodstainedChivalry, Head),
+    "魔女的炎之花" => (CrimsonWitch, Flower),
+    "魔女常燃之羽" => (CrimsonWitch, Feather),
+    "魔女破灭之时" => (CrimsonWitch, Sand),
+    "魔女的心之火" => (CrimsonWitch, Goblet),
+    "焦灼的魔女帽" => (CrimsonWitch, Head),
+    "角斗士的留恋" => (GladiatorFinale, Flower),
+    "角斗士的归宿" => (GladiatorFinale, Feather),
+    "角斗士的希冀" => (GladiatorFinale, Sand),
+    "角斗士的酣醉" => (GladiatorFinale, Goblet),
+    "角斗士的凯旋" => (GladiatorFinale, Head),
+    "饰金胸花" => (HeartOfDepth, Flower),
+    "追忆之风" => (HeartOfDepth, Feather),
+    "坚铜罗盘" => (HeartOfDepth, Sand),
+    "沉波之盏" => (HeartOfDepth, Goblet),
+    "酒渍船帽" => (HeartOfDepth, Head),
+    "渡火者的决绝" => (LavaWalker, Flower),
+    "渡火者的解脱" => (LavaWalker, Feather),
+    "渡火者的煎熬" => (LavaWalker, Sand),
+    "渡火者的醒悟" => (LavaWalker, Goblet),
+    "渡火者的智慧" => (LavaWalker, Head),
+    "远方的少女之心" => (MaidenBeloved, Flower),
+    "少女飘摇的思念" => (MaidenBeloved, Feather),
+    "少女苦短的良辰" => (MaidenBeloved, Sand),
+    "少女片刻的闲暇" => (MaidenBeloved, Goblet),
+    "少女易逝的芳颜" => (MaidenBeloved, Head),
+    "宗室之花" => (NoblesseOblige, Flower),
+    "宗室之翎" => (NoblesseOblige, Feather),
+    "宗室时计" => (NoblesseOblige, Sand),
+    "宗室银瓮" => (NoblesseOblige, Goblet),
+    "宗室面具" => (NoblesseOblige, Head),
+    "夏祭之花" => (RetracingBolide, Flower),
+    "夏祭终末" => (RetracingBolide, Feather),
+    "夏祭之刻" => (RetracingBolide, Sand),
+    "夏祭水玉" => (RetracingBolide, Goblet),
+    "夏祭之面" => (RetracingBolide, Head),
+    "平雷之心" => (ThunderSmoother, Flower),
+    "平雷之羽" => (ThunderSmoother, Feather),
+    "平雷之刻" => (ThunderSmoother, Sand),
+    "平雷之器" => (ThunderSmoother, Goblet),
+    "平雷之冠" => (ThunderSmoother, Head),
+    "雷鸟的怜悯" => (ThunderingFury, Flower),
+    "雷灾的孑遗" => (ThunderingFury, Feather),
+    "雷霆的时计" => (ThunderingFury, Sand),
+    "降雷的凶兆" => (ThunderingFury, Goblet),
+    "唤雷的头冠" => (ThunderingFury, Head),
+    "野花记忆的绿野" => (ViridescentVenerer, Flower),
+    "猎人青翠的箭羽" => (ViridescentVenerer, Feather),
+    "翠绿猎人的笃定" => (ViridescentVenerer, Sand),
+    "翠绿猎人的容器" => (ViridescentVenerer, Goblet),
+    "翠绿的猎人之冠" => (ViridescentVenerer, Head),
+    "乐团的晨光" => (WandererTroupe, Flower),
+    "琴师的箭羽" => (WandererTroupe, Feather),
+    "终幕的时计" => (WandererTroupe, Sand),
+    "终末的时计" => (WandererTroupe, Sand),
+    "吟游者之壶" => (WandererTroupe, Goblet),
+    "指挥的礼帽" => (WandererTroupe, Head),
+    "战狂的蔷薇" => (Berserker, Flower),
+    "战狂的翎羽" => (Berserker, Feather),
+    "战狂的时计" => (Berserker, Sand),
+    "战狂的骨杯" => (Berserker, Goblet),
+    "战狂的鬼面" => (Berserker, Head),
+    "勇士的勋章" => (BraveHeart, Flower),
+    "勇士的期许" => (BraveHeart, Feather),
+    "勇士的坚毅" => (BraveHeart, Sand),
+    "勇士的壮行" => (BraveHeart, Goblet),
+    "勇士的冠冕" => (BraveHeart, Head),
+    "守护之花" => (DefenderWill, Flower),
+    "守护徽印" => (DefenderWill, Feather),
+    "守护座钟" => (DefenderWill, Sand),
+    "守护之皿" => (DefenderWill, Goblet),
+    "守护束带" => (DefenderWill, Head),
+    "流放者之花" => (Exile, Flower),
+    "流放者之羽" => (Exile, Feather),
+    "流放者怀表" => (Exile, Sand),
+    "流放者之杯" => (Exile, Goblet),
+    "流放者头冠" => (Exile, Head),
+    "赌徒的胸花" => (Gambler, Flower),
+    "赌徒的羽饰" => (Gambler, Feather),
+    "赌徒的怀表" => (Gambler, Sand),
+    "赌徒的骰盅" => (Gambler, Goblet),
+    "赌徒的耳环" => (Gambler, Head),
+    "教官的胸花" => (Instructor, Flower),
+    "教官的羽饰" => (Instructor, Feather),
+    "教官的怀表" => (Instructor, Sand),
+    "教官的茶杯" => (Instructor, Goblet),
+    "教官的帽子" => (Instructor, Head),
+    "武人的红花" => (MartialArtist, Flower),
+    "武人的羽饰" => (MartialArtist, Feather),
+    "武人的水漏" => (MartialArtist, Sand),
+    "武人的酒杯" => (MartialArtist, Goblet),
+    "武人的头巾" => (MartialArtist, Head),
+    "祭水礼冠" => (PrayersForDestiny, Head),
+    "祭火礼冠" => (PrayersForIllumination, Head),
+    "祭雷礼冠" => (PrayersForWisdom, Head),
+    "祭冰礼冠" => (PrayersToSpringtime, Head),
+    "故人之心" => (ResolutionOfSojourner, Flower),
+    "归乡之羽" => (ResolutionOfSojourner, Feather),
+    "逐光之石" => (ResolutionOfSojourner, Sand),
+    "异国之盏" => (ResolutionOfSojourner, Goblet),
+    "感别之冠" => (ResolutionOfSojourner, Head),
+    "学士的书签" => (Scholar, Flower),
+    "学士的羽笔" => (Scholar, Feather),
+    "学士的时钟" => (Scholar, Sand),
+    "学士的墨杯" => (Scholar, Goblet),
+    "学士的镜片" => (Scholar, Head),
+    "奇迹之花" => (TinyMiracle, Flower),
+    "奇迹之羽" => (TinyMiracle, Feather),
+    "奇迹之沙" => (TinyMiracle, Sand),
+    "奇迹之杯" => (TinyMiracle, Goblet),
+    "奇迹耳坠" => (TinyMiracle, Head),
+    "冒险家之花" => (Adventurer, Flower),
+    "冒险家尾羽" => (Adventurer, Feather),
+    "冒险家怀表" => (Adventurer, Sand),
+    "冒险家金杯" => (Adventurer, Goblet),
+    "冒险家头带" => (Adventurer, Head),
+    "幸运儿绿花" => (LuckyDog, Flower),
+    "幸运儿鹰羽" => (LuckyDog, Feather),
+    "幸运儿沙漏" => (LuckyDog, Sand),
+    "幸运儿之杯" => (LuckyDog, Goblet),
+    "幸运儿银冠" => (LuckyDog, Head),
+    "游医的银莲" => (TravelingDoctor, Flower),
+    "游医的枭羽" => (TravelingDoctor, Feather),
+    "游医的怀钟" => (TravelingDoctor, Sand),
+    "游医的药壶" => (TravelingDoctor, Goblet),
+    "游医的方巾" => (TravelingDoctor, Head),
+    "勋绩之花" => (TenacityOfTheMillelith, Flower),
+    "昭武翎羽" => (TenacityOfTheMillelith, Feather),
+    "金铜时晷" => (TenacityOfTheMillelith, Sand),
+    "盟誓金爵" => (TenacityOfTheMillelith, Goblet),
+    "将帅兜鍪" => (TenacityOfTheMillelith, Head),
+    "无垢之花" => (PaleFlame, Flower),
+    "贤医之羽" => (PaleFlame, Feather),
+    "停摆之刻" => (PaleFlame, Sand),
+    "超越之盏" => (PaleFlame, Goblet),
+    "嗤笑之面" => (PaleFlame, Head),
+    "明威之镡" => (EmblemOfSeveredFate, Flower),
+    "切落之羽" => (EmblemOfSeveredFate, Feather),
+    "雷云之笼" => (EmblemOfSeveredFate, Sand),
+    "绯花之壶" => (EmblemOfSeveredFate, Goblet),
+    "华饰之兜" => (EmblemOfSeveredFate, Head),
+    "羁缠之花" => (ShimenawaReminiscence, Flower),
+    "思忆之矢" => (ShimenawaReminiscence, Feather),
+    "朝露之时" => (ShimenawaReminiscence, Sand),
+    "祈望之心" => (ShimenawaReminiscence, Goblet),
+    "无常之面" => (ShimenawaReminiscence, Head),
+    "荣花之期" => (HuskOfOpulentDreams, Flower),
+    "华馆之羽" => (HuskOfOpulentDreams, Feather),
+    "众生之谣" => (HuskOfOpulentDreams, Sand),
+    "梦醒之瓢" => (HuskOfOpulentDreams, Goblet),
+    "形骸之笠" => (HuskOfOpulentDreams, Head),
+    "海染之花" => (OceanHuedClam, Flower),
+    "渊宫之羽" => (OceanHuedClam, Feather),
+    "离别之贝" => (OceanHuedClam, Sand),
+    "真珠之笼" => (OceanHuedClam, Goblet),
+    "海祇之冠" => (OceanHuedClam, Head),
+    "生灵之华" => (VermillionHereafter, Flower),
+    "阳辔之遗" => (VermillionHereafter, Sand),
+    "潜光片羽" => (VermillionHereafter, Feather),
+    "结契之刻" => (VermillionHereafter, Goblet),
+    "虺雷之姿" => (VermillionHereafter, Head),
+    "魂香之花" => (EchoesOfAnOffering, Flower),
+    "祝祀之凭" => (EchoesOfAnOffering, Sand),
+    "垂玉之叶" => (EchoesOfAnOffering, Feather),
+    "涌泉之盏" => (EchoesOfAnOffering, Goblet),
+    "浮溯之珏" => (EchoesOfAnOffering, Head),
+    "迷宫的游人" => (DeepwoodMemories, Flower),
+    "翠蔓的智者" => (DeepwoodMemories, Feather),
+    "贤智的定期" => (DeepwoodMemories, Sand),
+    "迷误者之灯" => (DeepwoodMemories, Goblet),
+    "月桂的宝冠" => (DeepwoodMemories, Head),
+    "梦中的铁花" => (GildedDreams, Flower),
+    "裁断的翎羽" => (GildedDreams, Feather),
+    "沉金的岁月" => (GildedDreams, Sand),
+    "如蜜的终宴" => (GildedDreams, Goblet),
+    "沙王的投影" => (GildedDreams, Head),
+    "月女的华彩" => (FlowerOfParadiseLost, Flower),
+    "谢落的筵席" => (FlowerOfParadiseLost, Feather),
+    "凝结的时刻" => (FlowerOfParadiseLost, Sand),
+    "守秘的魔瓶" => (FlowerOfParadiseLost, Goblet),
+    "紫晶的花冠" => (FlowerOfParadiseLost, Head),
+    "众王之都的开端" => (DesertPavilionChronicle, Flower),
+    "黄金邦国的结末" => (DesertPavilionChronicle, Feather),
+    "失落迷途的机芯" => (DesertPavilionChronicle, Sand),
+    "迷醉长梦的守护" => (DesertPavilionChronicle, Goblet),
+    "流沙贵嗣的遗宝" => (DesertPavilionChronicle, Head),
+    "恶龙的单片镜" => (NymphsDream, Head),
+    "坏巫师的羽杖" => (NymphsDream, Feather),
+    "旅途中的鲜花" => (NymphsDream, Flower),
+    "水仙的时时刻刻" => (NymphsDream, Sand),
+    "勇者们的茶会" => (NymphsDream, Goblet),
+    "灵光明烁之心" => (VourukashasGlow, Head),
+    "琦色灵彩之羽" => (VourukashasGlow, Feather),
+    "灵光源起之蕊" => (VourukashasGlow, Flower),
+    "久远花落之时" => (VourukashasGlow, Sand),
+    "无边酣乐之筵" => (VourukashasGlow, Goblet),
+    "猎人的胸花" => (MarechausseeHunter, Flower),
+    "杰作的序曲" => (MarechausseeHunter, Feather),
+    "裁判的时刻" => (MarechausseeHunter, Sand),
+    "遗忘的容器" => (MarechausseeHunter, Goblet),
+    "老兵的容颜" => (MarechausseeHunter, Head),
+    "黄金乐曲的变奏" => (GoldenTroupe, Flower),
+    "黄金飞鸟的落羽" => (GoldenTroupe, Feather),
+    "黄金时代的先声" => (GoldenTroupe, Sand),
+    "黄金之夜的喧嚣" => (GoldenTroupe, Goblet),
+    "黄金剧团的奖赏" => (GoldenTroupe, Head),
+    "昔时传奏之诗" => (SongOfDaysPast, Head),
+    "昔时浮想之思" => (SongOfDaysPast, Feather),
+    "昔时遗落之誓" => (SongOfDaysPast, Flower),
+    "昔时回映之音" => (SongOfDaysPast, Sand),
+    "昔时应许之梦" => (SongOfDaysPast, Goblet),
+    "慈爱的淑女帽" => (NighttimeWhispersInTheEchoingWoods, Head),
+    "诚恳的蘸水笔" => (NighttimeWhispersInTheEchoingWoods, Feather),
+    "无私的妆饰花" => (NighttimeWhispersInTheEchoingWoods, Flower),
+    "忠实的砂时计" => (NighttimeWhispersInTheEchoingWoods, Sand),
+    "慷慨的墨水瓶" => (NighttimeWhispersInTheEchoingWoods, Goblet),
+    "异想零落的圆舞" => (FragmentOfHarmonicWhimsy, Head),
+    "古海玄幽的夜想" => (FragmentOfHarmonicWhimsy, Feather),
+    "谐律交响的前奏" => (FragmentOfHarmonicWhimsy, Flower),
+    "命途轮转的谐谑" => (FragmentOfHarmonicWhimsy, Sand),
+    "灵露倾洒的狂诗" => (FragmentOfHarmonicWhimsy, Goblet),
+    "失冕的宝冠" => (UnfinishedReverie, Head),
+    "褪光的翠尾" => (UnfinishedReverie, Feather),
+    "暗结的明花" => (UnfinishedReverie, Flower),
+    "举业的识刻" => (UnfinishedReverie, Sand),
+    "筹谋的共樽" => (UnfinishedReverie, Goblet),
+    "魔战士的羽面" => (ScrollOfTheHeroOfCinderCity, Head),
+    "巡山客的信标" => (ScrollOfTheHeroOfCinderCity, Feather),
+    "驯兽师的护符" => (ScrollOfTheHeroOfCinderCity, Flower),
+    "秘术家的金盘" => (ScrollOfTheHeroOfCinderCity, Sand),
+    "游学者的爪杯" => (ScrollOfTheHeroOfCinderCity, Goblet),
+    "诸圣的礼冠" => (ObsidianCodex, Head),
+    "灵髓的根脉" => (ObsidianCodex, Feather),
+    "异种的期许" => (ObsidianCodex, Flower),
+    "夜域的迷思" => (ObsidianCodex, Sand),
+    "纷争的前宴" => (ObsidianCodex, Goblet),
+    "深廊的遂失之冕" => (FinaleOfTheDeepGalleries, Head),
+    "深廊的漫远之约" => (FinaleOfTheDeepGalleries, Feather),
+    "深廊的回奏之歌" => (FinaleOfTheDeepGalleries, Flower),
+    "深廊的湮落之刻" => (FinaleOfTheDeepGalleries, Sand),
+    "深廊的饫赐之宴" => (FinaleOfTheDeepGalleries, Goblet),
+    "被浸染的缨盔" => (LongNightsOath, Head),
+    "夜鸣莺的尾羽" => (LongNightsOath, Feather),
+    "执灯人的誓词" => (LongNightsOath, Flower),
+    "不死者的哀铃" => (LongNightsOath, Sand),
+    "未吹响的号角" => (LongNightsOath, Goblet),
+}
+
+artifact_stat_names_zh_cn! {
+    "治疗加成" => HealingBonus,
+    "暴击伤害" => CriticalDamage,
+    "暴击率" => Critical,
+    "攻击力" => Atk / AtkPercentage,
+    "元素精通" => ElementalMastery,
+    "元素充能效率" => Recharge,
+    "生命值" => Hp / HpPercentage,
+    "防御力" => Def / DefPercentage,
+    "雷元素伤害加成" => ElectroBonus,
+    "火元素伤害加成" => PyroBonus,
+    "水元素伤害加成" => HydroBonus,
+    "冰元素伤害加成" => CryoBonus,
+    "风元素伤害加成" => AnemoBonus,
+    "岩元素伤害加成" => GeoBonus,
+    "草元素伤害加成" => DendroBonus,
+    "物理伤害加成" => PhysicalBonus,
+}
@@ -1,7 +1,3 @@
-#![feature(coroutines, coroutine_trait)]
-#![feature(fn_traits)]
-#![feature(stmt_expr_attributes)]
-
 pub mod scanner_controller;
 pub mod export;
 pub mod scanner;
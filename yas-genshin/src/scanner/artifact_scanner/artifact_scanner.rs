@@ -1,14 +1,17 @@
-use std::{cell::RefCell, ops::{Coroutine, CoroutineState}, pin::Pin, rc::Rc, sync::mpsc::{self, Sender}, time::SystemTime};
+use std::{cell::RefCell, rc::Rc, sync::{Arc, mpsc::{self, Sender}}, time::SystemTime};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::FromArgMatches;
 use image::RgbImage;
 use log::{error, info};
 
-use yas::capture::{Capturer, GenericCapturer};
+use yas::capture::{Capturer, CapturerConfig, FrameSnapshot, ReplayCapturer};
+use yas::dump::{DumpConfig, Dumper};
 use yas::game_info::GameInfo;
-use yas::ocr::{ImageToText, yas_ocr_model};
+use yas::ocr::{ImageToText, OCRModelConfig, yas_ocr_model};
 use yas::positioning::Pos;
+use yas::scanner_controller::repository_layout::{GridScanGenerator, GridScanProgress, ScanCheckpointConfig};
+use yas::system_control::{ReplayControl, SystemControl};
 use yas::window_info::FromWindowInfoRepository;
 use yas::window_info::WindowInfoRepository;
 
@@ -17,8 +20,10 @@ use crate::scanner::artifact_scanner::message_items::SendItem;
 use crate::scanner::artifact_scanner::scan_result::GenshinArtifactScanResult;
 use crate::scanner_controller::repository_layout::{
     GenshinRepositoryScanController,
+    GenshinRepositoryScanControllerWindowInfo,
     GenshinRepositoryScannerLogicConfig,
     ReturnResult as GenshinRepositoryControllerReturnResult,
+    ScanState,
 };
 
 use super::artifact_scanner_config::GenshinArtifactScannerConfig;
@@ -38,6 +43,11 @@ pub struct GenshinArtifactScanner {
     image_to_text: Box<dyn ImageToText<RgbImage> + Send>,
     controller: Rc<RefCell<GenshinRepositoryScanController>>,
     capturer: Rc<dyn Capturer<RgbImage>>,
+    // set when scanning recorded screenshots, the frames are advanced by the clicks and scrolls of the controller
+    replay: Option<Rc<ReplayCapturer>>,
+    dump_config: DumpConfig,
+    ocr_model_config: OCRModelConfig,
+    checkpoint_config: ScanCheckpointConfig,
 }
 
 impl GenshinArtifactScanner {
@@ -46,23 +56,50 @@ impl GenshinArtifactScanner {
 
 // constructor
 impl GenshinArtifactScanner {
-    fn get_image_to_text() -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
-        let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(
-            yas_ocr_model!("./models/model_training.onnx", "./models/index_2_word.json")?
-        );
+    fn get_image_to_text(ocr_model_config: &OCRModelConfig) -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
+        let model: Box<dyn ImageToText<RgbImage> + Send> = match ocr_model_config.load_model()? {
+            Some(model) => Box::new(model),
+            None => Box::new(
+                yas_ocr_model!("./models/model_training.onnx", "./models/index_2_word.json", "./models/manifest.json", &ocr_model_config.inference_options())?
+            ),
+        };
         Ok(model)
     }
 
-    fn get_capturer() -> Result<Rc<dyn Capturer<RgbImage>>> {
-        Ok(Rc::new(GenericCapturer::new()?))
+    fn get_controller(
+        window_info_repo: &WindowInfoRepository,
+        config: GenshinRepositoryScannerLogicConfig,
+        game_info: &GameInfo,
+        capturer: Rc<dyn Capturer<RgbImage>>,
+        system_control: Box<dyn SystemControl>,
+    ) -> Result<GenshinRepositoryScanController> {
+        let window_info = GenshinRepositoryScanControllerWindowInfo::from_window_info_repository(
+            game_info.window.to_rect_usize().size(),
+            game_info.ui,
+            game_info.platform,
+            window_info_repo,
+        )?;
+
+        Ok(GenshinRepositoryScanController::from_window_info(
+            window_info.to_artifact_layout(),
+            config,
+            game_info.clone(),
+            capturer,
+            system_control,
+        ))
     }
 
     pub fn new(
         window_info_repo: &WindowInfoRepository,
         config: GenshinArtifactScannerConfig,
         controller_config: GenshinRepositoryScannerLogicConfig,
+        capturer_config: CapturerConfig,
+        dump_config: DumpConfig,
+        ocr_model_config: OCRModelConfig,
+        checkpoint_config: ScanCheckpointConfig,
         game_info: GameInfo,
     ) -> Result<Self> {
+        let (capturer, replay) = capturer_config.build()?;
         Ok(Self {
             scanner_config: config,
             window_info: ArtifactScannerWindowInfo::from_window_info_repository(
@@ -72,12 +109,16 @@ impl GenshinArtifactScanner {
                 window_info_repo,
             )?,
             controller: Rc::new(RefCell::new(
-                GenshinRepositoryScanController::new(window_info_repo, controller_config, game_info.clone(), true)?
+                Self::get_controller(window_info_repo, controller_config, &game_info, capturer.clone(), ReplayControl::or_generic(&replay))?
             )),
             game_info,
-            image_to_text: Self::get_image_to_text()?,
+            image_to_text: Self::get_image_to_text(&ocr_model_config)?,
             // item count will be set later, once the scan starts
-            capturer: Self::get_capturer()?,
+            capturer,
+            replay,
+            dump_config,
+            ocr_model_config,
+            checkpoint_config,
         })
     }
 
@@ -92,33 +133,45 @@ impl GenshinArtifactScanner {
             game_info.platform,
             window_info_repo,
         )?;
+        let (capturer, replay) = CapturerConfig::from_arg_matches(arg_matches)?.build()?;
+        let ocr_model_config = OCRModelConfig::from_arg_matches(arg_matches)?;
         Ok(GenshinArtifactScanner {
             scanner_config: GenshinArtifactScannerConfig::from_arg_matches(arg_matches)?,
             window_info,
             controller: Rc::new(RefCell::new(
-                GenshinRepositoryScanController::from_arg_matches(window_info_repo, arg_matches, game_info.clone(), true)?
+                Self::get_controller(
+                    window_info_repo,
+                    GenshinRepositoryScannerLogicConfig::from_arg_matches(arg_matches)?,
+                    &game_info,
+                    capturer.clone(),
+                    ReplayControl::or_generic(&replay),
+                )?
             )),
             game_info,
-            image_to_text: Self::get_image_to_text()?,
-            capturer: Self::get_capturer()?,
+            image_to_text: Self::get_image_to_text(&ocr_model_config)?,
+            capturer,
+            replay,
+            dump_config: DumpConfig::from_arg_matches(arg_matches)?,
+            ocr_model_config,
+            checkpoint_config: ScanCheckpointConfig::from_arg_matches(arg_matches)?,
         })
     }
 }
 
 impl GenshinArtifactScanner {
-    pub fn capture_panel(&self) -> Result<RgbImage> {
-        self.capturer.capture_relative_to(
+    pub fn capture_panel(&self, frame: &FrameSnapshot) -> Result<RgbImage> {
+        frame.capture_relative_to(
             self.window_info.panel_rect.to_rect_i32(),
             self.game_info.window.origin(),
         )
     }
 
-    pub fn get_star(&self) -> Result<usize> {
+    pub fn get_star(&self, frame: &FrameSnapshot) -> Result<usize> {
         let pos: Pos<i32> = Pos {
             x: self.game_info.window.left + self.window_info.star_pos.x as i32,
             y: self.game_info.window.top + self.window_info.star_pos.y as i32,
         };
-        let color = self.capturer.capture_color(pos)?;
+        let color = frame.capture_color(pos)?;
 
         let match_colors = [
             image::Rgb([113, 119, 139]),
@@ -178,15 +231,32 @@ impl GenshinArtifactScanner {
         let (tx, rx) = mpsc::channel::<Option<SendItem>>();
         // let token = self.cancellation_token.clone();
         let count = self.get_item_count()?;
-        let worker = ArtifactScannerWorker::new(
-            self.window_info.clone(),
-            self.scanner_config.clone(),
-        )?;
-
-        let join_handle = worker.run(rx);
+        let checkpoint = self.checkpoint_config.load::<GenshinArtifactScanResult>()?;
+        if checkpoint.is_some() && self.replay.is_some() {
+            return Err(anyhow!("回放截图时无法继续扫描"));
+        }
+        let mut generator = match &checkpoint {
+            Some(checkpoint) => GenshinRepositoryScanController::get_generator_from_progress(self.controller.clone(), count as usize, &checkpoint.progress)?,
+            None => GenshinRepositoryScanController::get_generator(self.controller.clone(), count as usize),
+        };
+        let dumper = Dumper::from_config(&self.dump_config)?
+            // a resumed scan goes on after the dumps of the previous one
+            .map(|d| Arc::new(d.with_first_index(generator.start_index())));
+        let ocr_cache = self.ocr_model_config.ocr_cache();
+        // one worker per recognition thread, each loads its own model
+        let workers = (0..self.ocr_model_config.worker_count())
+            .map(|_| ArtifactScannerWorker::new(
+                self.window_info.clone(),
+                dumper.clone(),
+                &self.ocr_model_config,
+                ocr_cache.as_ref(),
+            ))
+            .collect::<Result<Vec<_>>>()?;
+
+        let join_handle = ArtifactScannerWorker::run(workers, rx, &self.scanner_config, self.window_info.col as usize);
         info!("Worker created");
 
-        self.send(&tx, count);
+        let progress = self.send(&tx, &mut generator);
 
         match tx.send(None) {
             Ok(_) => info!("扫描结束，等待识别线程结束，请勿关闭程序"),
@@ -194,12 +264,28 @@ impl GenshinArtifactScanner {
         }
 
         match join_handle.join() {
-            Ok(v) => {
+            Ok(recognition) => {
                 info!("识别耗时: {:?}", now.elapsed()?);
+                if let Some(ocr_cache) = &ocr_cache {
+                    info!("OCR 缓存：{}", ocr_cache.stats());
+                }
+
+                let progress = match recognition.failed_index {
+                    // the items after the failed one were not recognized, even if they were sent
+                    Some(index) => Some(generator.progress_at(generator.start_index() + index)),
+                    None => progress,
+                };
+                // results of the scan resumed from
+                let mut results = checkpoint.map(|c| c.results).unwrap_or_default();
+                results.extend(recognition.items);
+                // a replay can't be resumed, as it goes through the recording from the start
+                if self.replay.is_none() {
+                    self.checkpoint_config.update(progress, &results);
+                }
 
                 // filter min level
                 let min_level = self.scanner_config.min_level;
-                let v = v.iter().filter(|a| {
+                let v = results.iter().filter(|a| {
                     a.level >= min_level
                 }).cloned().collect();
 
@@ -209,115 +295,103 @@ impl GenshinArtifactScanner {
         }
     }
 
-    fn is_page_first_artifact(&self, cur_index: i32) -> bool {
-        let col = self.window_info.col;
-        let row = self.window_info.row;
+    /// Capture the panel, star and (on the first item of a page) the item list of the current item.
+    /// The list starts at the row and col of the page in `list_start`
+    fn capture_item(&self, list_start: Option<(usize, usize)>) -> Result<SendItem> {
+        // grab the window once, everything of the item is read from this frame
+        let frame = FrameSnapshot::capture(self.capturer.as_ref(), self.game_info.window)?;
+        let image = self.capture_panel(&frame)?;
+        let star = self.get_star(&frame)?;
+
+        let list_image = if let Some((start_row, _)) = list_start {
+            let origin = self.game_info.window;
+            let margin = self.window_info.scan_margin_pos;
+            let gap = self.window_info.item_gap_size;
+            let size = self.window_info.item_size;
+
+            let left = (origin.left as f64 + margin.x) as i32;
+            let top = (origin.top as f64
+                + margin.y
+                + (gap.height + size.height)
+                * start_row as f64)
+                as i32;
+            let width = (origin.width as f64 - margin.x) as i32;
+            let height = (origin.height as f64
+                - margin.y
+                - (gap.height + size.height)
+                * start_row as f64)
+                as i32;
+
+            let game_image = frame
+                .capture_rect(yas::positioning::Rect {
+                    left,
+                    top,
+                    width,
+                    height,
+                })?;
+            Some(game_image)
+        } else {
+            None
+        };
 
-        let page_size = col * row;
-        return cur_index % page_size == 0;
+        Ok(SendItem {
+            panel_image: image,
+            star,
+            list_image,
+            list_first_col: list_start.map(|(_, col)| col).unwrap_or(0),
+        })
     }
 
-    /// Get the starting row in the page where `cur_index` is in
-    /// max count: total count
-    /// cur_index: current item index (starting from 0)
-    fn get_start_row(&self, max_count: i32, cur_index: i32) -> i32 {
-        let col = self.window_info.col;
-        let row = self.window_info.row;
-
-        let page_size = col * row;
-        if max_count - cur_index >= page_size {
-            return 0;
-        } else {
-            let remain = max_count - cur_index;
-            let remain_row = (remain + col - 1) / col;
-            let scroll_row = remain_row.min(row);
-            return row - scroll_row;
+    /// Send an item to the worker, returns false if the scan should stop
+    fn send_item(&self, tx: &Sender<Option<SendItem>>, item: SendItem) -> bool {
+        // todo normalize types
+        if (item.star as i32) < self.scanner_config.min_star {
+            info!(
+                "找到满足最低星级要求 {} 的物品，准备退出……",
+                self.scanner_config.min_star
+            );
+            return false;
         }
-    }
 
-    fn send(&mut self, tx: &Sender<Option<SendItem>>, count: i32) {
-        let mut generator = GenshinRepositoryScanController::get_generator(self.controller.clone(), count as usize);
-        let mut artifact_index: i32 = 0;
+        tx.send(Some(item)).is_ok()
+    }
 
+    /// Click through the items and send them to the worker, returns the progress if the scan stops early
+    fn send(&mut self, tx: &Sender<Option<SendItem>>, generator: &mut GridScanGenerator<GenshinRepositoryScanControllerWindowInfo>) -> Option<GridScanProgress> {
         loop {
-            let pinned_generator = Pin::new(&mut generator);
-            match pinned_generator.resume(()) {
-                CoroutineState::Yielded(_) => {
-                    let image = self.capture_panel().unwrap();
-                    let star = self.get_star().unwrap();
-
-                    let list_image = if self.is_page_first_artifact(artifact_index) {
-                        let origin = self.game_info.window;
-                        let margin = self.window_info.scan_margin_pos;
-                        let gap = self.window_info.item_gap_size;
-                        let size = self.window_info.item_size;
-
-                        let left = (origin.left as f64 + margin.x) as i32;
-                        let top = (origin.top as f64
-                            + margin.y
-                            + (gap.height + size.height)
-                            * self.get_start_row(count, artifact_index) as f64)
-                            as i32;
-                        let width = (origin.width as f64 - margin.x) as i32;
-                        let height = (origin.height as f64
-                            - margin.y
-                            - (gap.height + size.height)
-                            * self.get_start_row(count, artifact_index) as f64)
-                            as i32;
-
-                        let game_image = self
-                            .capturer
-                            .capture_rect(yas::positioning::Rect {
-                                left,
-                                top,
-                                width,
-                                height,
-                            })
-                            .unwrap();
-                        Some(game_image)
-                    } else {
-                        None
+            match generator.resume() {
+                ScanState::Yielded => {
+                    let grid_item = generator.item();
+                    let list_start = grid_item.is_page_first.then_some((grid_item.row, grid_item.col));
+                    let item = match self.capture_item(list_start) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            error!("截图失败：{}", e);
+                            // the item is scanned again when resumed
+                            return Some(generator.progress_retrying_item());
+                        },
                     };
 
-
-                    artifact_index = artifact_index + 1;
-
-                    // todo normalize types
-                    if (star as i32) < self.scanner_config.min_star {
-                        info!(
-                            "找到满足最低星级要求 {} 的物品，准备退出……",
-                            self.scanner_config.min_star
-                        );
-                        break;
-                    }
-
-                    if tx
-                        .send(Some(SendItem {
-                            panel_image: image,
-                            star,
-                            list_image,
-                        }))
-                        .is_err()
-                    {
+                    if !self.send_item(tx, item) {
                         break;
                     }
-
-                    // scanned_count += 1;
                 }
-                CoroutineState::Complete(result) => {
+                ScanState::Complete(result) => {
                     match result {
                         Err(e) => error!("扫描发生错误：{}", e),
                         Ok(value) => {
                             match value {
                                 GenshinRepositoryControllerReturnResult::Interrupted => info!("用户中断"),
-                                GenshinRepositoryControllerReturnResult::Finished => ()
+                                GenshinRepositoryControllerReturnResult::Finished => return None,
                             }
                         }
                     }
 
-                    break;
+                    return Some(generator.progress());
                 }
             }
         }
+
+        None
     }
-}
\ No newline at end of file
+}
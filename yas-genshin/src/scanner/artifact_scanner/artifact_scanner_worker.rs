@@ -1,17 +1,21 @@
-use std::collections::HashSet;
+use std::sync::Arc;
 use std::sync::mpsc::Receiver;
 use std::thread::JoinHandle;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use image::Rgb;
 use image::{GenericImageView, RgbImage};
-use log::{error, info, warn};
+use log::{info, warn};
+use lazy_static::lazy_static;
 
-use yas::ocr::ImageToText;
+use yas::dump::{Dumper, ItemDump};
+use yas::ocr::{FieldConfidence, FieldLexicons, ImageToText, OCRCache, OCRModelConfig, LOW_CONFIDENCE_THRESHOLD};
 use yas::ocr::yas_ocr_model;
 use yas::positioning::{Pos, Rect};
 use yas::utils::color_distance;
+use yas::worker_pool::{spawn_recognition, RecognitionConfig, RecognitionResult, Recognized};
 
+use crate::artifact::{ARTIFACT_NAMES_ZH_CN, ARTIFACT_STAT_NAMES_ZH_CN};
 use crate::scanner::artifact_scanner::artifact_scanner_window_info::ArtifactScannerWindowInfo;
 use crate::scanner::artifact_scanner::GenshinArtifactScannerConfig;
 use crate::scanner::artifact_scanner::message_items::SendItem;
@@ -29,63 +33,115 @@ fn parse_level(s: &str) -> Result<i32> {
     anyhow::Ok(level)
 }
 
-fn get_image_to_text() -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
-    let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(
-        yas_ocr_model!("./models/model_training.onnx", "./models/index_2_word.json")?
-    );
-    Ok(model)
+lazy_static! {
+    static ref FIELD_LEXICONS: FieldLexicons = FieldLexicons::new()
+        .with(&["title_rect"], ARTIFACT_NAMES_ZH_CN)
+        .with(&["main_stat_name_rect"], ARTIFACT_STAT_NAMES_ZH_CN);
+}
+
+/// An item recognized by one of the recognition threads.
+/// The lock state of an item comes from the list image of its page, so it's set once the items are back in order
+struct RecognizedItem {
+    result: Result<GenshinArtifactScanResult>,
+    /// Lock states of the page, if the item is the first of a page
+    page_locks: Option<Vec<bool>>,
+    dump: Option<ItemDump>,
 }
 
 /// run in a separate thread, accept captured image and get an artifact
 pub struct ArtifactScannerWorker {
     model: Box<dyn ImageToText<RgbImage> + Send>,
     window_info: ArtifactScannerWindowInfo,
-    config: GenshinArtifactScannerConfig,
+    dumper: Option<Arc<Dumper>>,
 }
 
 impl ArtifactScannerWorker {
     pub fn new(
         window_info: ArtifactScannerWindowInfo,
-        config: GenshinArtifactScannerConfig,
+        dumper: Option<Arc<Dumper>>,
+        ocr_model_config: &OCRModelConfig,
+        ocr_cache: Option<&Arc<OCRCache>>,
     ) -> Result<Self> {
+        let model = ocr_model_config.image_to_text(ocr_cache, |options| {
+            yas_ocr_model!("./models/model_training.onnx", "./models/index_2_word.json", "./models/manifest.json", options)
+        })?;
+
         Ok(ArtifactScannerWorker {
-            model: get_image_to_text()?,
+            model,
             window_info,
-            config,
+            dumper,
         })
     }
 
-    /// the captured_img is a panel of the artifact, the rect is a region of the panel
-    fn model_inference(&self, rect: Rect<f64>, captured_img: &RgbImage) -> Result<String> {
-        let relative_rect = rect.translate(Pos {
-            x: -self.window_info.panel_rect.left,
-            y: -self.window_info.panel_rect.top,
-        });
-
-        let raw_img = captured_img.view(
-            relative_rect.left as u32, relative_rect.top as u32, relative_rect.width as u32, relative_rect.height as u32,
-        ).to_image();
+    /// the captured_img is a panel of the artifact, the rects are regions of the panel, which are recognized in one batch
+    /// the names are used for the cropped images and the raw strings in dump mode, and as the keys of the confidence
+    fn model_inference<const N: usize>(&self, regions: [(&str, Rect<f64>); N], captured_img: &RgbImage, dump: Option<&ItemDump>) -> Result<([String; N], FieldConfidence)> {
+        let images = regions.iter()
+            .map(|&(_, rect)| {
+                let relative_rect = rect.translate(Pos {
+                    x: -self.window_info.panel_rect.left,
+                    y: -self.window_info.panel_rect.top,
+                });
+
+                captured_img.view(
+                    relative_rect.left as u32, relative_rect.top as u32, relative_rect.width as u32, relative_rect.height as u32,
+                ).to_image()
+            })
+            .collect::<Vec<_>>();
+
+        let lexicons = regions.iter().map(|&(name, _)| FIELD_LEXICONS.get(name)).collect::<Vec<_>>();
+        let inference_result = self.model.image_to_text_batch_with_lexicon(&images, false, &lexicons)?;
+
+        let mut confidence = FieldConfidence::new();
+        for ((name, _), result) in regions.iter().zip(inference_result.iter()) {
+            confidence.insert(name, result.confidence);
+        }
+        for (name, c) in confidence.low_confidence_fields(LOW_CONFIDENCE_THRESHOLD) {
+            warn!("{} 识别置信度较低：{:.2}", name, c);
+        }
 
-        let inference_result = self.model.image_to_text(&raw_img, false);
+        if let Some(dump) = dump {
+            for (((name, _), raw_img), result) in regions.iter().zip(images.iter()).zip(inference_result.iter()) {
+                dump.record_ocr(name, raw_img, &result.text);
+            }
+        }
 
-        inference_result
+        let texts = inference_result.into_iter().map(|r| r.text).collect::<Vec<_>>();
+        let texts = texts.try_into().map_err(|_| anyhow!("OCR result count mismatch"))?;
+        Ok((texts, confidence))
     }
 
-    /// Parse the captured result (of type SendItem) to a scanned artifact
-    fn scan_item_image(&self, item: SendItem, lock: bool) -> Result<GenshinArtifactScanResult> {
+    /// Parse the captured result (of type SendItem) to a scanned artifact, the lock state is left unset
+    fn scan_item_image(&self, item: &SendItem, dump: Option<&ItemDump>) -> Result<GenshinArtifactScanResult> {
         let image = &item.panel_image;
+        if let Some(dump) = dump {
+            dump.save_image("panel", image);
+            if let Some(list_image) = item.list_image.as_ref() {
+                dump.save_image("list", list_image);
+            }
+        }
 
-        let str_title = self.model_inference(self.window_info.title_rect, image)?;
-        let str_main_stat_name = self.model_inference(self.window_info.main_stat_name_rect, image)?;
-        let str_main_stat_value = self.model_inference(self.window_info.main_stat_value_rect, image)?;
-
-        let str_sub_stat0 = self.model_inference(self.window_info.sub_stat_1, image)?;
-        let str_sub_stat1 = self.model_inference(self.window_info.sub_stat_2, image)?;
-        let str_sub_stat2 = self.model_inference(self.window_info.sub_stat_3, image)?;
-        let str_sub_stat3 = self.model_inference(self.window_info.sub_stat_4, image)?;
-
-        let str_level = self.model_inference(self.window_info.level_rect, image)?;
-        let str_equip = self.model_inference(self.window_info.item_equip_rect, image)?;
+        let ([
+            str_title,
+            str_main_stat_name,
+            str_main_stat_value,
+            str_sub_stat0,
+            str_sub_stat1,
+            str_sub_stat2,
+            str_sub_stat3,
+            str_level,
+            str_equip,
+        ], confidence) = self.model_inference([
+            ("title_rect", self.window_info.title_rect),
+            ("main_stat_name_rect", self.window_info.main_stat_name_rect),
+            ("main_stat_value_rect", self.window_info.main_stat_value_rect),
+            ("sub_stat_1", self.window_info.sub_stat_1),
+            ("sub_stat_2", self.window_info.sub_stat_2),
+            ("sub_stat_3", self.window_info.sub_stat_3),
+            ("sub_stat_4", self.window_info.sub_stat_4),
+            ("level_rect", self.window_info.level_rect),
+            ("item_equip_rect", self.window_info.item_equip_rect),
+        ], image, dump)?;
 
         anyhow::Ok(GenshinArtifactScanResult {
             name: str_title,
@@ -100,12 +156,13 @@ impl ArtifactScannerWorker {
             level: parse_level(&str_level)?,
             equip: str_equip,
             star: item.star as i32,
-            lock,
+            lock: false,
+            confidence,
         })
     }
 
-    /// Get all lock state from a list image
-    fn get_page_locks(&self, list_image: &RgbImage) -> Vec<bool> {
+    /// Get all lock state from a list image, from the item at `first_col` of the first row
+    fn get_page_locks(&self, list_image: &RgbImage, first_col: usize) -> Vec<bool> {
         let mut result = Vec::new();
 
         let row = self.window_info.row;
@@ -119,6 +176,10 @@ impl ArtifactScannerWorker {
                 break;
             }
             for c in 0..col {
+                if r == 0 && (c as usize) < first_col {
+                    continue;
+                }
+
                 let pos_x = (gap.width + size.width) * (c as f64) + lock_pos.x;
                 let pos_y = (gap.height + size.height) * (r as f64) + lock_pos.y;
 
@@ -144,87 +205,60 @@ impl ArtifactScannerWorker {
         result
     }
 
-    pub fn run(self, rx: Receiver<Option<SendItem>>) -> JoinHandle<Vec<GenshinArtifactScanResult>> {
-        std::thread::spawn(move || {
-            let mut results = Vec::new();
-            let mut hash: HashSet<GenshinArtifactScanResult> = HashSet::new();
-            // if too many artifacts are same in consecutive, then an error has occurred
-            let mut consecutive_dup_count = 0;
-
-            let is_verbose = self.config.verbose;
-            let min_level = self.config.min_level;
-            let info = self.window_info.clone();
-            // todo remove dump mode to another scanner
-            // let dump_mode = false;
-            // let model = self.model.clone();
-            // let panel_origin = Pos { x: self.window_info.panel_rect.left, y: self.window_info.panel_rect.top };
-
-            let mut locks = Vec::new();
-            let mut artifact_index: i32 = 0;
-
-            for item in rx.into_iter() {
-                // receiving None, which means the worker should end
-                let item = match item {
-                    Some(v) => v,
-                    None => break,
-                };
+    fn recognize(&self, index: usize, item: SendItem) -> RecognizedItem {
+        let dump = self.dumper.as_ref().map(|d| d.item(index));
+        let result = self.scan_item_image(&item, dump.as_ref());
 
+        RecognizedItem {
+            result,
+            page_locks: item.list_image.as_ref().map(|v| self.get_page_locks(v, item.list_first_col)),
+            dump,
+        }
+    }
+
+    pub fn run(workers: Vec<Self>, rx: Receiver<Option<SendItem>>, config: &GenshinArtifactScannerConfig, col: usize) -> JoinHandle<RecognitionResult<GenshinArtifactScanResult>> {
+        let min_level = config.min_level;
+        let mut locks = Vec::new();
+
+        spawn_recognition(
+            workers.into_iter()
+                .map(|worker| move |index, item| worker.recognize(index, item))
+                .collect(),
+            // receiving None, which means the worker should end
+            rx.into_iter().map_while(|item| item),
+            RecognitionConfig {
+                verbose: config.verbose,
+                // if too many artifacts are same in consecutive, then an error has occurred
+                max_consecutive_dup: (!config.ignore_dup).then_some(col),
+            },
+            move |artifact_index, item: RecognizedItem| {
                 // if there is a list image, then parse the lock state
-                match item.list_image.as_ref() {
-                    Some(v) => {
-                        locks = vec![locks, self.get_page_locks(v)].concat()
-                    }
-                    None => {}
-                };
-
-                artifact_index += 1;
-                let result = match self.scan_item_image(item, locks[artifact_index as usize - 1]) {
-                    Ok(v) => v,
-                    Err(e) => {
-                        error!("识别错误: {}", e);
-                        continue;
-                    }
-                };
+                if let Some(page_locks) = item.page_locks {
+                    locks.extend(page_locks);
+                }
 
-                if is_verbose {
-                    info!("{:?}", result);
+                let result = item.result.map(|mut v| {
+                    v.lock = locks.get(artifact_index).copied().unwrap_or_else(|| {
+                        warn!("第 {} 个圣遗物没有锁定状态，视为未锁定", artifact_index);
+                        false
+                    });
+                    v
+                });
+                if let Some(dump) = item.dump {
+                    dump.finish(result.as_ref());
                 }
 
+                let result = result?;
                 if result.level < min_level {
                     info!(
                         "找到满足最低等级要求 {} 的物品({})，准备退出……",
                         min_level, result.level
                     );
-                    break;
-                }
-
-                if hash.contains(&result) {
-                    consecutive_dup_count += 1;
-                    warn!("识别到重复物品: {:#?}", result);
-                } else {
-                    consecutive_dup_count = 0;
-                    hash.insert(result.clone());
-                    results.push(result);
+                    return Ok(Recognized::Stop);
                 }
 
-                if consecutive_dup_count >= info.col && !self.config.ignore_dup {
-                    error!("识别到连续多个重复物品，可能为翻页错误，或者为非背包顶部开始扫描");
-                    // token.cancel();
-                    break;
-                }
-
-                // if token.cancelled() {
-                // error!("扫描任务被取消");
-                // break;
-                // }
-            }
-
-            info!("识别结束，非重复物品数量: {}", hash.len());
-
-            // progress_bar.finish();
-            // MULTI_PROGRESS.remove(&progress_bar);
-
-            results
-        })
+                Ok(Recognized::Item(result))
+            },
+        )
     }
 }
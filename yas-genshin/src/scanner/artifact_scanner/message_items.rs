@@ -5,4 +5,6 @@ pub struct SendItem {
     pub panel_image: RgbImage,
     pub star: usize,
     pub list_image: Option<RgbImage>,
+    /// Column of the item in the first row of the list image, the items before it were scanned before resuming
+    pub list_first_col: usize,
 }
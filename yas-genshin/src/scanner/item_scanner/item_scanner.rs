@@ -35,11 +35,6 @@ impl ItemScannerWorker {
     }
 
     fn model_inference(&self, pos: Rect, captured_img: &RgbImage) -> Result<String> {
-        // todo move dump mode into a scanner
-        // if dump_mode {
-            // captured_img.save(Path::new("dumps").join(format!("{}_{}.rgb.png", name, cnt)))?;
-        // }
-
         let relative_rect = pos.translate(Pos {
             x: -self.window_info.panel_pos.left,
             y: -self.window_info.panel_pos.top,
@@ -1,6 +1,7 @@
 pub use artifact_scanner::GenshinArtifactScanner;
 pub use artifact_scanner::GenshinArtifactScannerConfig;
 pub use artifact_scanner::GenshinArtifactScanResult;
+pub use artifact_scanner::ArtifactScannerWindowInfo;
 
 mod artifact_scanner;
 // mod item_scanner;
@@ -1,11 +1,6 @@
-pub use config::GenshinRepositoryScannerLogicConfig;
 pub use controller::GenshinRepositoryScanController;
-pub use controller::ReturnResult;
-pub use scroll_result::ScrollResult;
 pub use window_info::GenshinRepositoryScanControllerWindowInfo;
+pub use yas::scanner_controller::repository_layout::{GridScanControllerConfig as GenshinRepositoryScannerLogicConfig, ReturnResult, ScanState, ScrollResult};
 
-mod config;
 mod controller;
-
-mod scroll_result;
 mod window_info;
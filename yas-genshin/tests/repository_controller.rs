@@ -0,0 +1,114 @@
+//! The generic behaviour of the grid controller is tested in yas, these check the layouts of the genshin window info
+
+use yas::game_info::{GameInfo, Platform, ResolutionFamily, UI};
+use yas::positioning::{Pos, Rect, Size};
+use yas::simulation::{all_items, SimulatedInventory};
+use yas::window_info::{load_window_info_repo, FromWindowInfoRepository, WindowInfoRepository};
+use yas_scanner_genshin::scanner::ArtifactScannerWindowInfo;
+use yas_scanner_genshin::scanner_controller::repository_layout::{
+    GenshinRepositoryScanControllerWindowInfo, GenshinRepositoryScannerLogicConfig, ReturnResult,
+};
+
+const WINDOW_SIZE: Size<usize> = Size { width: 1600, height: 900 };
+
+fn window_info_repo() -> WindowInfoRepository {
+    load_window_info_repo!("../window_info/windows1600x900.json")
+}
+
+fn game_info() -> GameInfo {
+    GameInfo {
+        window: Rect::new(100, 50, WINDOW_SIZE.width as i32, WINDOW_SIZE.height as i32),
+        resolution_family: ResolutionFamily::Windows16x9,
+        is_cloud: false,
+        ui: UI::Desktop,
+        platform: Platform::Windows,
+    }
+}
+
+fn config() -> GenshinRepositoryScannerLogicConfig {
+    GenshinRepositoryScannerLogicConfig {
+        scroll_delay: 0,
+        max_wait_switch_item: 200,
+        ..Default::default()
+    }
+}
+
+fn window_info(is_artifact: bool) -> GenshinRepositoryScanControllerWindowInfo {
+    let info = GenshinRepositoryScanControllerWindowInfo::from_window_info_repository(
+        WINDOW_SIZE, UI::Desktop, Platform::Windows, &window_info_repo(),
+    ).unwrap();
+
+    if is_artifact { info.to_artifact_layout() } else { info }
+}
+
+/// Scan an inventory laid out as the controller expects, returns the item selected at each yield
+fn scan(is_artifact: bool, item_count: usize, scroll_per_wheel: f64) -> (Vec<Option<usize>>, anyhow::Result<ReturnResult>) {
+    let window_info = window_info(is_artifact);
+    let inventory = SimulatedInventory::from_window_info(&window_info, &game_info(), item_count, scroll_per_wheel);
+    inventory.set_switch_delay(2);
+
+    let controller = inventory.controller(window_info, config(), game_info());
+    inventory.scan(controller, item_count)
+}
+
+#[test]
+fn scans_every_item_in_order() {
+    // 16 rows, the last page is estimated from the earlier scrolls
+    let (selected, result) = scan(false, 123, 13.0);
+    assert!(matches!(result, Ok(ReturnResult::Finished)));
+    assert_eq!(selected, all_items(123));
+}
+
+#[test]
+fn scans_artifact_layout() {
+    let (selected, result) = scan(true, 90, 17.0);
+    assert!(matches!(result, Ok(ReturnResult::Finished)));
+    assert_eq!(selected, all_items(90));
+}
+
+/// 32:9 is not recorded, the layout is derived from the anchored 21:9 one
+#[test]
+fn derives_the_layout_of_a_32x9_window() {
+    let repo = load_window_info_repo!(
+        "../window_info/windows1600x900.json",
+        "../window_info/windows2100x900.json",
+        "../window_info/windows3440x1440.json",
+    );
+    let window_size = Size::new(5120, 1440);
+
+    let info = GenshinRepositoryScanControllerWindowInfo::from_window_info_repository(
+        window_size, UI::Desktop, Platform::Windows, &repo,
+    ).unwrap();
+    // the panel sticks to the right edge, the items to the left
+    assert_eq!(info.panel_rect.left, 5120.0 - (3440.0 - 2528.0));
+    assert_eq!(info.scan_margin_pos, Pos::new(305.0, 161.0));
+
+    let artifact = ArtifactScannerWindowInfo::from_window_info_repository(
+        window_size, UI::Desktop, Platform::Windows, &repo,
+    ).unwrap();
+    assert!(artifact.title_rect.left > info.panel_rect.left);
+}
+
+/// 2560x1080 (64:27) is in no resolution family, its layout is derived from the closest ratio, 43:18
+#[test]
+fn derives_the_layout_of_a_2560x1080_window() {
+    let repo = load_window_info_repo!(
+        "../window_info/windows1600x900.json",
+        "../window_info/windows2100x900.json",
+        "../window_info/windows3440x1440.json",
+    );
+    let window_size = Size::new(2560, 1080);
+    assert_eq!(ResolutionFamily::new_or_closest(window_size), ResolutionFamily::Windows43x18);
+
+    let info = GenshinRepositoryScanControllerWindowInfo::from_window_info_repository(
+        window_size, UI::Desktop, Platform::Windows, &repo,
+    ).unwrap();
+    // scaled to fit the width, which is relatively narrower than 43:18
+    let scale = 2560.0 / 3440.0;
+    assert!((info.panel_rect.left - (2560.0 - (3440.0 - 2528.0) * scale)).abs() < 1e-6);
+    assert!((info.scan_margin_pos.x - 305.0 * scale).abs() < 1e-6);
+
+    ArtifactScannerWindowInfo::from_window_info_repository(
+        window_size, UI::Desktop, Platform::Windows, &repo,
+    ).unwrap();
+}
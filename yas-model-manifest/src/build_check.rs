@@ -0,0 +1,37 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+
+use crate::ModelManifest;
+
+const SKIP_ENV: &str = "YAS_SKIP_MODEL_CHECK";
+
+fn check(dir: &Path) -> Result<()> {
+    let read = |name: &str| std::fs::read(dir.join(name))
+        .map_err(|e| anyhow!("无法读取 {}：{}", dir.join(name).display(), e));
+
+    let model = read("model_training.onnx")?;
+    let dict = String::from_utf8(read("index_2_word.json")?)?;
+    let manifest = ModelManifest::load(dir.join("manifest.json"))?;
+
+    manifest.verify(&model, &dict)
+}
+
+/// Called from build scripts, checks `model_training.onnx` and `index_2_word.json` in the directory against `manifest.json`,
+/// so that a missing `git lfs pull` fails the build instead of producing a binary with a broken model.
+/// If `YAS_SKIP_MODEL_CHECK` is set, only a warning is emitted, e.g. for linting without the models
+pub fn check_model_dir<P: AsRef<Path>>(dir: P) {
+    let dir = dir.as_ref();
+    for name in ["model_training.onnx", "index_2_word.json", "manifest.json"] {
+        println!("cargo:rerun-if-changed={}", dir.join(name).display());
+    }
+    println!("cargo:rerun-if-env-changed={}", SKIP_ENV);
+
+    if let Err(e) = check(dir) {
+        if std::env::var_os(SKIP_ENV).is_some() {
+            println!("cargo:warning={}: {}", dir.display(), e);
+        } else {
+            panic!("{}: {}\n（设置环境变量 {} 可跳过该检查，但生成的程序无法正常识别）", dir.display(), e, SKIP_ENV);
+        }
+    }
+}
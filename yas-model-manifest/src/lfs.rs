@@ -0,0 +1,30 @@
+const LFS_POINTER_HEADER: &[u8] = b"version https://git-lfs.github.com/spec/v1";
+
+/// A Git LFS pointer file, which is checked out in place of the model when `git lfs pull` is not run
+pub struct LfsPointer {
+    pub oid: String,
+    pub size: usize,
+}
+
+impl LfsPointer {
+    /// Returns `None` if the data is not a pointer file
+    pub fn parse(data: &[u8]) -> Option<LfsPointer> {
+        // pointer files are tiny, a model is megabytes
+        if data.len() > 1024 || !data.starts_with(LFS_POINTER_HEADER) {
+            return None;
+        }
+
+        let text = std::str::from_utf8(data).ok()?;
+        let mut oid = String::new();
+        let mut size = 0;
+        for line in text.lines() {
+            if let Some(v) = line.strip_prefix("oid sha256:") {
+                oid = String::from(v.trim());
+            } else if let Some(v) = line.strip_prefix("size ") {
+                size = v.trim().parse().ok()?;
+            }
+        }
+
+        Some(LfsPointer { oid, size })
+    }
+}
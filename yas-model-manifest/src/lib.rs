@@ -0,0 +1,7 @@
+mod lfs;
+mod manifest;
+mod build_check;
+
+pub use lfs::LfsPointer;
+pub use manifest::{ModelManifest, dict_size, dict_words, sha256_hex};
+pub use build_check::check_model_dir;
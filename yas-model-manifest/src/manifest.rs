@@ -0,0 +1,107 @@
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::LfsPointer;
+
+/// Describes an OCR model and its dictionary, so that a broken checkout or a dictionary
+/// of another model is reported instead of silently giving wrong results
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelManifest {
+    /// sha256 of the onnx file, which is also the oid of its Git LFS pointer
+    pub sha256: String,
+    /// size of the onnx file in bytes
+    pub size: usize,
+    /// input shape of a single image, NCHW
+    pub input_shape: [usize; 4],
+    /// number of entries of the dictionary, which is the number of classes of the model output
+    pub dict_size: usize,
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Words of an `index_2_word.json` in the order of their index, the keys must be `0..n`
+pub fn dict_words(dict: &str) -> Result<Vec<String>> {
+    let json = serde_json::from_str::<serde_json::Value>(dict)?;
+    let object = json.as_object().ok_or(anyhow!("字典应为 JSON 对象"))?;
+
+    let mut words = object.iter()
+        .map(|(k, v)| {
+            let index = k.parse::<usize>().map_err(|_| anyhow!("字典的键 `{}` 不是数字", k))?;
+            let word = v.as_str().ok_or(anyhow!("字典中 `{}` 的值不是字符串", k))?;
+            Ok((index, String::from(word)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    words.sort_by_key(|&(index, _)| index);
+    if words.iter().enumerate().any(|(i, &(k, _))| i != k) {
+        bail!("字典的键应为从 0 开始的连续数字");
+    }
+
+    Ok(words.into_iter().map(|(_, word)| word).collect())
+}
+
+/// Number of entries of an `index_2_word.json`, the keys must be `0..n`
+pub fn dict_size(dict: &str) -> Result<usize> {
+    Ok(dict_words(dict)?.len())
+}
+
+impl ModelManifest {
+    pub fn from_json(s: &str) -> Result<ModelManifest> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ModelManifest> {
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("无法读取模型清单 {}：{}", path.display(), e))?;
+        Self::from_json(&s)
+    }
+
+    /// Describe a model, e.g. to write the manifest of a newly trained one
+    pub fn from_model(model: &[u8], dict: &str, input_shape: [usize; 4]) -> Result<ModelManifest> {
+        if LfsPointer::parse(model).is_some() {
+            bail!("模型文件是 Git LFS 指针文件");
+        }
+
+        Ok(ModelManifest {
+            sha256: sha256_hex(model),
+            size: model.len(),
+            input_shape,
+            dict_size: dict_size(dict)?,
+        })
+    }
+
+    /// Check the model file and its dictionary against the manifest
+    pub fn verify(&self, model: &[u8], dict: &str) -> Result<()> {
+        if let Some(pointer) = LfsPointer::parse(model) {
+            let hint = if pointer.oid == self.sha256 {
+                ""
+            } else {
+                "，且指针与模型清单不符"
+            };
+            bail!(
+                "OCR 模型文件是 Git LFS 指针文件（oid {}）而不是模型本身{}。请安装 Git LFS 并运行 `git lfs pull`",
+                pointer.oid, hint
+            );
+        }
+
+        if model.len() != self.size {
+            bail!("OCR 模型大小为 {} 字节，与模型清单中的 {} 字节不符", model.len(), self.size);
+        }
+        let sha256 = sha256_hex(model);
+        if sha256 != self.sha256 {
+            bail!("OCR 模型的 sha256 为 {}，与模型清单中的 {} 不符", sha256, self.sha256);
+        }
+
+        let dict_size = dict_size(dict)?;
+        if dict_size != self.dict_size {
+            bail!("字典大小为 {}，与模型清单中的 {} 不符，字典可能不属于该模型", dict_size, self.dict_size);
+        }
+
+        Ok(())
+    }
+}
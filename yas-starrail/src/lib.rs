@@ -1,5 +1,3 @@
-#![feature(coroutines, coroutine_trait)]
-#![feature(stmt_expr_attributes)]
 #![allow(unused_imports)]
 
 pub mod scanner_controller;
@@ -3,5 +3,7 @@ pub use relic::RelicSetName;
 pub use relic::RelicStat;
 pub use relic::RelicStatName;
 pub use relic::StarRailRelic;
+pub use zh_cn::{RELIC_NAMES_ZH_CN, RELIC_STAT_NAMES_ZH_CN};
 
 mod relic;
+mod zh_cn;
@@ -0,0 +1,191 @@
+use crate::relic::{RelicSetName, RelicSlot, RelicStatName};
+
+/// Lists the names for the lexicon of the relic panel, and parses them in `from_zh_cn`
+macro_rules! relic_names_zh_cn {
+    ($($name:literal => ($set:ident, $slot:ident),)*) => {
+        /// Names of all relic pieces, which are the titles of the relic panel
+        pub const RELIC_NAMES_ZH_CN: &[&str] = &[$($name),*];
+
+        impl RelicSetName {
+            pub fn from_zh_cn(s: &str) -> Option<RelicSetName> {
+                match s {
+                    $($name => Some(RelicSetName::$set),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl RelicSlot {
+            pub fn from_zh_cn(s: &str) -> Option<RelicSlot> {
+                match s {
+                    $($name => Some(RelicSlot::$slot),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+/// Same for the stats, `flat / percentage` for a name shared by both
+macro_rules! relic_stat_names_zh_cn {
+    (@stat $is_percentage:ident, $stat:ident) => {
+        RelicStatName::$stat
+    };
+    (@stat $is_percentage:ident, $stat:ident / $percentage:ident) => {
+        if $is_percentage { RelicStatName::$percentage } else { RelicStatName::$stat }
+    };
+    ($($name:literal => $stat:ident $(/ $percentage:ident)?,)*) => {
+        /// Names of all relic stats, without the value
+        pub const RELIC_STAT_NAMES_ZH_CN: &[&str] = &[$($name),*];
+
+        impl RelicStatName {
+            pub fn from_zh_cn(name: &str, is_percentage: bool) -> Option<RelicStatName> {
+                match name {
+                    $($name => Some(relic_stat_names_zh_cn!(@stat is_percentage, $stat $(/ $percentage)?)),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+// https://github.com/Mar-7th/StarRailRes/blob/master/index_new/cn/relics.json
+relic_names_zh_cn! {
+    "过客的逢春木簪" => (PasserbyofWanderingCloud, Head),
+    "过客的游龙臂鞲" => (PasserbyofWanderingCloud, Hands),
+    "过客的残绣风衣" => (PasserbyofWanderingCloud, Body),
+    "过客的冥途游履" => (PasserbyofWanderingCloud, Feet),
+    "快枪手的野穗毡帽" => (MusketeerofWildWheat, Head),
+    "快枪手的粗革手套" => (MusketeerofWildWheat, Hands),
+    "快枪手的猎风披肩" => (MusketeerofWildWheat, Body),
+    "快枪手的铆钉马靴" => (MusketeerofWildWheat, Feet),
+    "圣骑的宽恕盔面" => (KnightofPurityPalace, Head),
+    "圣骑的沉默誓环" => (KnightofPurityPalace, Hands),
+    "圣骑的肃穆胸甲" => (KnightofPurityPalace, Body),
+    "圣骑的秩序铁靴" => (KnightofPurityPalace, Feet),
+    "雪猎的荒神兜帽" => (HunterofGlacialForest, Head),
+    "雪猎的巨蜥手套" => (HunterofGlacialForest, Hands),
+    "雪猎的冰龙披风" => (HunterofGlacialForest, Body),
+    "雪猎的鹿皮软靴" => (HunterofGlacialForest, Feet),
+    "拳王的冠军护头" => (ChampionofStreetwiseBoxing, Head),
+    "拳王的重炮拳套" => (ChampionofStreetwiseBoxing, Hands),
+    "拳王的贴身护胸" => (ChampionofStreetwiseBoxing, Body),
+    "拳王的弧步战靴" => (ChampionofStreetwiseBoxing, Feet),
+    "铁卫的铸铁面盔" => (GuardofWutheringSnow, Head),
+    "铁卫的银鳞手甲" => (GuardofWutheringSnow, Hands),
+    "铁卫的旧制军服" => (GuardofWutheringSnow, Body),
+    "铁卫的白银护胫" => (GuardofWutheringSnow, Feet),
+    "火匠的黑耀目镜" => (FiresmithofLavaForging, Head),
+    "火匠的御火戒指" => (FiresmithofLavaForging, Hands),
+    "火匠的阻燃围裙" => (FiresmithofLavaForging, Body),
+    "火匠的合金义肢" => (FiresmithofLavaForging, Feet),
+    "天才的超距遥感" => (GeniusofBrilliantStars, Head),
+    "天才的频变捕手" => (GeniusofBrilliantStars, Hands),
+    "天才的元域深潜" => (GeniusofBrilliantStars, Body),
+    "天才的引力漫步" => (GeniusofBrilliantStars, Feet),
+    "乐队的偏光墨镜" => (BandofSizzlingThunder, Head),
+    "乐队的巡演手绳" => (BandofSizzlingThunder, Hands),
+    "乐队的钉刺皮衣" => (BandofSizzlingThunder, Body),
+    "乐队的铆钉短靴" => (BandofSizzlingThunder, Feet),
+    "翔鹰的长喙头盔" => (EagleofTwilightLine, Head),
+    "翔鹰的鹰击指环" => (EagleofTwilightLine, Hands),
+    "翔鹰的翼装束带" => (EagleofTwilightLine, Body),
+    "翔鹰的绒羽绑带" => (EagleofTwilightLine, Feet),
+    "怪盗的千人假面" => (ThiefofShootingMeteor, Head),
+    "怪盗的绘纹手套" => (ThiefofShootingMeteor, Hands),
+    "怪盗的纤钢爪钩" => (ThiefofShootingMeteor, Body),
+    "怪盗的流星快靴" => (ThiefofShootingMeteor, Feet),
+    "废土客的呼吸面罩" => (WastelanderofBanditryDesert, Head),
+    "废土客的荒漠终端" => (WastelanderofBanditryDesert, Hands),
+    "废土客的修士长袍" => (WastelanderofBanditryDesert, Body),
+    "废土客的动力腿甲" => (WastelanderofBanditryDesert, Feet),
+    "莳者的复明义眼" => (LongevousDisciple, Head),
+    "莳者的机巧木手" => (LongevousDisciple, Hands),
+    "莳者的承露羽衣" => (LongevousDisciple, Body),
+    "莳者的天人丝履" => (LongevousDisciple, Feet),
+    "信使的全息目镜" => (MessengerTraversingHackerspace, Head),
+    "信使的百变义手" => (MessengerTraversingHackerspace, Hands),
+    "信使的密信挎包" => (MessengerTraversingHackerspace, Body),
+    "信使的酷跑板鞋" => (MessengerTraversingHackerspace, Feet),
+    "大公的冥焰冠冕" => (TheAshblazingGrandDuke, Head),
+    "大公的绒火指套" => (TheAshblazingGrandDuke, Hands),
+    "大公的蒙恩长袍" => (TheAshblazingGrandDuke, Body),
+    "大公的绅雅礼靴" => (TheAshblazingGrandDuke, Feet),
+    "系囚的合啮拘笼" => (PrisonerinDeepConfinement, Head),
+    "系囚的铅石梏铐" => (PrisonerinDeepConfinement, Hands),
+    "系囚的幽闭缚束" => (PrisonerinDeepConfinement, Body),
+    "系囚的绝足锁桎" => (PrisonerinDeepConfinement, Feet),
+    "先驱的绝热围壳" => (PioneerDiverofDeadWaters, Head),
+    "先驱的虚极罗盘" => (PioneerDiverofDeadWaters, Hands),
+    "先驱的密合铅衣" => (PioneerDiverofDeadWaters, Body),
+    "先驱的泊星桩锚" => (PioneerDiverofDeadWaters, Feet),
+    "钟表匠的极目透镜" => (WatchmakerMasterofDreamMachinations, Head),
+    "钟表匠的交运腕表" => (WatchmakerMasterofDreamMachinations, Hands),
+    "钟表匠的空幻礼服" => (WatchmakerMasterofDreamMachinations, Body),
+    "钟表匠的隐梦革履" => (WatchmakerMasterofDreamMachinations, Feet),
+    "铁骑的索敌战盔" => (IronCavalryAgainsttheScourge, Head),
+    "铁骑的摧坚铁腕" => (IronCavalryAgainsttheScourge, Hands),
+    "铁骑的银影装甲" => (IronCavalryAgainsttheScourge, Body),
+    "铁骑的行空护胫" => (IronCavalryAgainsttheScourge, Feet),
+    "勇烈的玄枵面甲" => (TheWindSoaringValorous, Head),
+    "勇烈的钩爪腕甲" => (TheWindSoaringValorous, Hands),
+    "勇烈的飞翎瓷甲" => (TheWindSoaringValorous, Body),
+    "勇烈的逐猎腿甲" => (TheWindSoaringValorous, Feet),
+    "「黑塔」的空间站点" => (SpaceSealingStation, PlanarSphere),
+    "「黑塔」的漫历轨迹" => (SpaceSealingStation, LinkRope),
+    "罗浮仙舟的天外楼船" => (FleetoftheAgeless, PlanarSphere),
+    "罗浮仙舟的建木枝蔓" => (FleetoftheAgeless, LinkRope),
+    "公司的巨构总部" => (PanCosmicCommercialEnterprise, PlanarSphere),
+    "公司的贸易航道" => (PanCosmicCommercialEnterprise, LinkRope),
+    "贝洛伯格的存护堡垒" => (BelobogoftheArchitects, PlanarSphere),
+    "贝洛伯格的铁卫防线" => (BelobogoftheArchitects, LinkRope),
+    "螺丝星的机械烈阳" => (CelestialDifferentiator, PlanarSphere),
+    "螺丝星的环星孔带" => (CelestialDifferentiator, LinkRope),
+    "萨尔索图的移动城市" => (InertSalsotto, PlanarSphere),
+    "萨尔索图的晨昏界线" => (InertSalsotto, LinkRope),
+    "塔利亚的钉壳小镇" => (TaliaKingdomofBanditry, PlanarSphere),
+    "塔利亚的裸皮电线" => (TaliaKingdomofBanditry, LinkRope),
+    "翁瓦克的诞生之岛" => (SprightlyVonwacq, PlanarSphere),
+    "翁瓦克的环岛海岸" => (SprightlyVonwacq, LinkRope),
+    "泰科铵的镭射球场" => (RutilantArena, PlanarSphere),
+    "泰科铵的弧光赛道" => (RutilantArena, LinkRope),
+    "伊须磨洲的残船鲸落" => (BrokenKeel, PlanarSphere),
+    "伊须磨洲的坼裂缆索" => (BrokenKeel, LinkRope),
+    "格拉默的铁骑兵团" => (FirmamentFrontlineGlamoth, PlanarSphere),
+    "格拉默的寂静坟碑" => (FirmamentFrontlineGlamoth, LinkRope),
+    "匹诺康尼的堂皇酒店" => (PenaconyLandoftheDreams, PlanarSphere),
+    "匹诺康尼的逐梦轨道" => (PenaconyLandoftheDreams, LinkRope),
+    "茨冈尼亚的母神卧榻" => (SigoniatheUnclaimedDesolation, PlanarSphere),
+    "茨冈尼亚的轮回纽结" => (SigoniatheUnclaimedDesolation, LinkRope),
+    "出云的祸津众神" => (IzumoGenseiandTakamaDivineRealm, PlanarSphere),
+    "出云的终始一刀" => (IzumoGenseiandTakamaDivineRealm, LinkRope),
+    "都蓝的穹窿金帐" => (DuranDynastyofRunningWolves, PlanarSphere),
+    "都蓝的器兽缰辔" => (DuranDynastyofRunningWolves, LinkRope),
+    "铸炼宫的莲华灯芯" => (ForgeoftheKalpagniLantern, PlanarSphere),
+    "铸炼宫的焰轮天绸" => (ForgeoftheKalpagniLantern, LinkRope),
+    "露莎卡的水朽苍都" => (LushakatheSunkenSeas, PlanarSphere),
+    "露莎卡的双生航道" => (LushakatheSunkenSeas, LinkRope),
+    "蕉乐园的蕉芯广场" => (TheWondrousBananAmusementPark, PlanarSphere),
+    "蕉乐园的模因线缆" => (TheWondrousBananAmusementPark, LinkRope),
+}
+
+relic_stat_names_zh_cn! {
+    "生命值" => HP / HPPercentage,
+    "攻击力" => ATK / ATKPercentage,
+    "防御力" => DEF / DEFPercentage,
+    "速度" => SPD,
+    "暴击率" => CRITRate,
+    "暴击伤害" => CRITDMG,
+    "击破特攻" => BreakEffect,
+    "治疗量加成" => OutgoingHealingBoost,
+    "能量恢复效率" => EnergyRegenerationRate,
+    "效果命中" => EffectHitRate,
+    "物理属性伤害提高" => PhysicalDMGBoost,
+    "火属性伤害提高" => FireDMGBoost,
+    "冰属性伤害提高" => IceDMGBoost,
+    "雷属性伤害提高" => LightningDMGBoost,
+    "风属性伤害提高" => WindDMGBoost,
+    "量子属性伤害提高" => QuantumDMGBoost,
+    "虚数属性伤害提高" => ImaginaryDMGBoost,
+    "效果抵抗" => EffectRES,
+}
@@ -1,7 +1,7 @@
 pub use relic_scanner::{StarRailRelicScanner};
 pub use relic_scanner_config::StarRailRelicScannerConfig;
 pub use scan_result::StarRailRelicScanResult;
-// pub use relic_scanner_window_info::RelicScannerWindowInfo;
+pub use relic_scanner_window_info::RelicScannerWindowInfo;
 
 mod relic_scanner;
 mod relic_scanner_config;
@@ -1,14 +1,17 @@
-use std::{cell::RefCell, ops::{Coroutine, CoroutineState}, pin::Pin, rc::Rc, sync::mpsc::{self, Sender}, time::SystemTime};
+use std::{cell::RefCell, rc::Rc, sync::{Arc, mpsc::{self, Sender}}, time::SystemTime};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::FromArgMatches;
 use image::RgbImage;
 use log::{error, info};
 
-use yas::capture::{Capturer, GenericCapturer};
+use yas::capture::{Capturer, CapturerConfig, FrameSnapshot, ReplayCapturer};
+use yas::dump::{DumpConfig, Dumper};
 use yas::game_info::GameInfo;
-use yas::ocr::{ImageToText, yas_ocr_model};
+use yas::ocr::{ImageToText, OCRModelConfig, yas_ocr_model};
 use yas::positioning::Pos;
+use yas::scanner_controller::repository_layout::{GridScanGenerator, GridScanProgress, ScanCheckpointConfig};
+use yas::system_control::ReplayControl;
 use yas::utils::color_distance;
 use yas::window_info::{FromWindowInfoRepository, WindowInfoRepository};
 
@@ -17,7 +20,7 @@ use crate::scanner::relic_scanner::message_items::SendItem;
 use crate::scanner::relic_scanner::relic_scanner_window_info::RelicScannerWindowInfo;
 use crate::scanner::relic_scanner::relic_scanner_worker::RelicScannerWorker;
 use crate::scanner::relic_scanner::scan_result::StarRailRelicScanResult;
-use crate::scanner_controller::repository_layout::{ReturnResult, StarRailRepositoryScanController, StarRailRepositoryScannerLogicConfig};
+use crate::scanner_controller::repository_layout::{ReturnResult, ScanState, StarRailRepositoryScanController, StarRailRepositoryScanControllerWindowInfo, StarRailRepositoryScannerLogicConfig};
 
 use super::relic_scanner_config::StarRailRelicScannerConfig;
 
@@ -28,30 +31,39 @@ pub struct StarRailRelicScanner {
     image_to_text: Box<dyn ImageToText<RgbImage> + Send>,
     controller: Rc<RefCell<StarRailRepositoryScanController>>,
     capturer: Rc<dyn Capturer<RgbImage>>,
+    // set when scanning recorded screenshots, the frames are advanced by the clicks and scrolls of the controller
+    replay: Option<Rc<ReplayCapturer>>,
+    dump_config: DumpConfig,
+    ocr_model_config: OCRModelConfig,
+    checkpoint_config: ScanCheckpointConfig,
 
     match_colors: MatchColors,
 }
 
 // constructor
 impl StarRailRelicScanner {
-    fn get_image_to_text() -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
-        let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(
-            yas_ocr_model!("./models/model_training.onnx", "./models/index_2_word.json")?
-        );
+    fn get_image_to_text(ocr_model_config: &OCRModelConfig) -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
+        let model: Box<dyn ImageToText<RgbImage> + Send> = match ocr_model_config.load_model()? {
+            Some(model) => Box::new(model),
+            None => Box::new(
+                yas_ocr_model!("./models/model_training.onnx", "./models/index_2_word.json", "./models/manifest.json", &ocr_model_config.inference_options())?
+            ),
+        };
         // let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(yas::ocr::PPOCRChV4RecInfer::new()?);
         Ok(model)
     }
 
-    fn get_capturer() -> Result<Rc<dyn Capturer<RgbImage>>> {
-        Ok(Rc::new(GenericCapturer::new()?))
-    }
-
     pub fn new(
         window_info_repo: &WindowInfoRepository,
         config: StarRailRelicScannerConfig,
         controller_config: StarRailRepositoryScannerLogicConfig,
+        capturer_config: CapturerConfig,
+        dump_config: DumpConfig,
+        ocr_model_config: OCRModelConfig,
+        checkpoint_config: ScanCheckpointConfig,
         game_info: GameInfo
     ) -> Result<Self> {
+        let (capturer, replay) = capturer_config.build()?;
         Ok(StarRailRelicScanner {
             scanner_config: config,
             window_info: RelicScannerWindowInfo::from_window_info_repository(
@@ -63,11 +75,17 @@ impl StarRailRelicScanner {
             controller: Rc::new(RefCell::new(StarRailRepositoryScanController::new(
                 window_info_repo,
                 controller_config,
-                game_info.clone()
+                game_info.clone(),
+                capturer.clone(),
+                ReplayControl::or_generic(&replay),
             )?)),
             game_info,
-            image_to_text: Self::get_image_to_text()?,
-            capturer: Self::get_capturer()?,
+            image_to_text: Self::get_image_to_text(&ocr_model_config)?,
+            capturer,
+            replay,
+            dump_config,
+            ocr_model_config,
+            checkpoint_config,
 
             match_colors: MATCH_COLORS,
         })
@@ -84,34 +102,40 @@ impl StarRailRelicScanner {
             game_info.platform,
             window_info_repo
         )?;
+        let (capturer, replay) = CapturerConfig::from_arg_matches(arg_matches)?.build()?;
+        let ocr_model_config = OCRModelConfig::from_arg_matches(arg_matches)?;
         Ok(StarRailRelicScanner {
             scanner_config: StarRailRelicScannerConfig::from_arg_matches(arg_matches)?,
             window_info,
             controller: Rc::new(RefCell::new(
-                StarRailRepositoryScanController::from_arg_matches(window_info_repo, arg_matches, game_info.clone())?
+                StarRailRepositoryScanController::from_arg_matches(window_info_repo, arg_matches, game_info.clone(), capturer.clone(), ReplayControl::or_generic(&replay))?
             )),
             game_info,
-            image_to_text: Self::get_image_to_text()?,
-            capturer: Self::get_capturer()?,
+            image_to_text: Self::get_image_to_text(&ocr_model_config)?,
+            capturer,
+            replay,
+            dump_config: DumpConfig::from_arg_matches(arg_matches)?,
+            ocr_model_config,
+            checkpoint_config: ScanCheckpointConfig::from_arg_matches(arg_matches)?,
             match_colors: MATCH_COLORS,
         })
     }
 }
 
 impl StarRailRelicScanner {
-    pub fn capture_panel(&self) -> Result<RgbImage> {
-        self.capturer.capture_relative_to(
+    pub fn capture_panel(&self, frame: &FrameSnapshot) -> Result<RgbImage> {
+        frame.capture_relative_to(
             self.window_info.panel_rect.to_rect_i32(),
             self.game_info.window.origin()
         )
     }
 
-    pub fn get_star(&self) -> Result<usize> {
+    pub fn get_star(&self, frame: &FrameSnapshot) -> Result<usize> {
         let pos: Pos<i32> = Pos {
             x: self.game_info.window.left + self.window_info.star_pos.x as i32,
             y: self.game_info.window.top + self.window_info.star_pos.y as i32,
         };
-        let color = self.capturer.capture_color(pos)?;
+        let color = frame.capture_color(pos)?;
 
         let (index, _) = self.match_colors.match_colors_star
             .iter()
@@ -122,12 +146,12 @@ impl StarRailRelicScanner {
         Ok(index + 1)
     }
 
-    pub fn get_lock(&self) -> Result<bool> {
+    pub fn get_lock(&self, frame: &FrameSnapshot) -> Result<bool> {
         let pos: Pos<i32> = Pos {
             x: self.game_info.window.left + self.window_info.lock_pos.x as i32,
             y: self.game_info.window.top + self.window_info.lock_pos.y as i32,
         };
-        let color = self.capturer.capture_color(pos)?;
+        let color = frame.capture_color(pos)?;
 
         let (index, _) = self.match_colors.match_colors_lock
             .iter()
@@ -138,12 +162,12 @@ impl StarRailRelicScanner {
         Ok(index == 0)
     }
 
-    pub fn get_discard(&self) -> Result<bool> {
+    pub fn get_discard(&self, frame: &FrameSnapshot) -> Result<bool> {
         let pos: Pos<i32> = Pos {
             x: self.game_info.window.left + self.window_info.discard_pos.x as i32,
             y: self.game_info.window.top + self.window_info.discard_pos.y as i32,
         };
-        let color = self.capturer.capture_color(pos)?;
+        let color = frame.capture_color(pos)?;
 
         let (index, _) = self.match_colors.match_colors_discard
             .iter()
@@ -154,12 +178,12 @@ impl StarRailRelicScanner {
         Ok(index == 0)
     }
 
-    pub fn get_equipper(&self) -> Result<String> {
+    pub fn get_equipper(&self, frame: &FrameSnapshot) -> Result<String> {
         let pos: Pos<i32> = Pos {
             x: self.game_info.window.left + self.window_info.equipper_pos.x as i32,
             y: self.game_info.window.top + self.window_info.equipper_pos.y as i32,
         };
-        let color = self.capturer.capture_color(pos)?;
+        let color = frame.capture_color(pos)?;
 
         let (name, _) = self.match_colors.match_colors_equipper
             .iter()
@@ -205,15 +229,32 @@ impl StarRailRelicScanner {
         let (tx, rx) = mpsc::channel::<Option<SendItem>>();
         // let token = self.cancellation_token.clone();
         let count = self.get_item_count()?;
-        let worker = RelicScannerWorker::new(
-            self.window_info.clone(),
-            self.scanner_config.clone()
-        )?;
-
-        let join_handle = worker.run(rx);
+        let checkpoint = self.checkpoint_config.load::<StarRailRelicScanResult>()?;
+        if checkpoint.is_some() && self.replay.is_some() {
+            return Err(anyhow!("回放截图时无法继续扫描"));
+        }
+        let mut generator = match &checkpoint {
+            Some(checkpoint) => StarRailRepositoryScanController::get_generator_from_progress(self.controller.clone(), count as usize, &checkpoint.progress)?,
+            None => StarRailRepositoryScanController::get_generator(self.controller.clone(), count as usize),
+        };
+        let dumper = Dumper::from_config(&self.dump_config)?
+            // a resumed scan goes on after the dumps of the previous one
+            .map(|d| Arc::new(d.with_first_index(generator.start_index())));
+        let ocr_cache = self.ocr_model_config.ocr_cache();
+        // one worker per recognition thread, each loads its own model
+        let workers = (0..self.ocr_model_config.worker_count())
+            .map(|_| RelicScannerWorker::new(
+                self.window_info.clone(),
+                dumper.clone(),
+                &self.ocr_model_config,
+                ocr_cache.as_ref(),
+            ))
+            .collect::<Result<Vec<_>>>()?;
+
+        let join_handle = RelicScannerWorker::run(workers, rx, &self.scanner_config, self.window_info.col as usize);
         info!("Worker created");
 
-        self.send(&tx, count);
+        let progress = self.send(&tx, &mut generator);
 
         match tx.send(None) {
             Ok(_) => info!("扫描结束，等待识别线程结束，请勿关闭程序"),
@@ -227,60 +268,91 @@ impl StarRailRelicScanner {
         }
 
         match join_handle.join() {
-            Ok(v) => {
+            Ok(recognition) => {
                 info!("识别耗时: {:?}", now.elapsed()?);
-                Ok(v)
+                if let Some(ocr_cache) = &ocr_cache {
+                    info!("OCR 缓存：{}", ocr_cache.stats());
+                }
+
+                let progress = match recognition.failed_index {
+                    // the items after the failed one were not recognized, even if they were sent
+                    Some(index) => Some(generator.progress_at(generator.start_index() + index)),
+                    None => progress,
+                };
+                // results of the scan resumed from
+                let mut results = checkpoint.map(|c| c.results).unwrap_or_default();
+                results.extend(recognition.items);
+                // a replay can't be resumed, as it goes through the recording from the start
+                if self.replay.is_none() {
+                    self.checkpoint_config.update(progress, &results);
+                }
+
+                Ok(results)
             },
             Err(_) => Err(anyhow::anyhow!("识别线程出现错误")),
         }
     }
 
-    fn send(&mut self, tx: &Sender<Option<SendItem>>, count: i32) {
-        let mut generator = StarRailRepositoryScanController::get_generator(
-            self.controller.clone(),
-            count as usize
-        );
+    /// Capture the panel and the colored flags of the current item
+    fn capture_item(&self) -> Result<SendItem> {
+        // grab the window once, everything of the item is read from this frame
+        let frame = FrameSnapshot::capture(self.capturer.as_ref(), self.game_info.window)?;
+        let panel_image = self.capture_panel(&frame)?;
+        let equip = self.get_equipper(&frame)?;
+        let star = self.get_star(&frame)?;
+        let lock = self.get_lock(&frame)?;
+        let discard = self.get_discard(&frame)?;
+
+        Ok(SendItem { panel_image, equip, star, lock, discard })
+    }
 
-        loop {
-            let pinned_generator = Pin::new(&mut generator);
-            match pinned_generator.resume(()) {
-                CoroutineState::Yielded(_) => {
-                    // let image = self.capture_panel().unwrap();
-                    let panel_image = self.capture_panel().unwrap();
-                    let equip = self.get_equipper().unwrap();
-                    let star = self.get_star().unwrap();
-                    let lock = self.get_lock().unwrap();
-                    let discard = self.get_discard().unwrap();
-
-                    // todo normalize types
-                    if (star as i32) < self.scanner_config.min_star {
-                        info!(
-                            "找到满足最低星级要求 {} 的物品，准备退出……",
-                            self.scanner_config.min_star
-                        );
-                        break;
-                    }
+    /// Send an item to the worker, returns false if the scan should stop
+    fn send_item(&self, tx: &Sender<Option<SendItem>>, item: SendItem) -> bool {
+        // todo normalize types
+        if (item.star as i32) < self.scanner_config.min_star {
+            info!(
+                "找到满足最低星级要求 {} 的物品，准备退出……",
+                self.scanner_config.min_star
+            );
+            return false;
+        }
 
-                    if tx.send(Some(SendItem { panel_image, equip, star, lock, discard })).is_err() {
+        tx.send(Some(item)).is_ok()
+    }
+
+    /// Click through the items and send them to the worker, returns the progress if the scan stops early
+    fn send(&mut self, tx: &Sender<Option<SendItem>>, generator: &mut GridScanGenerator<StarRailRepositoryScanControllerWindowInfo>) -> Option<GridScanProgress> {
+        loop {
+            match generator.resume() {
+                ScanState::Yielded => {
+                    let item = match self.capture_item() {
+                        Ok(v) => v,
+                        Err(e) => {
+                            error!("截图失败：{}", e);
+                            // the item is scanned again when resumed
+                            return Some(generator.progress_retrying_item());
+                        },
+                    };
+                    if !self.send_item(tx, item) {
                         break;
                     }
-
-                    // scanned_count += 1;
                 },
-                CoroutineState::Complete(result) => {
+                ScanState::Complete(result) => {
                     match result {
                         Err(e) => error!("扫描发生错误：{}", e),
                         Ok(value) => {
                             match value {
                                 ReturnResult::Interrupted => info!("用户中断"),
-                                ReturnResult::Finished => ()
+                                ReturnResult::Finished => return None,
                             }
                         }
                     }
 
-                    break;
+                    return Some(generator.progress());
                 }
             }
         }
+
+        None
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,9 @@
-#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+use yas::ocr::FieldConfidence;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StarRailRelicScanResult {
     pub name: String,
     pub main_stat_name: String,
@@ -10,4 +15,67 @@ pub struct StarRailRelicScanResult {
     pub star: i32,
     pub lock: bool,
     pub discard: bool,
+    /// OCR confidence of each field, not taken into account when comparing results
+    pub confidence: FieldConfidence,
+}
+
+// the confidence differs between two reads of the same item, which is identified by the other fields.
+// The fields are destructured so that a new one is not left out
+
+impl PartialEq for StarRailRelicScanResult {
+    fn eq(&self, other: &Self) -> bool {
+        let Self {
+            name,
+            main_stat_name,
+            main_stat_value,
+            sub_stat_name,
+            sub_stat_value,
+            equip,
+            level,
+            star,
+            lock,
+            discard,
+            confidence: _,
+        } = self;
+        *name == other.name
+            && *main_stat_name == other.main_stat_name
+            && *main_stat_value == other.main_stat_value
+            && *sub_stat_name == other.sub_stat_name
+            && *sub_stat_value == other.sub_stat_value
+            && *equip == other.equip
+            && *level == other.level
+            && *star == other.star
+            && *lock == other.lock
+            && *discard == other.discard
+    }
+}
+
+impl Eq for StarRailRelicScanResult {}
+
+impl Hash for StarRailRelicScanResult {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let Self {
+            name,
+            main_stat_name,
+            main_stat_value,
+            sub_stat_name,
+            sub_stat_value,
+            equip,
+            level,
+            star,
+            lock,
+            discard,
+            confidence: _,
+        } = self;
+        name.hash(state);
+        main_stat_name.hash(state);
+        main_stat_value.hash(state);
+        sub_stat_name.hash(state);
+        sub_stat_value.hash(state);
+        equip.hash(state);
+        level.hash(state);
+        star.hash(state);
+        lock.hash(state);
+        discard.hash(state);
+    }
 }
@@ -1,10 +1,6 @@
 pub use scan_logic::StarRailRepositoryScanController;
-pub use scan_logic::ReturnResult;
-pub use config::StarRailRepositoryScannerLogicConfig;
 pub use window_info::StarRailRepositoryScanControllerWindowInfo;
-// pub use scroll_result::ScrollResult;
+pub use yas::scanner_controller::repository_layout::{GridScanControllerConfig as StarRailRepositoryScannerLogicConfig, ReturnResult, ScanState};
 
-mod config;
 mod scan_logic;
 mod window_info;
-mod scroll_result;
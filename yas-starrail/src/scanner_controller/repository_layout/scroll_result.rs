@@ -1,8 +0,0 @@
-#[derive(Debug)]
-pub enum ScrollResult {
-    TimeLimitExceeded,
-    Interrupt,
-    Success,
-    Failed,
-    Skip,
-}
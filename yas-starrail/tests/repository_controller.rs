@@ -0,0 +1,66 @@
+//! The generic behaviour of the grid controller is tested in yas, this checks the layout and the column flag of star rail
+
+use yas::game_info::{GameInfo, Platform, ResolutionFamily, UI};
+use yas::positioning::{Pos, Rect, Scalable, Size};
+use yas::simulation::{all_items, SimulatedInventory};
+use yas::window_info::{load_window_info_repo, FromWindowInfoRepository, WindowInfoRepository};
+use yas_scanner_starrail::scanner::relic_scanner::RelicScannerWindowInfo;
+use yas_scanner_starrail::scanner_controller::repository_layout::{
+    ReturnResult, StarRailRepositoryScanControllerWindowInfo, StarRailRepositoryScannerLogicConfig,
+};
+
+const WINDOW_SIZE: Size<usize> = Size { width: 1920, height: 1080 };
+
+fn window_info_repo() -> WindowInfoRepository {
+    load_window_info_repo!("../window_info/windows1920x1080.json")
+}
+
+fn game_info() -> GameInfo {
+    GameInfo {
+        window: Rect::new(0, 0, WINDOW_SIZE.width as i32, WINDOW_SIZE.height as i32),
+        resolution_family: ResolutionFamily::Windows16x9,
+        is_cloud: false,
+        ui: UI::Desktop,
+        platform: Platform::Windows,
+    }
+}
+
+#[test]
+fn scans_every_item_in_order() {
+    // 15 rows, the last pages are estimated from the earlier scrolls
+    let item_count = 131;
+    let window_info = StarRailRepositoryScanControllerWindowInfo::from_window_info_repository(
+        WINDOW_SIZE, UI::Desktop, Platform::Windows, &window_info_repo(),
+    ).unwrap();
+    let inventory = SimulatedInventory::from_window_info(&window_info, &game_info(), item_count, 15.0);
+    inventory.set_switch_delay(1);
+
+    let config = StarRailRepositoryScannerLogicConfig {
+        scroll_delay: 0,
+        max_wait_switch_item: 200,
+        ..Default::default()
+    };
+    let controller = inventory.controller(window_info, config, game_info());
+
+    let (selected, result) = inventory.scan(controller, item_count);
+    assert!(matches!(result, Ok(ReturnResult::Finished)));
+    assert_eq!(selected, all_items(item_count));
+}
+
+/// 32:9 is not recorded, the layout is derived from the anchored 16:9 one, scaled by the height
+#[test]
+fn derives_the_layout_of_a_32x9_window() {
+    let window_size = Size::new(5120, 1440);
+
+    let info = StarRailRepositoryScanControllerWindowInfo::from_window_info_repository(
+        window_size, UI::Desktop, Platform::Windows, &window_info_repo(),
+    ).unwrap();
+    // the panel sticks to the right edge, the items to the left
+    assert_eq!(info.panel_rect.left, 5120.0 - (1920.0 - 1395.0) * (1440.0 / 1080.0));
+    assert_eq!(info.scan_margin_pos, Pos::new(136.0, 206.0).scale(1440.0 / 1080.0));
+
+    let relic = RelicScannerWindowInfo::from_window_info_repository(
+        window_size, UI::Desktop, Platform::Windows, &window_info_repo(),
+    ).unwrap();
+    assert!(relic.title_rect.left > info.panel_rect.left);
+}
@@ -0,0 +1,3 @@
+fn main() {
+    yas_model_manifest::check_model_dir("src/scanner/echo_scanner/models");
+}
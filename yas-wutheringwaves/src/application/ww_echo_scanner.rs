@@ -1,11 +1,18 @@
 use anyhow::anyhow;
-use clap::{ArgMatches, Args, command};
+use clap::{ArgMatches, Args, command, FromArgMatches};
+use image::RgbImage;
 use log::info;
 use yas::export::ExportAssets;
-use yas::game_info::{GameInfo, GameInfoBuilder};
-use yas::window_info::{load_window_info_repo, WindowInfoRepository};
-use crate::scanner::{WWEchoScanner, WWEchoScannerConfig};
-use crate::scanner_controller::WWRepositoryLayoutConfig;
+use yas::capture::{CapturerConfig, ReplayCapturer};
+use yas::draw_capture_region::draw_window_info_from_repository;
+use yas::dump::DumpConfig;
+use yas::ocr::OCRModelConfig;
+use yas::scanner_controller::repository_layout::ScanCheckpointConfig;
+use yas::system_control::AbortKeyConfig;
+use yas::game_info::{GameInfo, GameInfoBuilder, Platform, UI};
+use yas::window_info::{load_window_info_repo, WindowInfoConfig, WindowInfoRepository};
+use crate::scanner::{EchoScannerWindowInfo, WWEchoScanner, WWEchoScannerConfig};
+use crate::scanner_controller::{WWRepositoryLayoutConfig, WWRepositoryLayoutWindowinfo};
 use anyhow::Result;
 
 pub struct WWEchoScannerApplication {
@@ -23,6 +30,12 @@ impl WWEchoScannerApplication {
         let mut cmd = command!();
         cmd = <WWEchoScannerConfig as Args>::augment_args_for_update(cmd);
         cmd = <WWRepositoryLayoutConfig as Args>::augment_args_for_update(cmd);
+        cmd = <CapturerConfig as Args>::augment_args_for_update(cmd);
+        cmd = <DumpConfig as Args>::augment_args_for_update(cmd);
+        cmd = <ScanCheckpointConfig as Args>::augment_args_for_update(cmd);
+        cmd = <OCRModelConfig as Args>::augment_args_for_update(cmd);
+        cmd = <AbortKeyConfig as Args>::augment_args_for_update(cmd);
+        cmd = <WindowInfoConfig as Args>::augment_args_for_update(cmd);
         // cmd = <ExportRelicConfig as Args>::augment_args_for_update(cmd);
         cmd
     }
@@ -33,7 +46,22 @@ impl WWEchoScannerApplication {
         )
     }
 
-    fn get_game_info() -> anyhow::Result<GameInfo> {
+    /// Draw the window info used by the scanner onto a screenshot of the game window, labelled by key
+    pub fn draw_window_info(window_info_config: &WindowInfoConfig, image: &mut RgbImage) -> Result<()> {
+        let mut window_info_repository = Self::get_window_info_repository();
+        window_info_config.apply(&mut window_info_repository)?;
+
+        let platform = Platform::current();
+        draw_window_info_from_repository::<WWRepositoryLayoutWindowinfo>(&window_info_repository, image, UI::Desktop, platform)?;
+        draw_window_info_from_repository::<EchoScannerWindowInfo>(&window_info_repository, image, UI::Desktop, platform)?;
+        Ok(())
+    }
+
+    fn get_game_info(capturer_config: &CapturerConfig) -> anyhow::Result<GameInfo> {
+        if let Some(dir) = &capturer_config.replay {
+            return ReplayCapturer::new(dir)?.game_info();
+        }
+
         let game_info = GameInfoBuilder::new()
             .add_local_window_name("鸣潮")
             .add_local_window_name("Wuthering Waves")
@@ -48,8 +76,11 @@ impl WWEchoScannerApplication {
         println!("START");
         // Self::init();
         let arg_matches = &self.arg_matches;
-        let window_info_repository = Self::get_window_info_repository();
-        let game_info = Self::get_game_info()?;
+        let mut window_info_repository = Self::get_window_info_repository();
+        WindowInfoConfig::from_arg_matches(arg_matches)?.apply(&mut window_info_repository)?;
+        AbortKeyConfig::from_arg_matches(arg_matches)?.apply();
+        let capturer_config = CapturerConfig::from_arg_matches(arg_matches)?;
+        let game_info = Self::get_game_info(&capturer_config)?;
 
         info!("window: {:?}", game_info.window);
         info!("ui: {:?}", game_info.ui);
@@ -57,7 +88,7 @@ impl WWEchoScannerApplication {
         info!("resolution family: {:?}", game_info.resolution_family);
 
         #[cfg(target_os = "windows")]
-        {
+        if capturer_config.replay.is_none() {
             // assure admin
             if !yas::utils::is_admin() {
                 return Err(anyhow!("请使用管理员运行"));
@@ -1,5 +1,5 @@
 pub use echo_name::WWEchoName;
-pub use stats::{WWStat, WWStatName};
+pub use stats::{WWStat, WWStatName, WW_STAT_NAMES_CHS};
 pub use echo::WWEcho;
 
 mod echo;
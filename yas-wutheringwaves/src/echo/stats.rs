@@ -22,6 +22,15 @@ pub enum WWStatName {
     ResonanceLiberationBonus,
 }
 
+/// Chinese names of all stats, without the value
+#[rustfmt::skip]
+pub const WW_STAT_NAMES_CHS: &[&str] = &[
+    "暴击伤害", "暴击率", "冷凝伤害加成", "气动伤害加成", "热熔伤害加成",
+    "导电伤害加成", "湮灭伤害加成", "衍射伤害加成", "共鸣效率", "攻击",
+    "防御", "生命", "治疗效果加成", "普攻伤害加成", "重击伤害加成",
+    "共鸣技能伤害加成", "共鸣解放伤害加成",
+];
+
 impl WWStatName {
     pub fn from_chs(chs: &str, is_percentage: bool) -> Option<Self> {
         let ret = match chs {
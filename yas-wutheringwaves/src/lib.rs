@@ -1,6 +1,3 @@
-#![feature(coroutine_trait, coroutines)]
-#![feature(stmt_expr_attributes)]
-
 #[allow(unused_imports)]
 
 pub mod echo;
@@ -1,7 +1,6 @@
 use std::cell::RefCell;
-use std::ops::{CoroutineState, Coroutine};
-use std::pin::Pin;
 use std::rc::Rc;
+use std::sync::Arc;
 use std::sync::mpsc;
 use std::sync::mpsc::Sender;
 use std::time::SystemTime;
@@ -12,9 +11,12 @@ use log::{error, info};
 use regex::Regex;
 use clap::FromArgMatches;
 
-use yas::capture::{Capturer, GenericCapturer, StreamingCapturer};
+use yas::capture::{Capturer, CapturerConfig, ReplayCapturer, StreamingCapturer};
+use yas::dump::{DumpConfig, Dumper};
 use yas::game_info::GameInfo;
-use yas::ocr::{ImageToText, yas_ocr_model};
+use yas::ocr::{ImageToText, OCRModelConfig, yas_ocr_model};
+use yas::scanner_controller::repository_layout::{GridScanGenerator, GridScanProgress, ScanCheckpointConfig};
+use yas::system_control::ReplayControl;
 use yas::window_info::{WindowInfoRepository, FromWindowInfoRepository};
 
 use crate::scanner::echo_scanner::echo_scanner_config::WWEchoScannerConfig;
@@ -22,7 +24,7 @@ use crate::scanner::echo_scanner::echo_scanner_window_info::EchoScannerWindowInf
 use crate::scanner::echo_scanner::echo_scanner_worker::WWEchoScannerWorker;
 use crate::scanner::echo_scanner::message_item::SendItem;
 use crate::scanner::echo_scanner::scan_result::WWEchoScanResult;
-use crate::scanner_controller::{ReturnResult, WWRepositoryLayoutConfig, WWRepositoryLayoutScanController};
+use crate::scanner_controller::{ReturnResult, ScanState, WWRepositoryLayoutConfig, WWRepositoryLayoutScanController, WWRepositoryLayoutWindowinfo};
 
 pub struct WWEchoScanner {
     scanner_config: WWEchoScannerConfig,
@@ -31,27 +33,36 @@ pub struct WWEchoScanner {
     image_to_text: Box<dyn ImageToText<RgbImage> + Send>,
     controller: Rc<RefCell<WWRepositoryLayoutScanController>>,
     capturer: Rc<dyn Capturer<RgbImage>>,
+    // set when scanning recorded screenshots, the frames are advanced by the clicks and scrolls of the controller
+    replay: Option<Rc<ReplayCapturer>>,
+    dump_config: DumpConfig,
+    ocr_model_config: OCRModelConfig,
+    checkpoint_config: ScanCheckpointConfig,
 }
 
 impl WWEchoScanner {
-    fn get_image_to_text() -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
-        let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(
-            yas_ocr_model!("./models/model_training.onnx", "./models/index_2_word.json")?
-        );
+    fn get_image_to_text(ocr_model_config: &OCRModelConfig) -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
+        let model: Box<dyn ImageToText<RgbImage> + Send> = match ocr_model_config.load_model()? {
+            Some(model) => Box::new(model),
+            None => Box::new(
+                yas_ocr_model!("./models/model_training.onnx", "./models/index_2_word.json", "./models/manifest.json", &ocr_model_config.inference_options())?
+            ),
+        };
         // let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(yas::ocr::PPOCRChV4RecInfer::new()?);
         Ok(model)
     }
 
-    fn get_capturer() -> Result<Rc<dyn Capturer<RgbImage>>> {
-        Ok(Rc::new(GenericCapturer::new()?))
-    }
-
     pub fn new(
         window_info_repo: &WindowInfoRepository,
         config: WWEchoScannerConfig,
         controller_config: WWRepositoryLayoutConfig,
+        capturer_config: CapturerConfig,
+        dump_config: DumpConfig,
+        ocr_model_config: OCRModelConfig,
+        checkpoint_config: ScanCheckpointConfig,
         game_info: GameInfo
     ) -> anyhow::Result<Self> {
+        let (capturer, replay) = capturer_config.build()?;
         Ok(Self {
             scanner_config: config,
             window_info: EchoScannerWindowInfo::from_window_info_repository(
@@ -63,11 +74,17 @@ impl WWEchoScanner {
             controller: Rc::new(RefCell::new(WWRepositoryLayoutScanController::new(
                 window_info_repo,
                 controller_config,
-                game_info.clone()
+                game_info.clone(),
+                capturer.clone(),
+                ReplayControl::or_generic(&replay),
             )?)),
             game_info,
-            image_to_text: Self::get_image_to_text()?,
-            capturer: Self::get_capturer()?,
+            image_to_text: Self::get_image_to_text(&ocr_model_config)?,
+            capturer,
+            replay,
+            dump_config,
+            ocr_model_config,
+            checkpoint_config,
         })
     }
 
@@ -82,26 +99,32 @@ impl WWEchoScanner {
             game_info.platform,
             window_info_repo
         )?;
+        let (capturer, replay) = CapturerConfig::from_arg_matches(arg_matches)?.build()?;
+        let ocr_model_config = OCRModelConfig::from_arg_matches(arg_matches)?;
         Ok(Self {
             scanner_config: WWEchoScannerConfig::from_arg_matches(arg_matches)?,
             window_info,
             controller: Rc::new(RefCell::new(
-                WWRepositoryLayoutScanController::from_arg_matches(window_info_repo, arg_matches, game_info.clone())?
+                WWRepositoryLayoutScanController::from_arg_matches(window_info_repo, arg_matches, game_info.clone(), capturer.clone(), ReplayControl::or_generic(&replay))?
             )),
             game_info,
-            image_to_text: Self::get_image_to_text()?,
-            capturer: Self::get_capturer()?,
+            image_to_text: Self::get_image_to_text(&ocr_model_config)?,
+            capturer,
+            replay,
+            dump_config: DumpConfig::from_arg_matches(arg_matches)?,
+            ocr_model_config,
+            checkpoint_config: ScanCheckpointConfig::from_arg_matches(arg_matches)?,
         })
     }
 }
 
 impl WWEchoScanner {
-    // fn capture_panel(&self) -> Result<RgbImage> {
-    //     self.capturer.capture_relative_to(
-    //         self.window_info.panel_rect.to_rect_i32(),
-    //         self.game_info.window.origin()
-    //     )
-    // }
+    fn capture_panel(&self) -> Result<RgbImage> {
+        self.capturer.capture_relative_to(
+            self.window_info.panel_rect.to_rect_i32(),
+            self.game_info.window.origin()
+        )
+    }
 
     /// Get Echo count
     fn get_item_count(&self) -> Result<usize> {
@@ -134,16 +157,51 @@ impl WWEchoScanner {
 
         let (image_tx, image_rx) = mpsc::channel::<SendItem>();
         let count = self.get_item_count()?;
+        let checkpoint = self.checkpoint_config.load::<WWEchoScanResult>()?;
+        if checkpoint.is_some() && self.replay.is_some() {
+            return Err(anyhow::anyhow!("回放截图时无法继续扫描"));
+        }
 
-        let worker = WWEchoScannerWorker::new(
-            self.window_info.clone(),
-            self.scanner_config.clone()
-        )?;
+        let mut generator = match &checkpoint {
+            Some(checkpoint) => WWRepositoryLayoutScanController::get_generator_from_progress(self.controller.clone(), count, &checkpoint.progress)?,
+            None => WWRepositoryLayoutScanController::get_generator(self.controller.clone(), count),
+        };
+
+        let dumper = Dumper::from_config(&self.dump_config)?
+            // a resumed scan goes on after the dumps of the previous one
+            .map(|d| Arc::new(d.with_first_index(generator.start_index())));
+        let ocr_cache = self.ocr_model_config.ocr_cache();
+        // one worker per recognition thread, each loads its own model
+        let workers = (0..self.ocr_model_config.worker_count())
+            .map(|_| WWEchoScannerWorker::new(
+                self.window_info.clone(),
+                dumper.clone(),
+                &self.ocr_model_config,
+                ocr_cache.as_ref(),
+            ))
+            .collect::<Result<Vec<_>>>()?;
+
+        let worker_join_handle = WWEchoScannerWorker::run(workers, image_rx, &self.scanner_config);
+
+        let progress = if self.replay.is_some() {
+            // there is no screen to stream, the panel is captured from the recording once an item is clicked
+            let progress = self.start_clicking_items(&mut generator, Some(&image_tx));
+            drop(image_tx);
+            progress
+        } else {
+            let panel_rect = self.window_info.panel_rect.to_rect_i32().translate(self.game_info.window.origin());
+            let streaming_capturer = StreamingCapturer::new(panel_rect);
+            let (capturer_join_handle, cancel_image_capturer) = streaming_capturer.start_transform(image_tx, |x| SendItem { panel_image: x });
+
+            let progress = self.start_clicking_items(&mut generator, None);
+
+            self.controller.borrow().profiler.borrow().print();
 
-        let worker_join_handle = worker.run(image_rx);
-        let panel_rect = self.window_info.panel_rect.to_rect_i32().translate(self.game_info.window.origin());
-        let streaming_capturer = StreamingCapturer::new(panel_rect);
-        let (capturer_join_handle, cancel_image_capturer) = streaming_capturer.start_transform(image_tx, |x| SendItem { panel_image: x });
+            cancel_image_capturer();
+
+            capturer_join_handle.join();
+            progress
+        };
 
         // self.send(&tx, count);
         //
@@ -158,16 +216,25 @@ impl WWEchoScanner {
         //     info!("平均模型推理时间：{} ms", ms);
         // }
 
-        self.start_clicking_items(count);
-
-        self.controller.borrow().profiler.borrow().print();
-
-        cancel_image_capturer();
+        let recognition = worker_join_handle.join().unwrap();
+        if let Some(ocr_cache) = &ocr_cache {
+            info!("OCR 缓存：{}", ocr_cache.stats());
+        }
 
-        capturer_join_handle.join();
-        let result = worker_join_handle.join().unwrap();
+        // results of the scan resumed from
+        let resumed_results = checkpoint.map(|c| c.results).unwrap_or_default();
+        let mut results = resumed_results.clone();
+        results.extend(recognition.items);
+        // a replay can't be resumed, as it goes through the recording from the start
+        if self.replay.is_none() {
+            match recognition.failed_index {
+                // the panels are streamed, so the item which failed is not known, a resumed scan starts where this one did
+                Some(_) => self.checkpoint_config.save(generator.progress_at(generator.start_index()), &resumed_results),
+                None => self.checkpoint_config.update(progress, &results),
+            }
+        }
 
-        Ok(result)
+        Ok(results)
 
         // match join_handle.join() {
         //     Ok(v) => {
@@ -178,36 +245,47 @@ impl WWEchoScanner {
         // }
     }
 
-    fn start_clicking_items(&mut self, count: usize) {
-        let mut generator = WWRepositoryLayoutScanController::get_generator(
-            self.controller.clone(),
-            count
-        );
-
+    /// Click through the items while the panels are streamed, or sent to `tx` item by item if there is one.
+    /// Returns the progress if the scan stops early
+    fn start_clicking_items(
+        &mut self,
+        generator: &mut GridScanGenerator<WWRepositoryLayoutWindowinfo>,
+        tx: Option<&Sender<SendItem>>,
+    ) -> Option<GridScanProgress> {
         loop {
-            let pinned_generator = Pin::new(&mut generator);
-            match pinned_generator.resume(()) {
-                CoroutineState::Yielded(_) => {
-                    // let panel_image = self.capture_panel().unwrap();
-                    //
-                    // if tx.send(Some(SendItem { panel_image })).is_err() {
-                    //     break;
-                    // }
+            match generator.resume() {
+                ScanState::Yielded => {
+                    if let Some(tx) = tx {
+                        let panel_image = match self.capture_panel() {
+                            Ok(v) => v,
+                            Err(e) => {
+                                error!("截图失败：{}", e);
+                                // the item is scanned again when resumed
+                                return Some(generator.progress_retrying_item());
+                            },
+                        };
+
+                        if tx.send(SendItem { panel_image }).is_err() {
+                            break;
+                        }
+                    }
                 },
-                CoroutineState::Complete(result) => {
+                ScanState::Complete(result) => {
                     match result {
                         Err(e) => error!("扫描发生错误：{}", e),
                         Ok(value) => {
                             match value {
                                 ReturnResult::Interrupted => info!("用户中断"),
-                                ReturnResult::Finished => ()
+                                ReturnResult::Finished => return None,
                             }
                         }
                     }
 
-                    break;
+                    return Some(generator.progress());
                 }
             }
         }
+
+        None
     }
 }
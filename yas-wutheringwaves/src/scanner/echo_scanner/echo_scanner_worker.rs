@@ -1,21 +1,24 @@
-use std::collections::HashSet;
+use std::sync::Arc;
 use std::sync::mpsc::Receiver;
 use std::thread::JoinHandle;
 use image::{GenericImageView, RgbImage};
-use yas::ocr::{ImageToText, yas_ocr_model};
+use yas::dump::{Dumper, ItemDump};
+use yas::ocr::{FieldConfidence, FieldLexicons, ImageToText, OCRCache, OCRModelConfig, LOW_CONFIDENCE_THRESHOLD, yas_ocr_model};
+use crate::echo::{WWEchoName, WW_STAT_NAMES_CHS};
 use crate::scanner::echo_scanner::echo_scanner_config::WWEchoScannerConfig;
 use crate::scanner::echo_scanner::echo_scanner_window_info::EchoScannerWindowInfo;
-use anyhow::Result;
-use log::{error, info, warn};
-use rayon::iter::ParallelBridge;
+use anyhow::{anyhow, Result};
+use log::warn;
+use lazy_static::lazy_static;
 use yas::positioning::{Pos, Rect};
+use yas::worker_pool::{spawn_recognition, RecognitionConfig, RecognitionResult, Recognized};
 use crate::scanner::echo_scanner::message_item::SendItem;
 use crate::scanner::echo_scanner::scan_result::WWEchoScanResult;
 
 pub struct WWEchoScannerWorker {
     model: Box<dyn ImageToText<RgbImage> + Send>,
     window_info: EchoScannerWindowInfo,
-    config: WWEchoScannerConfig,
+    dumper: Option<Arc<Dumper>>,
 }
 
 fn parse_level(s: &str) -> Result<usize> {
@@ -30,39 +33,69 @@ fn parse_level(s: &str) -> Result<usize> {
     return Ok(level);
 }
 
-fn get_image_to_text() -> Result<Box<dyn ImageToText<RgbImage> + Send>> {
-    let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(
-        yas_ocr_model!("./models/model_training.onnx", "./models/index_2_word.json")?
-    );
-    // let model: Box<dyn ImageToText<RgbImage> + Send> = Box::new(PPOCRChV4RecInfer::new()?);
-    Ok(model)
+lazy_static! {
+    static ref FIELD_LEXICONS: FieldLexicons = FieldLexicons::new()
+        .with(&["title_rect"], WWEchoName::ALL_CHS)
+        .with(&[
+            "main_stat1_name_rect", "main_stat2_name_rect",
+            "sub_stat_name_1", "sub_stat_name_2", "sub_stat_name_3", "sub_stat_name_4", "sub_stat_name_5",
+        ], WW_STAT_NAMES_CHS);
 }
 
 impl WWEchoScannerWorker {
     pub fn new(
         window_info: EchoScannerWindowInfo,
-        config: WWEchoScannerConfig,
+        dumper: Option<Arc<Dumper>>,
+        ocr_model_config: &OCRModelConfig,
+        ocr_cache: Option<&Arc<OCRCache>>,
     ) -> Result<Self> {
+        let model = ocr_model_config.image_to_text(ocr_cache, |options| {
+            yas_ocr_model!("./models/model_training.onnx", "./models/index_2_word.json", "./models/manifest.json", options)
+        })?;
+
         Ok(Self {
-            model: get_image_to_text()?,
+            model,
             window_info,
-            config,
+            dumper,
         })
     }
 
-    fn model_inference(&self, rect: Rect<f64>, captured_img: &RgbImage) -> Result<String> {
-        let relative_rect = rect.translate(Pos {
-            x: -self.window_info.panel_rect.left,
-            y: -self.window_info.panel_rect.top,
-        });
-
-        let raw_img = captured_img.view(
-            relative_rect.left as u32, relative_rect.top as u32, relative_rect.width as u32, relative_rect.height as u32
-        ).to_image();
-
-        let inference_result = self.model.image_to_text(&raw_img, false);
+    /// the rects are regions of the panel, which are recognized in one batch
+    /// the names are used for the cropped images and the raw strings in dump mode, and as the keys of the confidence
+    fn model_inference<const N: usize>(&self, regions: [(&str, Rect<f64>); N], captured_img: &RgbImage, dump: Option<&ItemDump>) -> Result<([String; N], FieldConfidence)> {
+        let images = regions.iter()
+            .map(|&(_, rect)| {
+                let relative_rect = rect.translate(Pos {
+                    x: -self.window_info.panel_rect.left,
+                    y: -self.window_info.panel_rect.top,
+                });
+
+                captured_img.view(
+                    relative_rect.left as u32, relative_rect.top as u32, relative_rect.width as u32, relative_rect.height as u32,
+                ).to_image()
+            })
+            .collect::<Vec<_>>();
+
+        let lexicons = regions.iter().map(|&(name, _)| FIELD_LEXICONS.get(name)).collect::<Vec<_>>();
+        let inference_result = self.model.image_to_text_batch_with_lexicon(&images, false, &lexicons)?;
+
+        let mut confidence = FieldConfidence::new();
+        for ((name, _), result) in regions.iter().zip(inference_result.iter()) {
+            confidence.insert(name, result.confidence);
+        }
+        for (name, c) in confidence.low_confidence_fields(LOW_CONFIDENCE_THRESHOLD) {
+            warn!("{} 识别置信度较低：{:.2}", name, c);
+        }
+
+        if let Some(dump) = dump {
+            for (((name, _), raw_img), result) in regions.iter().zip(images.iter()).zip(inference_result.iter()) {
+                dump.record_ocr(name, raw_img, &result.text);
+            }
+        }
 
-        inference_result
+        let texts = inference_result.into_iter().map(|r| r.text).collect::<Vec<_>>();
+        let texts = texts.try_into().map_err(|_| anyhow!("OCR result count mismatch"))?;
+        Ok((texts, confidence))
     }
 
     fn determine_star(&self, im: &RgbImage) -> Result<usize> {
@@ -77,27 +110,47 @@ impl WWEchoScannerWorker {
         Ok(5)
     }
 
-    fn parse_item(&self, item: SendItem) -> Result<WWEchoScanResult> {
+    fn parse_item(&self, item: SendItem, dump: Option<&ItemDump>) -> Result<WWEchoScanResult> {
         let image = &item.panel_image;
-
-        let str_title = self.model_inference(self.window_info.title_rect, &image)?;
-        let str_main_stat1_name = self.model_inference(self.window_info.main_stat1_name_rect, &image)?;
-        let str_main_stat1_value = self.model_inference(self.window_info.main_stat1_value_rect, &image)?;
-        let str_main_stat2_name = self.model_inference(self.window_info.main_stat2_name_rect, &image)?;
-        let str_main_stat2_value = self.model_inference(self.window_info.main_stat2_value_rect, &image)?;
-
-        let str_sub_stat0_name = self.model_inference(self.window_info.sub_stat_name_1, &image)?;
-        let str_sub_stat1_name = self.model_inference(self.window_info.sub_stat_name_2, &image)?;
-        let str_sub_stat2_name = self.model_inference(self.window_info.sub_stat_name_3, &image)?;
-        let str_sub_stat3_name = self.model_inference(self.window_info.sub_stat_name_4, &image)?;
-        let str_sub_stat4_name = self.model_inference(self.window_info.sub_stat_name_5, &image)?;
-        let str_sub_stat0_value = self.model_inference(self.window_info.sub_stat_value_1, &image)?;
-        let str_sub_stat1_value = self.model_inference(self.window_info.sub_stat_value_2, &image)?;
-        let str_sub_stat2_value = self.model_inference(self.window_info.sub_stat_value_3, &image)?;
-        let str_sub_stat3_value = self.model_inference(self.window_info.sub_stat_value_4, &image)?;
-        let str_sub_stat4_value = self.model_inference(self.window_info.sub_stat_value_5, &image)?;
-
-        let str_level = self.model_inference(self.window_info.level_rect, &image)?;
+        if let Some(dump) = dump {
+            dump.save_image("panel", image);
+        }
+
+        let ([
+            str_title,
+            str_main_stat1_name,
+            str_main_stat1_value,
+            str_main_stat2_name,
+            str_main_stat2_value,
+            str_sub_stat0_name,
+            str_sub_stat1_name,
+            str_sub_stat2_name,
+            str_sub_stat3_name,
+            str_sub_stat4_name,
+            str_sub_stat0_value,
+            str_sub_stat1_value,
+            str_sub_stat2_value,
+            str_sub_stat3_value,
+            str_sub_stat4_value,
+            str_level,
+        ], confidence) = self.model_inference([
+            ("title_rect", self.window_info.title_rect),
+            ("main_stat1_name_rect", self.window_info.main_stat1_name_rect),
+            ("main_stat1_value_rect", self.window_info.main_stat1_value_rect),
+            ("main_stat2_name_rect", self.window_info.main_stat2_name_rect),
+            ("main_stat2_value_rect", self.window_info.main_stat2_value_rect),
+            ("sub_stat_name_1", self.window_info.sub_stat_name_1),
+            ("sub_stat_name_2", self.window_info.sub_stat_name_2),
+            ("sub_stat_name_3", self.window_info.sub_stat_name_3),
+            ("sub_stat_name_4", self.window_info.sub_stat_name_4),
+            ("sub_stat_name_5", self.window_info.sub_stat_name_5),
+            ("sub_stat_value_1", self.window_info.sub_stat_value_1),
+            ("sub_stat_value_2", self.window_info.sub_stat_value_2),
+            ("sub_stat_value_3", self.window_info.sub_stat_value_3),
+            ("sub_stat_value_4", self.window_info.sub_stat_value_4),
+            ("sub_stat_value_5", self.window_info.sub_stat_value_5),
+            ("level_rect", self.window_info.level_rect),
+        ], &image, dump)?;
         // let str_equip = self.model_inference(self.window_info., &image)?;
 
         let star = self.determine_star(&image)?;
@@ -126,50 +179,30 @@ impl WWEchoScannerWorker {
             // equip: item.equip + &str_equip,
             star,
             // lock: item.lock,
+            confidence,
         })
     }
 
-    pub fn run(self, rx: Receiver<SendItem>) -> JoinHandle<Vec<WWEchoScanResult>> {
-        std::thread::spawn(move || {
-            let mut results = Vec::new();
-            let mut hash = HashSet::new();
-            let mut consecutive_dup_count = 0;
-
-            let is_verbose = self.config.verbose;
-            let min_level = self.config.min_level;
-            let info = self.window_info.clone();
-
-            for (_cnt, item) in rx.into_iter().enumerate() {
-                let result = match self.parse_item(item) {
-                    Ok(v) => v,
-                    Err(e) => {
-                        // error!("识别错误: {}", e);
-                        continue;
-                    },
-                };
-
-                if is_verbose {
-                    info!("{:?}", result);
-                }
-
-                if hash.contains(&result) {
-                    consecutive_dup_count += 1;
-                    // warn!("识别到重复物品: {:#?}", result);
-                } else {
-                    consecutive_dup_count = 0;
-                    hash.insert(result.clone());
-                    results.push(result);
-                }
-
-                // if consecutive_dup_count >= info.col && !self.config.ignore_dup {
-                //     error!("识别到连续多个重复物品，可能为翻页错误，或者为非背包顶部开始扫描");
-                //     break;
-                // }
-            }
-
-            info!("识别结束，非重复物品数量: {}", hash.len());
+    fn recognize(&self, index: usize, item: SendItem) -> Result<WWEchoScanResult> {
+        let dump = self.dumper.as_ref().map(|d| d.item(index));
+        let result = self.parse_item(item, dump.as_ref());
+        if let Some(dump) = dump {
+            dump.finish(result.as_ref());
+        }
+        result
+    }
 
-            results
-        })
+    pub fn run(workers: Vec<Self>, rx: Receiver<SendItem>, config: &WWEchoScannerConfig) -> JoinHandle<RecognitionResult<WWEchoScanResult>> {
+        spawn_recognition(
+            workers.into_iter()
+                .map(|worker| move |index, item| worker.recognize(index, item))
+                .collect(),
+            rx,
+            RecognitionConfig {
+                verbose: config.verbose,
+                max_consecutive_dup: None,
+            },
+            |_, result: Result<WWEchoScanResult>| Ok(Recognized::Item(result?)),
+        )
     }
 }
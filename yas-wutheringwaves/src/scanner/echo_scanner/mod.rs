@@ -1,5 +1,6 @@
 pub use echo_scanner_config::WWEchoScannerConfig;
 pub use echo_scanner::WWEchoScanner;
+pub use echo_scanner_window_info::EchoScannerWindowInfo;
 
 mod message_item;
 mod echo_scanner_config;
@@ -1,4 +1,9 @@
-#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+use yas::ocr::FieldConfidence;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WWEchoScanResult {
     pub name: String,
     pub main_stat1_name: String,
@@ -10,4 +15,63 @@ pub struct WWEchoScanResult {
     // pub equip: String,
     pub level: usize,
     pub star: usize,
+    /// OCR confidence of each field, not taken into account when comparing results
+    pub confidence: FieldConfidence,
+}
+
+// the confidence differs between two reads of the same item, which is identified by the other fields.
+// The fields are destructured so that a new one is not left out
+
+impl PartialEq for WWEchoScanResult {
+    fn eq(&self, other: &Self) -> bool {
+        let Self {
+            name,
+            main_stat1_name,
+            main_stat1_value,
+            main_stat2_name,
+            main_stat2_value,
+            sub_stat_names,
+            sub_stat_values,
+            level,
+            star,
+            confidence: _,
+        } = self;
+        *name == other.name
+            && *main_stat1_name == other.main_stat1_name
+            && *main_stat1_value == other.main_stat1_value
+            && *main_stat2_name == other.main_stat2_name
+            && *main_stat2_value == other.main_stat2_value
+            && *sub_stat_names == other.sub_stat_names
+            && *sub_stat_values == other.sub_stat_values
+            && *level == other.level
+            && *star == other.star
+    }
+}
+
+impl Eq for WWEchoScanResult {}
+
+impl Hash for WWEchoScanResult {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let Self {
+            name,
+            main_stat1_name,
+            main_stat1_value,
+            main_stat2_name,
+            main_stat2_value,
+            sub_stat_names,
+            sub_stat_values,
+            level,
+            star,
+            confidence: _,
+        } = self;
+        name.hash(state);
+        main_stat1_name.hash(state);
+        main_stat1_value.hash(state);
+        main_stat2_name.hash(state);
+        main_stat2_value.hash(state);
+        sub_stat_names.hash(state);
+        sub_stat_values.hash(state);
+        level.hash(state);
+        star.hash(state);
+    }
 }
@@ -1,7 +1,6 @@
-pub use config::WWRepositoryLayoutConfig;
 pub use repository_layout_window_info::WWRepositoryLayoutWindowinfo;
-pub use scan_logic::{ReturnResult, WWRepositoryLayoutScanController};
+pub use scan_logic::WWRepositoryLayoutScanController;
+pub use yas::scanner_controller::repository_layout::{GridScanControllerConfig as WWRepositoryLayoutConfig, ReturnResult, ScanState};
 
 mod repository_layout_window_info;
-mod config;
 mod scan_logic;
@@ -0,0 +1,67 @@
+//! The generic behaviour of the grid controller is tested in yas, this checks the fixed row scroll
+//! and the white pixel change detection of wuthering waves
+
+use yas::game_info::{GameInfo, Platform, ResolutionFamily, UI};
+use yas::positioning::{Pos, Rect, Size};
+use yas::simulation::{all_items, SimulatedInventory};
+use yas::window_info::{load_window_info_repo, FromWindowInfoRepository, WindowInfoRepository};
+use yas_wutheringwaves::scanner::EchoScannerWindowInfo;
+use yas_wutheringwaves::scanner_controller::{ReturnResult, WWRepositoryLayoutConfig, WWRepositoryLayoutWindowinfo};
+
+const WINDOW_SIZE: Size<usize> = Size { width: 2560, height: 1440 };
+
+fn window_info_repo() -> WindowInfoRepository {
+    load_window_info_repo!("../window_info/windows2560x1440.json")
+}
+
+fn game_info() -> GameInfo {
+    GameInfo {
+        window: Rect::new(0, 0, WINDOW_SIZE.width as i32, WINDOW_SIZE.height as i32),
+        resolution_family: ResolutionFamily::Windows16x9,
+        is_cloud: false,
+        ui: UI::Desktop,
+        platform: Platform::Windows,
+    }
+}
+
+#[test]
+fn scans_every_item_in_order() {
+    let item_count = 61;
+    let window_info = WWRepositoryLayoutWindowinfo::from_window_info_repository(
+        WINDOW_SIZE, UI::Desktop, Platform::Windows, &window_info_repo(),
+    ).unwrap();
+    // the controller scrolls a row with 8 wheel events
+    let scroll_per_wheel = (window_info.item_size.height + window_info.item_gap_size.height) / 8.0;
+    let inventory = SimulatedInventory::from_window_info(&window_info, &game_info(), item_count, scroll_per_wheel);
+    inventory.set_switch_delay(3);
+
+    let config = WWRepositoryLayoutConfig {
+        scroll_delay: 0,
+        max_wait_switch_item: 200,
+        ..Default::default()
+    };
+    let controller = inventory.controller(window_info, config, game_info());
+
+    let (selected, result) = inventory.scan(controller, item_count);
+    assert!(matches!(result, Ok(ReturnResult::Finished)));
+    assert_eq!(selected, all_items(item_count));
+    assert_eq!(inventory.wheel_events(), 7 * 8);
+}
+
+/// 32:9 is not recorded, the layout is derived from the anchored 16:9 one, scaled by the height
+#[test]
+fn derives_the_layout_of_a_32x9_window() {
+    let window_size = Size::new(5120, 1440);
+
+    let info = WWRepositoryLayoutWindowinfo::from_window_info_repository(
+        window_size, UI::Desktop, Platform::Windows, &window_info_repo(),
+    ).unwrap();
+    // the panel sticks to the right edge, the items to the left
+    assert_eq!(info.panel_rect.left, 5120.0 - (2560.0 - 1728.0));
+    assert_eq!(info.scan_margin_pos, Pos::new(279.0, 167.0));
+
+    let echo = EchoScannerWindowInfo::from_window_info_repository(
+        window_size, UI::Desktop, Platform::Windows, &window_info_repo(),
+    ).unwrap();
+    assert!(echo.title_rect.left > info.panel_rect.left);
+}
@@ -0,0 +1,42 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use image::{Rgb, RgbImage};
+use yas_core::ocr::{pre_process, to_gray, Preprocessor, PREPROCESSED_LEN};
+
+/// Light strokes on a dark background, roughly what a cropped item name looks like
+fn text_image(width: u32, height: u32) -> RgbImage {
+    RgbImage::from_fn(width, height, |x, y| {
+        let in_margin = y < height / 5 || y >= height - height / 5;
+        let stroke = !in_margin && (x / 3) % 4 != 0 && (x / 17) % 5 != 4;
+        if stroke {
+            Rgb([235, 230, 220])
+        } else {
+            Rgb([40 + (x % 7) as u8, 45, 60 + (y % 5) as u8])
+        }
+    })
+}
+
+fn preprocess(c: &mut Criterion) {
+    let mut group = c.benchmark_group("preprocess");
+
+    for &(width, height) in [(200, 25), (400, 40), (800, 60)].iter() {
+        let image = text_image(width, height);
+        let size = format!("{}x{}", width, height);
+
+        group.bench_with_input(BenchmarkId::new("image_buffer", &size), &image, |b, image| {
+            b.iter(|| pre_process(to_gray(black_box(image))))
+        });
+
+        let mut preprocessor = Preprocessor::new();
+        let mut out = vec![0.0; PREPROCESSED_LEN];
+        group.bench_with_input(BenchmarkId::new("row_major", &size), &image, |b, image| {
+            b.iter(|| {
+                preprocessor.pre_process_rgb(black_box(image.as_raw()), width as usize, height as usize, &mut out)
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, preprocess);
+criterion_main!(benches);
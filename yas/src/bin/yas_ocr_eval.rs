@@ -0,0 +1,60 @@
+use anyhow::{anyhow, Result};
+use clap::{command, Args, FromArgMatches};
+use image::RgbImage;
+use log::info;
+use yas_core::ocr::{ImageToText, OCRModelConfig, PPOCRChV4RecInfer};
+use yas_core::ocr_evaluation::{EvaluationModel, LabelledDataset, OCREvaluationConfig, OCREvaluator};
+
+fn get_model(config: &OCREvaluationConfig, ocr_model_config: &OCRModelConfig) -> Result<Box<dyn ImageToText<RgbImage>>> {
+    let model: Box<dyn ImageToText<RgbImage>> = match config.model {
+        EvaluationModel::Yas => Box::new(
+            ocr_model_config.load_model()?
+                .ok_or_else(|| anyhow!("评估 yas 模型需要通过 --ocr-model 和 --ocr-dict 指定模型"))?
+        ),
+        EvaluationModel::PPOCR => Box::new(PPOCRChV4RecInfer::new_with_options(&ocr_model_config.inference_options())?),
+    };
+    Ok(model)
+}
+
+fn run() -> Result<()> {
+    let mut cmd = command!()
+        .about("在标注数据集上评估 OCR 模型的识别准确率");
+    cmd = <OCREvaluationConfig as Args>::augment_args_for_update(cmd);
+    cmd = <OCRModelConfig as Args>::augment_args_for_update(cmd);
+    let matches = cmd.get_matches();
+
+    let config = OCREvaluationConfig::from_arg_matches(&matches)?;
+    let ocr_model_config = OCRModelConfig::from_arg_matches(&matches)?;
+
+    let dataset = LabelledDataset::load(&config.dataset)?;
+    info!("共 {} 个样本", dataset.len());
+    let model = get_model(&config, &ocr_model_config)?;
+
+    let report = OCREvaluator::new(model.as_ref()).evaluate(&dataset)?;
+
+    if config.top_confusions > 0 && !report.confusions.is_empty() {
+        println!("{}", report.get_confusion_table(config.top_confusions));
+    }
+    if config.show_failures > 0 && !report.failures.is_empty() {
+        println!("{}", report.get_failure_table(config.show_failures));
+    }
+    println!("{}", report);
+
+    if let Some(output) = &config.output {
+        report.save(output)?;
+        info!("评估结果已保存到 {:?}", output);
+    }
+
+    Ok(())
+}
+
+pub fn main() {
+    env_logger::Builder::new()
+        .filter_level(log::LevelFilter::Info)
+        .init();
+
+    if let Err(e) = run() {
+        log::error!("error: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,27 @@
+use std::path::PathBuf;
+
+#[derive(Clone, clap::Args)]
+pub struct CalibrationConfig {
+    /// The layout which the new one is derived from
+    #[arg(id = "reference-layout", long = "reference-layout", help = "参考窗口布局文件（JSON）", value_name = "FILE")]
+    pub reference_layout: PathBuf,
+
+    /// A screenshot of the game window, in the resolution of the reference layout
+    #[arg(id = "reference-image", long = "reference-image", help = "参考布局分辨率下的游戏窗口截图", value_name = "FILE")]
+    pub reference_image: PathBuf,
+
+    /// A screenshot of the game window in the new resolution, with the same page opened
+    #[arg(id = "screenshot", long = "screenshot", help = "新分辨率下的游戏窗口截图，需要和参考截图打开相同的界面", value_name = "FILE")]
+    pub screenshot: PathBuf,
+
+    #[arg(id = "output", long = "output", short = 'o', help = "输出的窗口布局文件", value_name = "FILE", default_value = "window_info.json")]
+    pub output: PathBuf,
+
+    /// Matches below this score are replaced by the value scaled from the reference
+    #[arg(id = "min-confidence", long = "min-confidence", help = "最低匹配置信度，低于该值时使用按比例缩放的值", default_value_t = 0.6)]
+    pub min_confidence: f64,
+
+    /// How far a region may be away from where it's expected, in pixels of the new resolution
+    #[arg(id = "search-radius", long = "search-radius", help = "匹配搜索半径（像素）", default_value_t = 48)]
+    pub search_radius: u32,
+}
@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use image::{GrayImage, RgbImage};
+use image::imageops::{self, FilterType};
+use prettytable::{row, Table};
+
+use crate::calibration::{CalibrationConfig, TemplateMatcher};
+use crate::positioning::{Pos, Rect, Size};
+use crate::window_info::{fit_factor, Anchor, AnchorX, AnchorY, WindowInfoTemplatePerSize, WindowInfoType};
+
+/// Half of the side of the square matched around a position, in pixels of the reference
+const POS_PATCH_RADIUS: f64 = 20.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CalibrationMethod {
+    /// Located in the screenshot
+    Matched,
+    /// The match failed or was not confident, scaled from the reference instead
+    Fallback,
+    /// Sizes and lengths, which can't be matched and are scaled from the reference
+    Scaled,
+    /// Invariant values
+    Copied,
+}
+
+impl fmt::Display for CalibrationMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            CalibrationMethod::Matched => "matched",
+            CalibrationMethod::Fallback => "fallback",
+            CalibrationMethod::Scaled => "scaled",
+            CalibrationMethod::Copied => "copied",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+pub struct CalibrationEntry {
+    pub key: String,
+    pub value: WindowInfoType,
+    pub method: CalibrationMethod,
+    /// Match score of the best candidate, even if it's rejected
+    pub confidence: Option<f64>,
+    /// How far the match is from the value scaled from the reference
+    pub offset: Option<Pos<f64>>,
+}
+
+pub struct CalibrationReport {
+    pub resolution: Size<usize>,
+    pub template: WindowInfoTemplatePerSize,
+    pub entries: Vec<CalibrationEntry>,
+}
+
+/// Derives a layout for a new resolution, by locating the regions of a reference layout in a screenshot
+pub struct Calibrator {
+    reference: WindowInfoTemplatePerSize,
+    reference_image: GrayImage,
+    matcher: TemplateMatcher,
+    target_size: Size<usize>,
+    min_confidence: f64,
+    search_radius: u32,
+}
+
+fn round_value(value: WindowInfoType) -> WindowInfoType {
+    let round_rect = |r: Rect<f64>| Rect::new(r.left.round(), r.top.round(), r.width.round(), r.height.round());
+    let round_pos = |p: Pos<f64>| Pos { x: p.x.round(), y: p.y.round() };
+    let round_2 = |v: f64| (v * 100.0).round() / 100.0;
+
+    match value {
+        WindowInfoType::Rect(rect) => WindowInfoType::Rect(round_rect(rect)),
+        WindowInfoType::Pos(pos) => WindowInfoType::Pos(round_pos(pos)),
+        WindowInfoType::AnchoredRect { anchor, rect } => WindowInfoType::AnchoredRect { anchor, rect: round_rect(rect) },
+        WindowInfoType::AnchoredPos { anchor, pos } => WindowInfoType::AnchoredPos { anchor, pos: round_pos(pos) },
+        WindowInfoType::Size(size) => WindowInfoType::Size(Size::new(round_2(size.width), round_2(size.height))),
+        WindowInfoType::Float(v) => WindowInfoType::Float(round_2(v)),
+        _ => value,
+    }
+}
+
+impl Calibrator {
+    pub fn new(
+        reference: WindowInfoTemplatePerSize,
+        reference_image: &RgbImage,
+        screenshot: &RgbImage,
+        min_confidence: f64,
+        search_radius: u32,
+    ) -> Result<Self> {
+        let resolution = reference.current_resolution;
+        if reference_image.width() as usize != resolution.width || reference_image.height() as usize != resolution.height {
+            return Err(anyhow!(
+                "参考截图尺寸 {}x{} 与参考布局分辨率 {}x{} 不一致",
+                reference_image.width(), reference_image.height(), resolution.width, resolution.height
+            ));
+        }
+
+        Ok(Self {
+            reference,
+            reference_image: imageops::grayscale(reference_image),
+            matcher: TemplateMatcher::new(imageops::grayscale(screenshot)),
+            target_size: Size::new(screenshot.width() as usize, screenshot.height() as usize),
+            min_confidence,
+            search_radius,
+        })
+    }
+
+    pub fn from_config(config: &CalibrationConfig) -> Result<Self> {
+        let reference = WindowInfoTemplatePerSize::from_file(&config.reference_layout)?;
+        let open = |path: &Path| -> Result<RgbImage> {
+            Ok(image::open(path).map_err(|e| anyhow!("无法读取截图 {:?}：{}", path, e))?.to_rgb8())
+        };
+
+        Self::new(
+            reference,
+            &open(&config.reference_image)?,
+            &open(&config.screenshot)?,
+            config.min_confidence,
+            config.search_radius,
+        )
+    }
+
+    fn factor(&self) -> f64 {
+        fit_factor(self.reference.current_resolution, self.target_size)
+    }
+
+    /// The anchors tried for a value without one. When the aspect ratio is unchanged, all anchors agree
+    fn candidate_anchors(&self) -> Vec<Anchor> {
+        let from = self.reference.current_resolution;
+        let to = self.target_size;
+        if from.width * to.height == to.width * from.height {
+            return vec![Anchor::default()];
+        }
+
+        let mut result = Vec::new();
+        for y in [AnchorY::Top, AnchorY::Center, AnchorY::Bottom] {
+            for x in [AnchorX::Left, AnchorX::Center, AnchorX::Right] {
+                result.push(Anchor { x, y });
+            }
+        }
+        result
+    }
+
+    /// Find the top left corner of a reference region in the screenshot, along with the match score.
+    /// Some surroundings of the region are included in the patch, so that regions of varying text are still matched by the frame around them
+    fn locate(&self, region: Rect<f64>, margin: f64, anchors: &[Anchor]) -> Option<(Pos<f64>, f64)> {
+        let (ref_w, ref_h) = self.reference_image.dimensions();
+        let left = (region.left - margin).floor().max(0.0) as u32;
+        let top = (region.top - margin).floor().max(0.0) as u32;
+        let right = ((region.left + region.width + margin).ceil() as u32).min(ref_w);
+        let bottom = ((region.top + region.height + margin).ceil() as u32).min(ref_h);
+        if right <= left || bottom <= top {
+            return None;
+        }
+
+        let factor = self.factor();
+        let crop = imageops::crop_imm(&self.reference_image, left, top, right - left, bottom - top).to_image();
+        let patch = imageops::resize(
+            &crop,
+            ((crop.width() as f64 * factor).round() as u32).max(1),
+            ((crop.height() as f64 * factor).round() as u32).max(1),
+            FilterType::Triangle,
+        );
+
+        let from = self.reference.current_resolution;
+        let mut best: Option<(Pos<i32>, f64)> = None;
+        for anchor in anchors.iter() {
+            let (x, y) = anchor.map(left as f64, top as f64, from, self.target_size);
+            let expected = Pos { x: x.round() as i32, y: y.round() as i32 };
+            if let Some(m) = self.matcher.match_template(&patch, expected, self.search_radius) {
+                if best.map(|(_, score)| m.score > score).unwrap_or(true) {
+                    best = Some((m.pos, m.score));
+                }
+            }
+        }
+
+        best.map(|(pos, score)| (
+            Pos {
+                x: pos.x as f64 + (region.left - left as f64) * factor,
+                y: pos.y as f64 + (region.top - top as f64) * factor,
+            },
+            score
+        ))
+    }
+
+    fn calibrate_value(&self, value: WindowInfoType) -> (WindowInfoType, CalibrationMethod, Option<f64>, Option<Pos<f64>>) {
+        let from = self.reference.current_resolution;
+        let factor = self.factor();
+        let scaled = value.resolve(from, self.target_size);
+
+        let (region, margin) = match value {
+            WindowInfoType::Rect(rect) | WindowInfoType::AnchoredRect { rect, .. } => {
+                (rect, (rect.width.min(rect.height) * 0.1).max(8.0))
+            },
+            WindowInfoType::Pos(pos) | WindowInfoType::AnchoredPos { pos, .. } => {
+                let r = POS_PATCH_RADIUS;
+                (Rect::new(pos.x - r, pos.y - r, 2.0 * r, 2.0 * r), 0.0)
+            },
+            WindowInfoType::Size(_) | WindowInfoType::Float(_) => return (scaled, CalibrationMethod::Scaled, None, None),
+            WindowInfoType::InvariantInt(_) | WindowInfoType::InvariantFloat(_) => return (value, CalibrationMethod::Copied, None, None),
+        };
+        let anchors = match value {
+            WindowInfoType::AnchoredRect { anchor, .. } | WindowInfoType::AnchoredPos { anchor, .. } => vec![anchor],
+            _ => self.candidate_anchors(),
+        };
+
+        let (found, confidence) = match self.locate(region, margin, &anchors) {
+            Some(v) => v,
+            None => return (scaled, CalibrationMethod::Fallback, None, None),
+        };
+        if confidence < self.min_confidence {
+            return (scaled, CalibrationMethod::Fallback, Some(confidence), None);
+        }
+
+        let new_rect = |rect: Rect<f64>| Rect::new(found.x, found.y, rect.width * factor, rect.height * factor);
+        let new_pos = Pos { x: found.x + POS_PATCH_RADIUS * factor, y: found.y + POS_PATCH_RADIUS * factor };
+        let new_value = match value {
+            WindowInfoType::Rect(rect) => WindowInfoType::Rect(new_rect(rect)),
+            WindowInfoType::AnchoredRect { anchor, rect } => WindowInfoType::AnchoredRect { anchor, rect: new_rect(rect) },
+            WindowInfoType::Pos(_) => WindowInfoType::Pos(new_pos),
+            WindowInfoType::AnchoredPos { anchor, .. } => WindowInfoType::AnchoredPos { anchor, pos: new_pos },
+            _ => unreachable!(),
+        };
+
+        let offset = match (new_value, scaled) {
+            (WindowInfoType::Rect(r) | WindowInfoType::AnchoredRect { rect: r, .. }, WindowInfoType::Rect(s)) => Pos { x: r.left - s.left, y: r.top - s.top },
+            (WindowInfoType::Pos(p) | WindowInfoType::AnchoredPos { pos: p, .. }, WindowInfoType::Pos(s)) => Pos { x: p.x - s.x, y: p.y - s.y },
+            _ => unreachable!(),
+        };
+
+        (new_value, CalibrationMethod::Matched, Some(confidence), Some(offset))
+    }
+
+    pub fn calibrate(&self) -> CalibrationReport {
+        let mut keys = self.reference.data.keys().collect::<Vec<_>>();
+        keys.sort();
+
+        let mut entries = Vec::new();
+        let mut data = HashMap::new();
+        for key in keys {
+            let (value, method, confidence, offset) = self.calibrate_value(self.reference.data[key]);
+            let value = round_value(value);
+            data.insert(key.clone(), value);
+            entries.push(CalibrationEntry {
+                key: key.clone(),
+                value,
+                method,
+                confidence,
+                offset,
+            });
+        }
+
+        CalibrationReport {
+            resolution: self.target_size,
+            template: WindowInfoTemplatePerSize {
+                current_resolution: self.target_size,
+                platform: self.reference.platform,
+                ui: self.reference.ui,
+                data,
+            },
+            entries,
+        }
+    }
+}
+
+impl CalibrationReport {
+    /// Write the layout with sorted keys, so that it diffs well
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let value = serde_json::to_value(&self.template)?;
+        std::fs::write(path, serde_json::to_string_pretty(&value)?)?;
+        Ok(())
+    }
+
+    pub fn count(&self, method: CalibrationMethod) -> usize {
+        self.entries.iter().filter(|e| e.method == method).count()
+    }
+
+    pub fn get_table(&self) -> Table {
+        let mut table = Table::new();
+
+        table.add_row(row!["Key", "Method", "Confidence", "Offset", "Value"]);
+        for entry in self.entries.iter() {
+            let confidence = entry.confidence.map(|c| format!("{:.3}", c)).unwrap_or(String::from("-"));
+            let offset = entry.offset.map(|p| format!("({:+.0}, {:+.0})", p.x, p.y)).unwrap_or(String::from("-"));
+            table.add_row(row![entry.key, entry.method, confidence, offset, format!("{:?}", entry.value)]);
+        }
+
+        table
+    }
+}
+
+impl fmt::Display for CalibrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.get_table())?;
+        write!(
+            f,
+            "{}x{}: {} matched, {} fallback, {} scaled, {} copied",
+            self.resolution.width,
+            self.resolution.height,
+            self.count(CalibrationMethod::Matched),
+            self.count(CalibrationMethod::Fallback),
+            self.count(CalibrationMethod::Scaled),
+            self.count(CalibrationMethod::Copied),
+        )
+    }
+}
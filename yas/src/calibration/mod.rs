@@ -0,0 +1,7 @@
+mod calibration_config;
+mod template_matching;
+mod calibrator;
+
+pub use calibration_config::CalibrationConfig;
+pub use template_matching::{TemplateMatcher, MatchResult};
+pub use calibrator::{Calibrator, CalibrationReport, CalibrationEntry, CalibrationMethod};
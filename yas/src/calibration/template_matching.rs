@@ -0,0 +1,145 @@
+use std::cell::{Ref, RefCell};
+use std::collections::HashMap;
+
+use image::{GrayImage, ImageBuffer, Luma};
+use image::imageops::{self, FilterType};
+
+use crate::positioning::Pos;
+
+type F32GrayImage = ImageBuffer<Luma<f32>, Vec<f32>>;
+
+/// Patches are downscaled to about this many pixels for the coarse search
+const COARSE_PATCH_AREA: f64 = 2048.0;
+
+#[derive(Debug, Copy, Clone)]
+pub struct MatchResult {
+    /// Top left corner of the patch in the searched image
+    pub pos: Pos<i32>,
+    /// Normalized cross correlation, in [-1, 1]
+    pub score: f64,
+}
+
+struct Patch {
+    data: F32GrayImage,
+    mean: f64,
+    norm: f64,
+}
+
+impl Patch {
+    fn new(data: F32GrayImage) -> Self {
+        let n = data.len() as f64;
+        let mean = data.iter().map(|&v| v as f64).sum::<f64>() / n;
+        let norm = data.iter().map(|&v| (v as f64 - mean).powi(2)).sum::<f64>().sqrt();
+        Self { data, mean, norm }
+    }
+}
+
+fn to_f32(image: &GrayImage) -> F32GrayImage {
+    ImageBuffer::from_fn(image.width(), image.height(), |x, y| Luma([image.get_pixel(x, y)[0] as f32]))
+}
+
+/// Normalized cross correlation of the patch placed at (x, y), the patch must be inside the image
+fn ncc_at(image: &F32GrayImage, patch: &Patch, x: u32, y: u32) -> f64 {
+    let (w, h) = patch.data.dimensions();
+    let mut sum = 0.0;
+    let mut sum_sq = 0.0;
+    let mut cross = 0.0;
+    for py in 0..h {
+        for px in 0..w {
+            let t = image.get_pixel(x + px, y + py)[0] as f64;
+            let p = patch.data.get_pixel(px, py)[0] as f64;
+            sum += t;
+            sum_sq += t * t;
+            cross += (p - patch.mean) * t;
+        }
+    }
+
+    let n = (w * h) as f64;
+    let image_norm = (sum_sq - sum * sum / n).max(0.0).sqrt();
+    let denominator = patch.norm * image_norm;
+    // flat regions carry no information
+    if denominator < 1e-6 {
+        0.0
+    } else {
+        cross / denominator
+    }
+}
+
+/// Locates patches in an image by normalized cross correlation, within a window around where they're expected.
+/// A coarse search on downscaled images is refined at full resolution
+pub struct TemplateMatcher {
+    image: F32GrayImage,
+    gray: GrayImage,
+    // downscaled copies of the image, keyed by the scale divisor
+    pyramid: RefCell<HashMap<u32, F32GrayImage>>,
+}
+
+impl TemplateMatcher {
+    pub fn new(image: GrayImage) -> Self {
+        Self {
+            image: to_f32(&image),
+            gray: image,
+            pyramid: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn downscaled(&self, divisor: u32) -> Ref<'_, F32GrayImage> {
+        if !self.pyramid.borrow().contains_key(&divisor) {
+            let w = (self.gray.width() / divisor).max(1);
+            let h = (self.gray.height() / divisor).max(1);
+            let image = to_f32(&imageops::resize(&self.gray, w, h, FilterType::Triangle));
+            self.pyramid.borrow_mut().insert(divisor, image);
+        }
+        Ref::map(self.pyramid.borrow(), |pyramid| &pyramid[&divisor])
+    }
+
+    /// Best position in `[expected - radius, expected + radius]` on both axes, clamped to the image
+    fn search(image: &F32GrayImage, patch: &Patch, expected: Pos<i32>, radius: i32) -> Option<MatchResult> {
+        let (w, h) = patch.data.dimensions();
+        if w > image.width() || h > image.height() {
+            return None;
+        }
+        let max_x = (image.width() - w) as i32;
+        let max_y = (image.height() - h) as i32;
+
+        let mut best: Option<MatchResult> = None;
+        for y in (expected.y - radius).max(0)..=(expected.y + radius).min(max_y) {
+            for x in (expected.x - radius).max(0)..=(expected.x + radius).min(max_x) {
+                let score = ncc_at(image, patch, x as u32, y as u32);
+                if best.map(|b| score > b.score).unwrap_or(true) {
+                    best = Some(MatchResult { pos: Pos { x, y }, score });
+                }
+            }
+        }
+
+        best
+    }
+
+    pub fn match_template(&self, patch: &GrayImage, expected: Pos<i32>, radius: u32) -> Option<MatchResult> {
+        let (w, h) = patch.dimensions();
+        if w == 0 || h == 0 {
+            return None;
+        }
+        let radius = radius as i32;
+
+        let divisor = ((w * h) as f64 / COARSE_PATCH_AREA).sqrt().floor() as u32;
+        // keep enough of the patch to be meaningful
+        let divisor = divisor.min(w.min(h) / 4).max(1);
+        if divisor == 1 {
+            return Self::search(&self.image, &Patch::new(to_f32(patch)), expected, radius);
+        }
+
+        let coarse_image = self.downscaled(divisor);
+        let coarse_patch = imageops::resize(patch, w / divisor, h / divisor, FilterType::Triangle);
+        let coarse_expected = Pos { x: expected.x / divisor as i32, y: expected.y / divisor as i32 };
+        let coarse = Self::search(
+            &coarse_image,
+            &Patch::new(to_f32(&coarse_patch)),
+            coarse_expected,
+            radius / divisor as i32 + 1
+        )?;
+
+        let refine_expected = Pos { x: coarse.pos.x * divisor as i32, y: coarse.pos.y * divisor as i32 };
+        Self::search(&self.image, &Patch::new(to_f32(patch)), refine_expected, divisor as i32)
+    }
+}
@@ -0,0 +1,11 @@
+use clap::ValueEnum;
+
+/// Screen capture backend, only takes effect on linux
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum CapturerBackend {
+    /// Wayland if `WAYLAND_DISPLAY` is set, otherwise X11
+    #[default]
+    Auto,
+    X11,
+    Wayland,
+}
@@ -0,0 +1,32 @@
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use anyhow::Result;
+use image::RgbImage;
+
+use crate::capture::{Capturer, CapturerBackend, GenericCapturer, ReplayCapturer};
+
+#[derive(Clone, clap::Args, Default)]
+pub struct CapturerConfig {
+    /// Replay a directory of recorded full-window screenshots, instead of capturing the game window
+    #[arg(id = "replay", long = "replay", help = "使用截图目录回放扫描，不需要游戏窗口", value_name = "DIR")]
+    pub replay: Option<PathBuf>,
+
+    /// The screen capture backend on linux
+    #[arg(id = "capturer", long = "capturer", default_value_t = CapturerBackend::Auto, help = "截图方式（仅 Linux）")]
+    #[arg(value_enum)]
+    pub backend: CapturerBackend,
+}
+
+impl CapturerConfig {
+    /// The capturer of a scan, and the replay if it goes through recorded screenshots,
+    /// which the system control of the scan has to advance (see `ReplayControl::or_generic`)
+    pub fn build(&self) -> Result<(Rc<dyn Capturer<RgbImage>>, Option<Rc<ReplayCapturer>>)> {
+        if let Some(dir) = &self.replay {
+            let replay = Rc::new(ReplayCapturer::new(dir)?);
+            Ok((replay.clone(), Some(replay)))
+        } else {
+            Ok((Rc::new(GenericCapturer::with_backend(self.backend)?), None))
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use anyhow::{anyhow, Result};
+use image::RgbImage;
+
+use crate::capture::Capturer;
+use crate::positioning::{Pos, Rect};
+
+/// A single capture of a screen region, usually the whole game window.
+/// Rects and pixels are read from the in-memory frame instead of grabbing the screen again,
+/// coordinates are screen coordinates, the same as for any other capturer
+pub struct FrameSnapshot {
+    image: RgbImage,
+    rect: Rect<i32>,
+}
+
+impl FrameSnapshot {
+    /// Grab `rect` with the capturer once
+    pub fn capture(capturer: &dyn Capturer<RgbImage>, rect: Rect<i32>) -> Result<Self> {
+        let image = capturer.capture_rect(rect)?;
+        Ok(Self::from_image(image, Pos { x: rect.left, y: rect.top }))
+    }
+
+    /// Wrap an already captured image, whose top left pixel is at `origin` on the screen
+    pub fn from_image(image: RgbImage, origin: Pos<i32>) -> Self {
+        let rect = Rect::new(origin.x, origin.y, image.width() as i32, image.height() as i32);
+        Self {
+            image,
+            rect,
+        }
+    }
+
+    pub fn image(&self) -> &RgbImage {
+        &self.image
+    }
+
+    pub fn rect(&self) -> Rect<i32> {
+        self.rect
+    }
+
+    fn contains(&self, rect: Rect<i32>) -> bool {
+        rect.width > 0 && rect.height > 0
+            && rect.left >= self.rect.left
+            && rect.top >= self.rect.top
+            && rect.left + rect.width <= self.rect.left + self.rect.width
+            && rect.top + rect.height <= self.rect.top + self.rect.height
+    }
+}
+
+impl Capturer<RgbImage> for FrameSnapshot {
+    fn capture_rect(&self, rect: Rect<i32>) -> Result<RgbImage> {
+        if !self.contains(rect) {
+            return Err(anyhow!("capture region {} is out of frame {}", rect, self.rect));
+        }
+
+        let image = image::imageops::crop_imm(
+            &self.image,
+            (rect.left - self.rect.left) as u32,
+            (rect.top - self.rect.top) as u32,
+            rect.width as u32,
+            rect.height as u32,
+        ).to_image();
+
+        Ok(image)
+    }
+
+    fn capture_color(&self, pos: Pos<i32>) -> Result<image::Rgb<u8>> {
+        let rect = Rect::new(pos.x, pos.y, 1, 1);
+        if !self.contains(rect) {
+            return Err(anyhow!("capture position {} is out of frame {}", pos, self.rect));
+        }
+
+        Ok(*self.image.get_pixel((pos.x - self.rect.left) as u32, (pos.y - self.rect.top) as u32))
+    }
+}
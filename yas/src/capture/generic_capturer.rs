@@ -1,12 +1,98 @@
+#[cfg(target_os = "linux")]
+use anyhow::{anyhow, Result};
+#[cfg(target_os = "linux")]
+use image::RgbImage;
+#[cfg(target_os = "linux")]
+use log::warn;
+
+use crate::capture::CapturerBackend;
+#[cfg(target_os = "linux")]
+use crate::capture::Capturer;
+#[cfg(target_os = "linux")]
+use crate::positioning::Rect;
+
 #[cfg(target_os = "windows")]
 use crate::capture::WindowsCapturer;
 #[cfg(target_os = "windows")]
 pub type GenericCapturer = WindowsCapturer;
 
+#[cfg(target_os = "windows")]
+impl WindowsCapturer {
+    /// There is only one backend on windows
+    pub fn with_backend(_backend: CapturerBackend) -> anyhow::Result<Self> {
+        Self::new()
+    }
+}
+
+/// Connects to one of the backends
+#[cfg(target_os = "linux")]
+type BackendInit = fn() -> Result<Box<dyn Capturer<RgbImage> + Send>>;
+
+/// On linux the backend is selected at runtime, because a Wayland compositor and an X server
+/// (or XWayland, where Wine usually runs) may both be available
 #[cfg(target_os = "linux")]
-use crate::capture::LibwayshotCapturer;
+pub struct GenericCapturer {
+    inner: Box<dyn Capturer<RgbImage> + Send>,
+}
+
+#[cfg(target_os = "linux")]
+impl GenericCapturer {
+    pub fn new() -> Result<Self> {
+        Self::with_backend(CapturerBackend::Auto)
+    }
+
+    pub fn with_backend(backend: CapturerBackend) -> Result<Self> {
+        let inner = match backend {
+            CapturerBackend::X11 => Self::x11()?,
+            CapturerBackend::Wayland => Self::wayland()?,
+            CapturerBackend::Auto => {
+                let is_wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+                let (first, second): (BackendInit, BackendInit) = if is_wayland {
+                    (Self::wayland, Self::x11)
+                } else {
+                    (Self::x11, Self::wayland)
+                };
+
+                match first() {
+                    Ok(v) => v,
+                    Err(e) => {
+                        warn!("截图方式初始化失败，尝试另一种：{}", e);
+                        second()?
+                    }
+                }
+            }
+        };
+
+        Ok(Self { inner })
+    }
+
+    #[cfg(feature = "capturer_x11")]
+    fn x11() -> Result<Box<dyn Capturer<RgbImage> + Send>> {
+        Ok(Box::new(crate::capture::X11Capturer::new()?))
+    }
+
+    #[cfg(not(feature = "capturer_x11"))]
+    fn x11() -> Result<Box<dyn Capturer<RgbImage> + Send>> {
+        Err(anyhow!("X11 capturer is not enabled, build with feature `capturer_x11`"))
+    }
+
+    #[cfg(feature = "capturer_libwayshot")]
+    fn wayland() -> Result<Box<dyn Capturer<RgbImage> + Send>> {
+        Ok(Box::new(crate::capture::LibwayshotCapturer::new()?))
+    }
+
+    #[cfg(not(feature = "capturer_libwayshot"))]
+    fn wayland() -> Result<Box<dyn Capturer<RgbImage> + Send>> {
+        Err(anyhow!("Wayland capturer is not enabled, build with feature `capturer_libwayshot`"))
+    }
+}
+
 #[cfg(target_os = "linux")]
-pub type GenericCapturer = LibwayshotCapturer;
+impl Capturer<RgbImage> for GenericCapturer {
+    fn capture_rect(&self, rect: Rect<i32>) -> Result<RgbImage> {
+        self.inner.capture_rect(rect)
+    }
+}
 
 // #[cfg(target_os = "macos")]
-// pub type GenericCapturer = 
\ No newline at end of file
+// pub type GenericCapturer = 
@@ -1,10 +1,18 @@
 pub use stream_capturer::StreamingCapturer;
 pub use capturer::Capturer;
 pub use generic_capturer::GenericCapturer;
+pub use replay_capturer::ReplayCapturer;
+pub use capturer_config::CapturerConfig;
+pub use capturer_backend::CapturerBackend;
+pub use frame_snapshot::FrameSnapshot;
 
 mod capturer;
 mod generic_capturer;
 mod stream_capturer;
+mod replay_capturer;
+mod capturer_config;
+mod capturer_backend;
+mod frame_snapshot;
 
 // windows
 
@@ -23,8 +31,12 @@ pub use winapi_capturer::WinapiCapturer;
 pub use windows_capturer::WindowsCapturer;
 
 // linux
-#[cfg(target_os = "linux")]
+#[cfg(all(target_os = "linux", feature = "capturer_libwayshot"))]
 mod libwayshot_capturer;
+#[cfg(all(target_os = "linux", feature = "capturer_x11"))]
+mod x11_capturer;
 
-#[cfg(target_os = "linux")]
+#[cfg(all(target_os = "linux", feature = "capturer_libwayshot"))]
 pub use libwayshot_capturer::LibwayshotCapturer;
+#[cfg(all(target_os = "linux", feature = "capturer_x11"))]
+pub use x11_capturer::X11Capturer;
@@ -0,0 +1,121 @@
+use std::cell::{Cell, RefCell};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use image::RgbImage;
+use log::info;
+
+use crate::capture::{Capturer, FrameSnapshot};
+use crate::game_info::{GameInfo, Platform, ResolutionFamily, UI};
+use crate::positioning::{Pos, Rect};
+
+/// Serves captures from a directory of recorded full-window screenshots, instead of the screen.
+/// The window is assumed to be at the origin, so a captured rect is a rect inside the screenshot.
+/// Frames are sorted by file name, the first one is the window before the scan, and each of the others
+/// is the window after an input of the scan. The current frame only changes when `advance` is called,
+/// which is done by `ReplayControl` for every click and wheel event, so the scan controller goes through
+/// the recording as it went through the game
+pub struct ReplayCapturer {
+    frames: Vec<PathBuf>,
+    index: Cell<usize>,
+    // decoded image of the current frame
+    current: RefCell<Option<FrameSnapshot>>,
+}
+
+impl ReplayCapturer {
+    /// An optional file in the replay directory, which overrides the game info derived from the frames
+    pub const GAME_INFO_FILENAME: &'static str = "game_info.json";
+
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        let mut frames = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let is_png = path.extension()
+                .map(|ext| ext.eq_ignore_ascii_case("png"))
+                .unwrap_or(false);
+            if path.is_file() && is_png {
+                frames.push(path);
+            }
+        }
+        frames.sort();
+
+        if frames.is_empty() {
+            return Err(anyhow!("回放目录 {:?} 中没有截图", dir));
+        }
+        info!("回放目录 {:?}，共 {} 帧", dir, frames.len());
+
+        Ok(Self {
+            frames,
+            index: Cell::new(0),
+            current: RefCell::new(None),
+        })
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn frame_index(&self) -> usize {
+        self.index.get()
+    }
+
+    /// Move to the next frame, returns false if there are no more frames
+    pub fn advance(&self) -> bool {
+        let next = self.index.get() + 1;
+        if next >= self.frames.len() {
+            return false;
+        }
+
+        self.index.set(next);
+        *self.current.borrow_mut() = None;
+        true
+    }
+
+    /// Get game info of the recorded window, the window size is the size of the first frame
+    pub fn game_info(&self) -> Result<GameInfo> {
+        let dir = self.frames[0].parent().unwrap_or(Path::new("."));
+        let game_info_path = dir.join(Self::GAME_INFO_FILENAME);
+        if game_info_path.is_file() {
+            let s = std::fs::read_to_string(game_info_path)?;
+            let mut game_info: GameInfo = serde_json::from_str(&s)?;
+            game_info.window = game_info.window.translate(Pos {
+                x: -game_info.window.left,
+                y: -game_info.window.top,
+            });
+            return Ok(game_info);
+        }
+
+        let (width, height) = image::image_dimensions(&self.frames[0])?;
+        let window = Rect::new(0, 0, width as i32, height as i32);
+        let resolution_family = ResolutionFamily::new_or_closest(window.to_rect_usize().size());
+
+        Ok(GameInfo {
+            window,
+            resolution_family,
+            is_cloud: false,
+            ui: UI::Desktop,
+            // all the bundled layouts are recorded on windows
+            platform: Platform::Windows,
+        })
+    }
+
+    fn load_current_frame(&self) -> Result<()> {
+        if self.current.borrow().is_none() {
+            let path = &self.frames[self.index.get()];
+            let image = image::open(path)?.to_rgb8();
+            *self.current.borrow_mut() = Some(FrameSnapshot::from_image(image, Pos { x: 0, y: 0 }));
+        }
+        Ok(())
+    }
+}
+
+impl Capturer<RgbImage> for ReplayCapturer {
+    fn capture_rect(&self, rect: Rect<i32>) -> Result<RgbImage> {
+        self.load_current_frame()?;
+
+        let current = self.current.borrow();
+        current.as_ref().unwrap().capture_rect(rect)
+            .map_err(|e| anyhow!("frame {}: {}", self.index.get(), e))
+    }
+}
@@ -0,0 +1,183 @@
+use std::cell::RefCell;
+
+use anyhow::{anyhow, Result};
+use image::RgbImage;
+use log::{info, warn};
+use x11rb::connection::Connection;
+use x11rb::protocol::shm::ConnectionExt as _;
+use x11rb::protocol::xproto::{ConnectionExt as _, ImageFormat, ImageOrder, Window};
+use x11rb::rust_connection::RustConnection;
+
+use crate::capture::Capturer;
+use crate::positioning::Rect;
+
+/// A SysV shared memory segment attached to both this process and the X server
+struct ShmSegment {
+    seg: u32,
+    addr: *mut u8,
+    size: usize,
+}
+
+// the segment is owned by a single capturer, and only accessed through it
+unsafe impl Send for ShmSegment {}
+
+impl ShmSegment {
+    fn new(conn: &RustConnection, size: usize) -> Result<Self> {
+        // SAFETY: plain SysV shm calls, the segment is marked for removal right after both sides attached,
+        // so it is freed by the kernel once detached
+        unsafe {
+            let shmid = libc::shmget(libc::IPC_PRIVATE, size, libc::IPC_CREAT | 0o600);
+            if shmid < 0 {
+                return Err(anyhow!("shmget failed: {}", std::io::Error::last_os_error()));
+            }
+
+            let addr = libc::shmat(shmid, std::ptr::null(), 0);
+            if addr as isize == -1 {
+                libc::shmctl(shmid, libc::IPC_RMID, std::ptr::null_mut());
+                return Err(anyhow!("shmat failed: {}", std::io::Error::last_os_error()));
+            }
+
+            let seg = conn.generate_id()?;
+            let attached = conn.shm_attach(seg, shmid as u32, false)
+                .map_err(anyhow::Error::from)
+                .and_then(|cookie| cookie.check().map_err(anyhow::Error::from));
+            libc::shmctl(shmid, libc::IPC_RMID, std::ptr::null_mut());
+            if let Err(e) = attached {
+                libc::shmdt(addr);
+                return Err(e);
+            }
+
+            Ok(Self {
+                seg,
+                addr: addr as *mut u8,
+                size,
+            })
+        }
+    }
+
+    fn release(self, conn: &RustConnection) {
+        let _ = conn.shm_detach(self.seg);
+        // SAFETY: addr was returned by shmat and is detached only once
+        unsafe {
+            libc::shmdt(self.addr as *const libc::c_void);
+        }
+    }
+}
+
+/// Captures the root window of an X11 display, which also works for XWayland and Wine.
+/// MIT-SHM is used when the server supports it (local displays), otherwise falls back to XGetImage
+pub struct X11Capturer {
+    conn: RustConnection,
+    root: Window,
+    use_shm: bool,
+    shm: RefCell<Option<ShmSegment>>,
+}
+
+impl X11Capturer {
+    pub fn new() -> Result<Self> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        let setup = conn.setup();
+        let screen = &setup.roots[screen_num];
+
+        // only the common 24/32 bit depth with 4 bytes per pixel, BGRX in memory, is supported
+        let bits_per_pixel = setup.pixmap_formats.iter()
+            .find(|f| f.depth == screen.root_depth)
+            .map(|f| f.bits_per_pixel);
+        if bits_per_pixel != Some(32) || setup.image_byte_order != ImageOrder::LSB_FIRST {
+            return Err(anyhow!(
+                "unsupported X11 pixel format: depth {}, bits per pixel {:?}",
+                screen.root_depth, bits_per_pixel
+            ));
+        }
+        let root = screen.root;
+
+        let use_shm = match conn.shm_query_version()?.reply() {
+            Ok(_) => true,
+            Err(e) => {
+                warn!("X11 MIT-SHM 不可用，使用 XGetImage：{}", e);
+                false
+            }
+        };
+        info!("X11 截图，MIT-SHM: {}", use_shm);
+
+        Ok(Self {
+            conn,
+            root,
+            use_shm,
+            shm: RefCell::new(None),
+        })
+    }
+
+    fn capture_bgrx_shm(&self, rect: Rect<i32>) -> Result<Vec<u8>> {
+        let size = rect.width as usize * rect.height as usize * 4;
+
+        let mut shm = self.shm.borrow_mut();
+        if shm.as_ref().map(|s| s.size < size).unwrap_or(true) {
+            if let Some(old) = shm.take() {
+                old.release(&self.conn);
+            }
+            *shm = Some(ShmSegment::new(&self.conn, size)?);
+        }
+        let segment = shm.as_ref().unwrap();
+
+        self.conn.shm_get_image(
+            self.root,
+            rect.left as i16,
+            rect.top as i16,
+            rect.width as u16,
+            rect.height as u16,
+            !0,
+            ImageFormat::Z_PIXMAP.into(),
+            segment.seg,
+            0,
+        )?.reply()?;
+
+        // SAFETY: the server has written `size` bytes into the segment, and the reply above guarantees it's done
+        let data = unsafe { std::slice::from_raw_parts(segment.addr, size) };
+        Ok(data.to_vec())
+    }
+
+    fn capture_bgrx(&self, rect: Rect<i32>) -> Result<Vec<u8>> {
+        let reply = self.conn.get_image(
+            ImageFormat::Z_PIXMAP,
+            self.root,
+            rect.left as i16,
+            rect.top as i16,
+            rect.width as u16,
+            rect.height as u16,
+            !0,
+        )?.reply()?;
+        Ok(reply.data)
+    }
+}
+
+impl Drop for X11Capturer {
+    fn drop(&mut self) {
+        if let Some(shm) = self.shm.borrow_mut().take() {
+            shm.release(&self.conn);
+        }
+    }
+}
+
+impl Capturer<RgbImage> for X11Capturer {
+    fn capture_rect(&self, rect: Rect<i32>) -> Result<RgbImage> {
+        if rect.width <= 0 || rect.height <= 0 {
+            return Err(anyhow!("invalid capture region {}", rect));
+        }
+
+        let data = if self.use_shm {
+            self.capture_bgrx_shm(rect)?
+        } else {
+            self.capture_bgrx(rect)?
+        };
+
+        let rgb = data
+            .chunks_exact(4)
+            .take(rect.width as usize * rect.height as usize)
+            .flat_map(|p| [p[2], p[1], p[0]])
+            .collect::<Vec<u8>>();
+
+        RgbImage::from_raw(rect.width as u32, rect.height as u32, rgb)
+            .ok_or(anyhow!("X11 image size mismatch for region {}", rect))
+    }
+}
@@ -1,55 +1,96 @@
-use crate::positioning::{Pos, Rect};
+use crate::draw_capture_region::label_font::{draw_label, label_scale, label_size, put_pixel_checked};
+use crate::positioning::{Pos, Rect, Size};
 
 
 pub trait DrawCaptureRegion {
     fn draw_capture_region(&self, image: &mut image::RgbImage);
+
+    /// Draw the region along with its name
+    fn draw_capture_region_with_label(&self, image: &mut image::RgbImage, _label: &str) {
+        self.draw_capture_region(image);
+    }
 }
 
-// todo other types
 impl DrawCaptureRegion for Pos<f64> {
     fn draw_capture_region(&self, image: &mut image::RgbImage) {
         let blue = image::Rgb([0, 0, 255]);
 
-        let x = self.x as u32;
-        let y = self.y as u32;
+        let x = self.x as i64;
+        let y = self.y as i64;
 
         for i in x - 1..=x + 1 {
             for j in y - 1..=y + 1 {
-                image.put_pixel(i, j, blue);
+                put_pixel_checked(image, i, j, blue);
             }
         }
 
         for i in x - 5..=x + 5 {
-            image.put_pixel(i, y + 5, blue);
-            image.put_pixel(i, y - 5, blue);
+            put_pixel_checked(image, i, y + 5, blue);
+            put_pixel_checked(image, i, y - 5, blue);
         }
 
         for j in y - 5..=y + 5 {
-            image.put_pixel(x + 5, j, blue);
-            image.put_pixel(x - 5, j, blue);
+            put_pixel_checked(image, x + 5, j, blue);
+            put_pixel_checked(image, x - 5, j, blue);
         }
     }
+
+    fn draw_capture_region_with_label(&self, image: &mut image::RgbImage, label: &str) {
+        self.draw_capture_region(image);
+
+        let scale = label_scale(image);
+        let (_, height) = label_size(label, scale);
+        // right to the marker
+        let x = self.x as i64 + 8;
+        let y = self.y as i64 - height as i64 / 2;
+        draw_label(image, label, x, y, scale, image::Rgb([0, 160, 255]));
+    }
 }
 
 impl DrawCaptureRegion for Rect<f64> {
     fn draw_capture_region(&self, image: &mut image::RgbImage) {
         let red = image::Rgb([255, 0, 0]);
 
-        let left = self.left as u32;
-        let top = self.top as u32;
-        let width = self.width as u32;
-        let height = self.height as u32;
+        let left = self.left as i64;
+        let top = self.top as i64;
+        let width = self.width as i64;
+        let height = self.height as i64;
         let bottom = top + height;
         let right = left + width;
 
         for x in left..right {
-            image.put_pixel(x, top, red);
-            image.put_pixel(x, bottom, red);
+            put_pixel_checked(image, x, top, red);
+            put_pixel_checked(image, x, bottom, red);
         }
 
-        for y in top..bottom {
-            image.put_pixel(left, y, red);
-            image.put_pixel(right, y, red);
+        for y in top..=bottom {
+            put_pixel_checked(image, left, y, red);
+            put_pixel_checked(image, right, y, red);
         }
     }
-}
\ No newline at end of file
+
+    fn draw_capture_region_with_label(&self, image: &mut image::RgbImage, label: &str) {
+        self.draw_capture_region(image);
+
+        let scale = label_scale(image);
+        let (_, height) = label_size(label, scale);
+        // above the rect, or inside it if there's no room
+        let top = self.top as i64;
+        let y = if top >= height as i64 { top - height as i64 } else { top + 1 };
+        draw_label(image, label, self.left as i64, y, scale, image::Rgb([255, 0, 0]));
+    }
+}
+
+// the following are not regions in the window, nothing to draw
+
+impl DrawCaptureRegion for Size<f64> {
+    fn draw_capture_region(&self, _image: &mut image::RgbImage) {}
+}
+
+impl DrawCaptureRegion for f64 {
+    fn draw_capture_region(&self, _image: &mut image::RgbImage) {}
+}
+
+impl DrawCaptureRegion for i32 {
+    fn draw_capture_region(&self, _image: &mut image::RgbImage) {}
+}
@@ -0,0 +1,25 @@
+use anyhow::Result;
+use image::RgbImage;
+
+use crate::game_info::{Platform, UI};
+use crate::positioning::Size;
+use crate::window_info::{FromWindowInfoRepository, WindowInfoRepository};
+
+/// Draws every field of a window info struct, labelled by its window info key.
+/// Implemented by `#[derive(YasWindowInfo)]`
+pub trait DrawWindowInfo {
+    fn draw_window_info(&self, image: &mut RgbImage);
+}
+
+/// Read `T` for a window of the size of the screenshot, and draw it onto the screenshot
+pub fn draw_window_info_from_repository<T>(
+    repo: &WindowInfoRepository,
+    image: &mut RgbImage,
+    ui: UI,
+    platform: Platform,
+) -> Result<()> where T: FromWindowInfoRepository + DrawWindowInfo {
+    let size = Size::new(image.width() as usize, image.height() as usize);
+    let window_info = T::from_window_info_repository(size, ui, platform, repo)?;
+    window_info.draw_window_info(image);
+    Ok(())
+}
@@ -0,0 +1,11 @@
+use std::path::PathBuf;
+
+#[derive(Clone, clap::Args)]
+pub struct DrawWindowInfoConfig {
+    /// A screenshot of the game window, the layout of its size is drawn
+    #[arg(id = "screenshot", long = "screenshot", help = "游戏窗口截图", value_name = "FILE")]
+    pub screenshot: PathBuf,
+
+    #[arg(id = "output", long = "output", short = 'o', help = "输出的标注图片（PNG）", value_name = "FILE", default_value = "window_info.png")]
+    pub output: PathBuf,
+}
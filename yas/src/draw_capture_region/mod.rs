@@ -1 +1,8 @@
 pub mod draw_capture_region;
+mod draw_window_info;
+mod draw_window_info_config;
+mod label_font;
+
+pub use draw_capture_region::DrawCaptureRegion;
+pub use draw_window_info::{DrawWindowInfo, draw_window_info_from_repository};
+pub use draw_window_info_config::DrawWindowInfoConfig;
@@ -0,0 +1,8 @@
+use std::path::PathBuf;
+
+#[derive(Clone, clap::Args, Default)]
+pub struct DumpConfig {
+    /// Write every captured image, cropped OCR region and raw OCR string into this directory
+    #[arg(id = "dump", long = "dump", help = "将截图、识别区域及原始识别结果输出到指定目录，用于排查识别错误", value_name = "DIR")]
+    pub dump: Option<PathBuf>,
+}
@@ -0,0 +1,113 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use image::RgbImage;
+use log::{info, warn};
+use serde::Serialize;
+
+use crate::dump::DumpConfig;
+
+/// Writes what the scan workers see into a directory, one sub directory per item:
+///
+/// ```text
+/// <dir>/00012/panel.png
+/// <dir>/00012/list.png
+/// <dir>/00012/<ocr region name>.png
+/// <dir>/00012/manifest.json
+/// ```
+///
+/// Dumping is best-effort, a failed write is logged and never stops the scan
+pub struct Dumper {
+    dir: PathBuf,
+    /// Index of the first item, so that a resumed scan dumps after the items of the previous one
+    first_index: usize,
+}
+
+impl Dumper {
+    pub const MANIFEST_FILENAME: &'static str = "manifest.json";
+
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+        info!("dump 模式，输出目录：{:?}", dir);
+
+        Ok(Self { dir, first_index: 0 })
+    }
+
+    pub fn with_first_index(self, first_index: usize) -> Self {
+        Self { first_index, ..self }
+    }
+
+    pub fn from_config(config: &DumpConfig) -> Result<Option<Self>> {
+        match &config.dump {
+            Some(dir) => Ok(Some(Self::new(dir)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Start dumping the item with the index (starting from 0, counted from the first index)
+    pub fn item(&self, index: usize) -> ItemDump {
+        let index = self.first_index + index;
+        let dir = self.dir.join(format!("{:05}", index));
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("无法创建 dump 目录 {:?}：{}", dir, e);
+        }
+
+        ItemDump {
+            dir,
+            index,
+            ocr: RefCell::new(BTreeMap::new()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Manifest<'a, T: Serialize> {
+    index: usize,
+    ocr: &'a BTreeMap<String, String>,
+    result: Option<&'a T>,
+    error: Option<String>,
+}
+
+/// Images and raw OCR strings of a single item, the manifest is written on `finish`
+pub struct ItemDump {
+    dir: PathBuf,
+    index: usize,
+    ocr: RefCell<BTreeMap<String, String>>,
+}
+
+impl ItemDump {
+    pub fn save_image(&self, name: &str, image: &RgbImage) {
+        let path = self.dir.join(format!("{}.png", name));
+        if let Err(e) = image.save(&path) {
+            warn!("无法保存 dump 图片 {:?}：{}", path, e);
+        }
+    }
+
+    /// Save the cropped region which is fed to the OCR model, and the raw string it outputs
+    pub fn record_ocr(&self, name: &str, image: &RgbImage, text: &str) {
+        self.save_image(name, image);
+        self.ocr.borrow_mut().insert(String::from(name), String::from(text));
+    }
+
+    /// Write the manifest with the parsed result, or the error why the item can not be parsed
+    pub fn finish<T: Serialize>(self, result: std::result::Result<&T, &anyhow::Error>) {
+        let ocr = self.ocr.borrow();
+        let manifest = Manifest {
+            index: self.index,
+            ocr: &ocr,
+            result: result.ok(),
+            error: result.err().map(|e| e.to_string()),
+        };
+
+        let path = self.dir.join(Dumper::MANIFEST_FILENAME);
+        let written = serde_json::to_string_pretty(&manifest)
+            .map_err(anyhow::Error::from)
+            .and_then(|s| std::fs::write(&path, s).map_err(anyhow::Error::from));
+        if let Err(e) = written {
+            warn!("无法写入 dump 清单 {:?}：{}", path, e);
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub use dump_config::DumpConfig;
+pub use dumper::{Dumper, ItemDump};
+
+mod dump_config;
+mod dumper;
@@ -1,8 +1,9 @@
 use crate::game_info::{ResolutionFamily, UI};
 use crate::game_info::ui::Platform;
+use serde::{Deserialize, Serialize};
 use crate::positioning::Rect;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GameInfo {
     pub window: Rect<i32>,
     pub resolution_family: ResolutionFamily,
@@ -39,6 +39,10 @@ impl GameInfoBuilder {
         }
         
         #[cfg(target_os = "linux")]
-        crate::game_info::os::get_game_info()
+        {
+            let local_window_names = self.local_window_names.iter().map(|x| x.as_str()).collect::<Vec<_>>();
+            let cloud_window_names = self.cloud_window_names.iter().map(|x| x.as_str()).collect::<Vec<_>>();
+            crate::game_info::os::get_game_info(&local_window_names, &cloud_window_names)
+        }
     }
 }
@@ -1,42 +1,152 @@
+use std::io::stdin;
+
 use anyhow::{Result, anyhow};
+use log::info;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{Atom, AtomEnum, ClientMessageEvent, ConnectionExt, EventMask, Window};
+use x11rb::rust_connection::RustConnection;
 
 use crate::game_info::{GameInfo, Platform, UI, ResolutionFamily};
 use crate::positioning::Rect;
+use crate::utils;
+
+struct Atoms {
+    net_client_list: Atom,
+    net_wm_name: Atom,
+    net_active_window: Atom,
+    utf8_string: Atom,
+}
+
+impl Atoms {
+    fn new(conn: &RustConnection) -> Result<Self> {
+        let intern = |name: &str| -> Result<Atom> {
+            Ok(conn.intern_atom(false, name.as_bytes())?.reply()?.atom)
+        };
+
+        Ok(Self {
+            net_client_list: intern("_NET_CLIENT_LIST")?,
+            net_wm_name: intern("_NET_WM_NAME")?,
+            net_active_window: intern("_NET_ACTIVE_WINDOW")?,
+            utf8_string: intern("UTF8_STRING")?,
+        })
+    }
+}
+
+/// Top level windows managed by the window manager (EWMH `_NET_CLIENT_LIST`)
+fn get_client_list(conn: &RustConnection, root: Window, atoms: &Atoms) -> Result<Vec<Window>> {
+    let reply = conn.get_property(false, root, atoms.net_client_list, AtomEnum::WINDOW, 0, u32::MAX)?.reply()?;
+    let windows = reply.value32()
+        .ok_or(anyhow!("窗口管理器不支持 _NET_CLIENT_LIST"))?
+        .collect();
+    Ok(windows)
+}
+
+/// `_NET_WM_NAME` in utf-8, falls back to the legacy `WM_NAME`
+fn get_window_title(conn: &RustConnection, window: Window, atoms: &Atoms) -> Result<Option<String>> {
+    let reply = conn.get_property(false, window, atoms.net_wm_name, atoms.utf8_string, 0, u32::MAX)?.reply()?;
+    if !reply.value.is_empty() {
+        return Ok(Some(String::from_utf8_lossy(&reply.value).into_owned()));
+    }
+
+    let reply = conn.get_property(false, window, AtomEnum::WM_NAME, AtomEnum::ANY, 0, u32::MAX)?.reply()?;
+    if !reply.value.is_empty() {
+        return Ok(Some(String::from_utf8_lossy(&reply.value).into_owned()));
+    }
+
+    Ok(None)
+}
+
+fn get_window(
+    conn: &RustConnection,
+    root: Window,
+    atoms: &Atoms,
+    local_window_names: &[&str],
+    cloud_window_names: &[&str],
+) -> Result<(Window, bool)> {
+    let mut viable_windows = Vec::new();
+    for window in get_client_list(conn, root, atoms)? {
+        // windows may be destroyed while iterating
+        let title = match get_window_title(conn, window, atoms) {
+            Ok(Some(t)) => t,
+            _ => continue,
+        };
+        let trimmed = title.trim();
+
+        if local_window_names.contains(&trimmed) {
+            viable_windows.push((window, String::from(trimmed), false));
+        } else if cloud_window_names.contains(&trimmed) {
+            viable_windows.push((window, String::from(trimmed), true));
+        }
+    }
+
+    if viable_windows.len() == 1 {
+        return Ok((viable_windows[0].0, viable_windows[0].2));
+    } else if viable_windows.is_empty() {
+        let names = local_window_names.iter().chain(cloud_window_names.iter()).collect::<Vec<_>>();
+        return Err(anyhow!("未找到游戏窗口，请确认{:?}已经开启", names));
+    }
+
+    println!("找到多个符合名称的窗口，请手动选择窗口：");
+    for (i, (window, title, _)) in viable_windows.iter().enumerate() {
+        println!("{}: {} (0x{:x})", i, title, window);
+    }
+    let mut index = String::new();
+    stdin().read_line(&mut index)?;
+
+    let idx = index.trim().parse::<usize>()?;
+    if idx < viable_windows.len() {
+        Ok((viable_windows[idx].0, viable_windows[idx].2))
+    } else {
+        Err(anyhow!("索引{}超出范围", idx))
+    }
+}
+
+/// Ask the window manager to raise and focus the window (EWMH `_NET_ACTIVE_WINDOW`)
+fn activate_window(conn: &RustConnection, root: Window, window: Window, atoms: &Atoms) -> Result<()> {
+    // source indication 2: the request is from a pager or similar tool, so it's not ignored by focus stealing prevention
+    let event = ClientMessageEvent::new(32, window, atoms.net_active_window, [2, x11rb::CURRENT_TIME, 0, 0, 0]);
+    conn.send_event(
+        false,
+        root,
+        EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+        event,
+    )?;
+    conn.flush()?;
+    Ok(())
+}
+
+/// Client area of the window in root (screen) coordinates, the window manager frame is not included
+fn get_window_rect(conn: &RustConnection, root: Window, window: Window) -> Result<Rect<i32>> {
+    let geometry = conn.get_geometry(window)?.reply()?;
+    let translated = conn.translate_coordinates(window, root, 0, 0)?.reply()?;
+
+    Ok(Rect::new(
+        translated.dst_x as i32,
+        translated.dst_y as i32,
+        geometry.width as i32,
+        geometry.height as i32,
+    ))
+}
+
+pub fn get_game_info(local_window_names: &[&str], cloud_window_names: &[&str]) -> Result<GameInfo> {
+    let (conn, screen_num) = x11rb::connect(None)
+        .map_err(|e| anyhow!("无法连接 X11 显示服务器：{}", e))?;
+    let root = conn.setup().roots[screen_num].root;
+    let atoms = Atoms::new(&conn)?;
+
+    let (window, is_cloud) = get_window(&conn, root, &atoms, local_window_names, cloud_window_names)?;
+    info!("找到游戏窗口 0x{:x}", window);
+
+    activate_window(&conn, root, window, &atoms)?;
+    utils::sleep(1000);
 
-pub fn get_game_info() -> Result<GameInfo> {
-    let window_id = String::from_utf8(
-            std::process::Command::new("sh")
-                .arg("-c")
-                .arg(r#" xwininfo|grep "Window id"|cut -d " " -f 4 "#)
-                .output()
-                .unwrap()
-                .stdout,
-        )?;
-    let window_id = window_id.trim_end_matches("\n");
-
-    let position_size = String::from_utf8(
-            std::process::Command::new("sh")
-                .arg("-c")
-                .arg(&format!(r#" xwininfo -id {window_id}|cut -f 2 -d :|tr -cd "0-9\n"|grep -v "^$"|sed -n "1,2p;5,6p" "#))
-                .output()
-                .unwrap()
-                .stdout,
-        )?;
-
-    let mut info = position_size.split("\n");
-
-    let left = info.next().unwrap().parse().unwrap();
-    let top = info.next().unwrap().parse().unwrap();
-    let width = info.next().unwrap().parse().unwrap();
-    let height = info.next().unwrap().parse().unwrap();
-
-    let rect = Rect::new(left, top, width, height);
-    let rf = ResolutionFamily::new(rect.size()).ok_or(anyhow!("unknown resolution family"))?;
+    let rect = get_window_rect(&conn, root, window)?;
+    let resolution_family = ResolutionFamily::new_or_closest(rect.to_rect_usize().size());
 
     Ok(GameInfo {
-        window: rect.to_rect_i32(),
-        resolution_family: rf,
-        is_cloud: false,
+        window: rect,
+        resolution_family,
+        is_cloud,
         ui: UI::Desktop,
         platform: Platform::Linux,
     })
@@ -1,9 +1,11 @@
+use log::warn;
 use serde::{Deserialize, Serialize};
 use crate::positioning::Size;
 
 #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ResolutionFamily {
     // PC
+    Windows32x9,
     Windows43x18,
     Windows7x3,
     Windows16x9,
@@ -20,7 +22,9 @@ impl ResolutionFamily {
         let height = size.height as u32;
         let width = size.width as u32;
 
-        if height * 43 == width * 18 {
+        if height * 32 == width * 9 {
+            Some(ResolutionFamily::Windows32x9)
+        } else if height * 43 == width * 18 {
             Some(ResolutionFamily::Windows43x18)
         } else if height * 16 == width * 9 {
             Some(ResolutionFamily::Windows16x9)
@@ -36,4 +40,28 @@ impl ResolutionFamily {
             None
         }
     }
+    /// The family of `size`, or the PC family of the closest ratio for a size which is in none, e.g. 2560x1080 (64:27).
+    /// The layouts of such a window are derived from the anchored ones of the closest ratio
+    pub fn new_or_closest(size: Size<usize>) -> Self {
+        if let Some(family) = Self::new(size) {
+            return family;
+        }
+
+        let ratio = size.width as f64 / size.height as f64;
+        let family = [
+            (ResolutionFamily::Windows32x9, 32.0 / 9.0),
+            (ResolutionFamily::Windows43x18, 43.0 / 18.0),
+            (ResolutionFamily::Windows7x3, 7.0 / 3.0),
+            (ResolutionFamily::Windows16x9, 16.0 / 9.0),
+            (ResolutionFamily::Windows8x5, 8.0 / 5.0),
+            (ResolutionFamily::Windows4x3, 4.0 / 3.0),
+        ]
+            .into_iter()
+            .min_by(|(_, a), (_, b)| (a - ratio).abs().total_cmp(&(b - ratio).abs()))
+            .map(|(family, _)| family)
+            .unwrap();
+        warn!("分辨率 {}x{} 不在支持的比例中，按最接近的 {:?} 处理", size.width, size.height, family);
+
+        family
+    }
 }
@@ -1,10 +1,5 @@
-#![feature(decl_macro)]
-#![feature(concat_idents)]
 #![allow(unused_imports)]
 
-#[cfg(all(feature = "ort", feature = "tract_onnx"))]
-compile_error!("feature \"ort\" and \"tract_onnx\" cannot be enabled at the same time");
-
 extern crate log;
 extern crate lazy_static;
 
@@ -19,3 +14,10 @@ pub mod system_control;
 pub mod ocr;
 pub mod positioning;
 pub mod profiler;
+pub mod dump;
+pub mod calibration;
+pub mod worker_pool;
+pub mod ocr_evaluation;
+#[cfg(feature = "simulation")]
+pub mod simulation;
+pub mod scanner_controller;
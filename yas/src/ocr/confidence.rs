@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Fields below this confidence are likely misread
+pub const LOW_CONFIDENCE_THRESHOLD: f32 = 0.8;
+
+/// Recognized text along with how sure the model is about it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TextWithConfidence {
+    pub text: String,
+    /// Probability of each char of `text`
+    pub char_confidence: Vec<f32>,
+    /// Mean of the char confidences. For an empty text, the mean probability of the blank frames
+    pub confidence: f32,
+}
+
+impl TextWithConfidence {
+    /// A blank image which is known to contain no text, without running the model
+    pub fn blank() -> Self {
+        Self {
+            text: String::new(),
+            char_confidence: Vec::new(),
+            confidence: 1.0,
+        }
+    }
+}
+
+fn is_probability(row: &[f32]) -> bool {
+    let sum: f32 = row.iter().sum();
+    row.iter().all(|&v| (0.0..=1.0).contains(&v)) && (sum - 1.0).abs() < 1e-2
+}
+
+/// Index and probability of the most likely class of a frame.
+/// The scores are used as is if they are already probabilities, otherwise softmax is applied
+fn frame_max_probability(row: &[f32]) -> (usize, f32) {
+    let mut max_index = 0;
+    let mut max_value = f32::NEG_INFINITY;
+    for (i, &v) in row.iter().enumerate() {
+        if v > max_value {
+            max_value = v;
+            max_index = i;
+        }
+    }
+
+    if is_probability(row) {
+        (max_index, max_value)
+    } else {
+        // softmax of the max value
+        let denominator: f32 = row.iter().map(|&v| (v - max_value).exp()).sum();
+        (max_index, 1.0 / denominator)
+    }
+}
+
+/// Probabilities of all classes of a frame, softmax is applied if the scores are not probabilities yet
+pub(crate) fn frame_probabilities(row: &[f32]) -> Vec<f32> {
+    if is_probability(row) {
+        return row.to_vec();
+    }
+
+    let max_value = row.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let exp = row.iter().map(|&v| (v - max_value).exp()).collect::<Vec<_>>();
+    let sum: f32 = exp.iter().sum();
+    exp.into_iter().map(|v| v / sum).collect()
+}
+
+/// Greedy CTC decoding. `value(t, c)` is the score of class `c` at frame `t`,
+/// `word(c)` is the text of class `c`, or `None` for the blank class.
+/// Repeated frames of the same class are merged, keeping the highest probability
+pub fn ctc_greedy_decode<'a>(
+    time_steps: usize,
+    class_count: usize,
+    value: impl Fn(usize, usize) -> f32,
+    word: impl Fn(usize) -> Option<&'a str>,
+) -> TextWithConfidence {
+    let mut text = String::new();
+    let mut char_confidence: Vec<f32> = Vec::new();
+    let mut blank_confidence_sum = 0.0;
+    let mut blank_count = 0;
+
+    let mut row = vec![0.0; class_count];
+    let mut last_index = None;
+    // number of chars emitted by the last frame, if it's not blank
+    let mut last_len = 0;
+    for t in 0..time_steps {
+        for (c, v) in row.iter_mut().enumerate() {
+            *v = value(t, c);
+        }
+        let (index, probability) = frame_max_probability(&row);
+
+        match word(index) {
+            None => {
+                blank_confidence_sum += probability;
+                blank_count += 1;
+                last_len = 0;
+            },
+            Some(_) if last_index == Some(index) => {
+                let len = char_confidence.len();
+                for c in char_confidence[len - last_len..].iter_mut() {
+                    *c = c.max(probability);
+                }
+            },
+            Some(w) => {
+                text.push_str(w);
+                last_len = w.chars().count();
+                char_confidence.extend(std::iter::repeat_n(probability, last_len));
+            },
+        }
+        last_index = Some(index);
+    }
+
+    let confidence = if !char_confidence.is_empty() {
+        char_confidence.iter().sum::<f32>() / char_confidence.len() as f32
+    } else if blank_count > 0 {
+        blank_confidence_sum / blank_count as f32
+    } else {
+        1.0
+    };
+
+    TextWithConfidence {
+        text,
+        char_confidence,
+        confidence,
+    }
+}
+
+/// Confidence of each ocr field of a scan result, keyed by field name.
+/// It's not part of the identity of an item, scan results leave it out when they are compared or hashed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FieldConfidence(BTreeMap<String, f32>);
+
+impl FieldConfidence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, field: &str, confidence: f32) {
+        self.0.insert(String::from(field), confidence);
+    }
+
+    pub fn get(&self, field: &str) -> Option<f32> {
+        self.0.get(field).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f32)> {
+        self.0.iter().map(|(k, &v)| (k.as_str(), v))
+    }
+
+    /// The lowest confidence among all fields, 1 if there is none
+    pub fn min(&self) -> f32 {
+        self.0.values().copied().fold(1.0, f32::min)
+    }
+
+    /// Fields below the threshold, which should be checked or captured again
+    pub fn low_confidence_fields(&self, threshold: f32) -> Vec<(&str, f32)> {
+        self.iter().filter(|&(_, c)| c < threshold).collect()
+    }
+}
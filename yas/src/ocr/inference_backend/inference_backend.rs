@@ -0,0 +1,85 @@
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+use log::warn;
+use ndarray::ArrayD;
+
+/// A loaded onnx model, which runs on one of the backends compiled in
+pub trait InferenceBackend: Send {
+    /// Run the model with a single f32 input, returns its first output
+    fn run(&self, input: ArrayD<f32>) -> Result<ArrayD<f32>>;
+
+    fn name(&self) -> &'static str;
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum OCRBackend {
+    /// ONNX Runtime if it's available, otherwise tract
+    #[default]
+    Auto,
+    /// ONNX Runtime
+    Ort,
+    /// tract, pure Rust
+    Tract,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InferenceOptions {
+    pub backend: OCRBackend,
+    /// Intra-op threads, `None` to use the default of the backend
+    pub intra_threads: Option<usize>,
+}
+
+/// Input shape to load a model with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputShape {
+    /// As declared in the model, which may have a dynamic batch dimension
+    Declared,
+    Fixed(Vec<usize>),
+    /// `None` dimensions are only known when running. tract can not optimize such a model
+    Partial(Vec<Option<usize>>),
+}
+
+#[cfg(feature = "ort")]
+fn load_ort(model: &[u8], options: &InferenceOptions) -> Result<Box<dyn InferenceBackend>> {
+    Ok(Box::new(super::OrtBackend::new(model, options.intra_threads)?))
+}
+
+#[cfg(not(feature = "ort"))]
+fn load_ort(_model: &[u8], _options: &InferenceOptions) -> Result<Box<dyn InferenceBackend>> {
+    bail!("此版本未编译 ONNX Runtime 后端（feature \"ort\"），请使用 --ocr-backend=tract")
+}
+
+#[cfg(feature = "tract_onnx")]
+fn load_tract(model: &[u8], input_shape: &InputShape, options: &InferenceOptions) -> Result<Box<dyn InferenceBackend>> {
+    if options.intra_threads.is_some_and(|n| n > 1) {
+        warn!("tract 后端仅支持单线程推理，忽略 --ocr-threads");
+    }
+    Ok(Box::new(super::TractBackend::new(model, input_shape)?))
+}
+
+#[cfg(not(feature = "tract_onnx"))]
+fn load_tract(_model: &[u8], _input_shape: &InputShape, _options: &InferenceOptions) -> Result<Box<dyn InferenceBackend>> {
+    bail!("此版本未编译 tract 后端（feature \"tract_onnx\"），请使用 --ocr-backend=ort")
+}
+
+/// Load an onnx model with the backend in `options`.
+/// `Auto` falls back to tract if ONNX Runtime fails to load the model, e.g. its shared library is missing
+pub fn load_backend(model: &[u8], input_shape: &InputShape, options: &InferenceOptions) -> Result<Box<dyn InferenceBackend>> {
+    match options.backend {
+        OCRBackend::Ort => load_ort(model, options),
+        OCRBackend::Tract => load_tract(model, input_shape, options),
+        OCRBackend::Auto => {
+            if cfg!(feature = "ort") {
+                match load_ort(model, options) {
+                    Ok(backend) => return Ok(backend),
+                    Err(e) if cfg!(feature = "tract_onnx") => warn!("ONNX Runtime 不可用，改用 tract：{}", e),
+                    Err(e) => return Err(e),
+                }
+            }
+            if cfg!(feature = "tract_onnx") {
+                return load_tract(model, input_shape, options);
+            }
+            bail!("此版本未编译任何 OCR 推理后端，请启用 feature \"ort\" 或 \"tract_onnx\"")
+        }
+    }
+}
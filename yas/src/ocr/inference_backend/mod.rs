@@ -0,0 +1,11 @@
+mod inference_backend;
+#[cfg(feature = "ort")]
+mod ort_backend;
+#[cfg(feature = "tract_onnx")]
+mod tract_backend;
+
+pub use inference_backend::{load_backend, InferenceBackend, InferenceOptions, InputShape, OCRBackend};
+#[cfg(feature = "ort")]
+pub use ort_backend::OrtBackend;
+#[cfg(feature = "tract_onnx")]
+pub use tract_backend::TractBackend;
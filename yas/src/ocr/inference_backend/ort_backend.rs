@@ -0,0 +1,35 @@
+use anyhow::Result;
+use ndarray::ArrayD;
+use ort::{GraphOptimizationLevel, Session};
+
+use super::InferenceBackend;
+
+const DEFAULT_INTRA_THREADS: usize = 4;
+
+pub struct OrtBackend {
+    session: Session,
+}
+
+impl OrtBackend {
+    /// ONNX Runtime handles dynamic shapes itself, so the input shape is not needed
+    pub fn new(model: &[u8], intra_threads: Option<usize>) -> Result<Self> {
+        let session = Session::builder()?
+            .with_optimization_level(GraphOptimizationLevel::Level3)?
+            .with_intra_threads(intra_threads.unwrap_or(DEFAULT_INTRA_THREADS))?
+            .commit_from_memory(model)?;
+
+        Ok(Self { session })
+    }
+}
+
+impl InferenceBackend for OrtBackend {
+    fn run(&self, input: ArrayD<f32>) -> Result<ArrayD<f32>> {
+        let outputs = self.session.run(ort::inputs![input]?)?;
+        let output = outputs[0].try_extract_tensor::<f32>()?.to_owned();
+        Ok(output)
+    }
+
+    fn name(&self) -> &'static str {
+        "ort"
+    }
+}
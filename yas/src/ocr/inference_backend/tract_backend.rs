@@ -0,0 +1,68 @@
+use anyhow::Result;
+use ndarray::ArrayD;
+use tract_onnx::prelude::*;
+use tract_onnx::tract_hir::infer::{DimFact, GenericFactoid, InferenceOp, ShapeFactoid};
+
+use super::{InferenceBackend, InputShape};
+
+type TypedModelType = RunnableModel<TypedFact, Box<dyn TypedOp>, Graph<TypedFact, Box<dyn TypedOp>>>;
+type InferenceModelType = RunnableModel<InferenceFact, Box<dyn InferenceOp>, Graph<InferenceFact, Box<dyn InferenceOp>>>;
+
+enum Plan {
+    Typed(TypedModelType),
+    /// Models with dynamic dimensions other than the declared ones are run without optimization
+    Inference(InferenceModelType),
+}
+
+pub struct TractBackend {
+    plan: Plan,
+}
+
+impl TractBackend {
+    pub fn new(model: &[u8], input_shape: &InputShape) -> Result<Self> {
+        let mut reader = model;
+        let inference_model = tract_onnx::onnx().model_for_read(&mut reader)?;
+
+        let plan = match input_shape {
+            InputShape::Declared => Plan::Typed(inference_model.into_optimized()?.into_runnable()?),
+            InputShape::Fixed(shape) => Plan::Typed(
+                inference_model
+                    .with_input_fact(0, f32::fact(shape.clone()).into())?
+                    .into_optimized()?
+                    .into_runnable()?
+            ),
+            InputShape::Partial(dims) => {
+                let shape = ShapeFactoid::closed(dims.iter()
+                    .map(|dim| match dim {
+                        Some(d) => GenericFactoid::Only(d.to_dim()),
+                        None => DimFact::default(),
+                    })
+                    .collect());
+                let fact = InferenceFact::new().with_datum_type(DatumType::F32).with_shape(shape);
+                Plan::Inference(inference_model.with_input_fact(0, fact)?.into_runnable()?)
+            },
+        };
+
+        Ok(Self { plan })
+    }
+}
+
+impl InferenceBackend for TractBackend {
+    fn run(&self, input: ArrayD<f32>) -> Result<ArrayD<f32>> {
+        // tract may depend on another version of ndarray, so the arrays are converted through their raw data
+        let shape = input.shape().to_vec();
+        let data = input.iter().copied().collect::<Vec<_>>();
+        let tensor = Tensor::from_shape(&shape, &data)?;
+        let outputs = match &self.plan {
+            Plan::Typed(model) => model.run(tvec!(tensor.into()))?,
+            Plan::Inference(model) => model.run(tvec!(tensor.into()))?,
+        };
+
+        let output = outputs[0].to_array_view::<f32>()?;
+        Ok(ArrayD::from_shape_vec(output.shape(), output.iter().copied().collect())?)
+    }
+
+    fn name(&self) -> &'static str {
+        "tract"
+    }
+}
@@ -0,0 +1,319 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use crate::ocr::confidence::frame_probabilities;
+use crate::ocr::{ctc_greedy_decode, TextWithConfidence};
+
+/// Number of prefixes kept at each frame of the constrained decoding
+pub const LEXICON_BEAM_WIDTH: usize = 16;
+
+#[derive(Default)]
+struct TrieNode {
+    ch: Option<char>,
+    children: Vec<(char, usize)>,
+    word: Option<usize>,
+}
+
+/// A closed vocabulary of a field, such as artifact names or stat names, stored as a trie of chars
+pub struct Lexicon {
+    nodes: Vec<TrieNode>,
+    words: Vec<String>,
+    fingerprint: u64,
+}
+
+impl Lexicon {
+    pub fn new<I, S>(words: I) -> Self where I: IntoIterator<Item = S>, S: AsRef<str> {
+        let mut lexicon = Lexicon {
+            nodes: vec![TrieNode::default()],
+            words: Vec::new(),
+            fingerprint: 0,
+        };
+        for word in words {
+            lexicon.insert(word.as_ref());
+        }
+
+        let mut hasher = DefaultHasher::new();
+        lexicon.words.hash(&mut hasher);
+        lexicon.fingerprint = hasher.finish();
+
+        lexicon
+    }
+
+    fn insert(&mut self, word: &str) {
+        if word.is_empty() {
+            return;
+        }
+
+        let mut node = 0;
+        for ch in word.chars() {
+            node = match self.child(node, ch) {
+                Some(child) => child,
+                None => {
+                    let child = self.nodes.len();
+                    self.nodes.push(TrieNode { ch: Some(ch), ..Default::default() });
+                    self.nodes[node].children.push((ch, child));
+                    child
+                }
+            };
+        }
+
+        if self.nodes[node].word.is_none() {
+            self.nodes[node].word = Some(self.words.len());
+            self.words.push(String::from(word));
+        }
+    }
+
+    fn child(&self, node: usize, ch: char) -> Option<usize> {
+        self.nodes[node].children.iter().find(|&&(c, _)| c == ch).map(|&(_, child)| child)
+    }
+
+    /// A hash of the words, lexicons with the same words have the same fingerprint
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        let mut node = 0;
+        for ch in word.chars() {
+            node = match self.child(node, ch) {
+                Some(child) => child,
+                None => return false,
+            };
+        }
+        self.nodes[node].word.is_some()
+    }
+
+    /// The word with the smallest edit distance to `text`
+    pub fn closest(&self, text: &str) -> Option<&str> {
+        self.words.iter()
+            .min_by_key(|w| edit_distance::edit_distance(w, text))
+            .map(|w| w.as_str())
+    }
+
+    /// Replace a recognized text with the closest word, if it's at most a third of the chars away.
+    /// Used for models whose raw output is not available
+    pub fn correct(&self, result: TextWithConfidence) -> TextWithConfidence {
+        if result.text.is_empty() || self.contains(&result.text) {
+            return result;
+        }
+
+        let word = match self.closest(&result.text) {
+            Some(w) => w,
+            None => return result,
+        };
+        let len = word.chars().count();
+        if edit_distance::edit_distance(word, &result.text) * 3 > len {
+            return result;
+        }
+
+        TextWithConfidence {
+            text: String::from(word),
+            char_confidence: vec![result.confidence; len],
+            confidence: result.confidence,
+        }
+    }
+}
+
+/// The lexicons of the fields of an item with a closed vocabulary, by field name.
+/// Such fields are decoded with their lexicon, so that a misread char still gives a valid name
+#[derive(Default)]
+pub struct FieldLexicons {
+    lexicons: Vec<Lexicon>,
+    fields: HashMap<&'static str, usize>,
+}
+
+impl FieldLexicons {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode all the `fields` with one lexicon of `words`
+    pub fn with<I, S>(mut self, fields: &[&'static str], words: I) -> Self where I: IntoIterator<Item = S>, S: AsRef<str> {
+        self.lexicons.push(Lexicon::new(words));
+        for &field in fields {
+            self.fields.insert(field, self.lexicons.len() - 1);
+        }
+        self
+    }
+
+    pub fn get(&self, field: &str) -> Option<&Lexicon> {
+        self.fields.get(field).map(|&i| &self.lexicons[i])
+    }
+}
+
+fn log_add(a: f32, b: f32) -> f32 {
+    if a == f32::NEG_INFINITY {
+        return b;
+    }
+    if b == f32::NEG_INFINITY {
+        return a;
+    }
+    let max = a.max(b);
+    max + ((a - max).exp() + (b - max).exp()).ln()
+}
+
+/// Best path of `labels` through the frames, returns the probability of each label,
+/// which is the highest probability among the frames aligned to it
+fn align(probabilities: &[Vec<f32>], labels: &[usize], blank: usize) -> Vec<f32> {
+    let time_steps = probabilities.len();
+    // labels interleaved with blanks
+    let extended = std::iter::once(blank)
+        .chain(labels.iter().flat_map(|&l| [l, blank]))
+        .collect::<Vec<_>>();
+    let states = extended.len();
+    let log_p = |t: usize, s: usize| probabilities[t][extended[s]].max(f32::MIN_POSITIVE).ln();
+
+    let mut score = vec![vec![f32::NEG_INFINITY; states]; time_steps];
+    let mut from = vec![vec![0; states]; time_steps];
+    score[0][0] = log_p(0, 0);
+    if states > 1 {
+        score[0][1] = log_p(0, 1);
+    }
+    for t in 1..time_steps {
+        for s in 0..states {
+            let mut best = (score[t - 1][s], s);
+            if s >= 1 && score[t - 1][s - 1] > best.0 {
+                best = (score[t - 1][s - 1], s - 1);
+            }
+            if s >= 2 && extended[s] != blank && extended[s] != extended[s - 2] && score[t - 1][s - 2] > best.0 {
+                best = (score[t - 1][s - 2], s - 2);
+            }
+            score[t][s] = best.0 + log_p(t, s);
+            from[t][s] = best.1;
+        }
+    }
+
+    let mut s = if states > 1 && score[time_steps - 1][states - 2] > score[time_steps - 1][states - 1] {
+        states - 2
+    } else {
+        states - 1
+    };
+    let mut confidence = vec![0.0_f32; labels.len()];
+    for t in (0..time_steps).rev() {
+        if s % 2 == 1 {
+            let i = s / 2;
+            confidence[i] = confidence[i].max(probabilities[t][extended[s]]);
+        }
+        s = from[t][s];
+    }
+
+    confidence
+}
+
+/// CTC prefix beam search where every prefix must be in the lexicon.
+/// Returns the most probable word, or `None` if no word can be aligned to the frames
+pub fn ctc_lexicon_decode<'a>(
+    time_steps: usize,
+    class_count: usize,
+    value: impl Fn(usize, usize) -> f32,
+    word: impl Fn(usize) -> Option<&'a str>,
+    lexicon: &Lexicon,
+    beam_width: usize,
+) -> Option<TextWithConfidence> {
+    if time_steps == 0 {
+        return None;
+    }
+
+    let blank = (0..class_count).find(|&c| word(c).is_none())?;
+    // the lexicon is matched char by char, so only single char classes are used
+    let char_to_class = (0..class_count)
+        .filter_map(|c| {
+            let mut chars = word(c)?.chars();
+            match (chars.next(), chars.next()) {
+                (Some(ch), None) => Some((ch, c)),
+                _ => None,
+            }
+        })
+        .collect::<HashMap<char, usize>>();
+    let class_of = |node: usize| lexicon.nodes[node].ch.and_then(|ch| char_to_class.get(&ch).copied());
+
+    let probabilities = (0..time_steps)
+        .map(|t| frame_probabilities(&(0..class_count).map(|c| value(t, c)).collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+
+    // trie node -> log probability of the prefix ending with a blank, and ending with its last char
+    let mut beams: HashMap<usize, (f32, f32)> = HashMap::from([(0, (0.0, f32::NEG_INFINITY))]);
+    for frame in probabilities.iter() {
+        let log_p = |c: usize| frame[c].max(f32::MIN_POSITIVE).ln();
+        let mut next: HashMap<usize, (f32, f32)> = HashMap::new();
+
+        for (&node, &(blank_score, char_score)) in beams.iter() {
+            let total = log_add(blank_score, char_score);
+            let last_class = class_of(node);
+
+            let entry = next.entry(node).or_insert((f32::NEG_INFINITY, f32::NEG_INFINITY));
+            entry.0 = log_add(entry.0, total + log_p(blank));
+            // the last char repeated, which is merged
+            if let Some(last) = last_class {
+                entry.1 = log_add(entry.1, char_score + log_p(last));
+            }
+
+            for &(_, child) in lexicon.nodes[node].children.iter() {
+                let c = match class_of(child) {
+                    Some(c) => c,
+                    None => continue,
+                };
+                // the same char twice in a row needs a blank in between
+                let score = if last_class == Some(c) { blank_score } else { total } + log_p(c);
+                let entry = next.entry(child).or_insert((f32::NEG_INFINITY, f32::NEG_INFINITY));
+                entry.1 = log_add(entry.1, score);
+            }
+        }
+
+        let mut candidates = next.into_iter().collect::<Vec<_>>();
+        let total = |&(_, (b, c)): &(usize, (f32, f32))| log_add(b, c);
+        candidates.sort_by(|a, b| total(b).partial_cmp(&total(a)).unwrap_or(Ordering::Equal));
+        candidates.truncate(beam_width);
+        beams = candidates.into_iter().collect();
+    }
+
+    let (best, _) = beams.iter()
+        .filter(|&(&node, _)| lexicon.nodes[node].word.is_some())
+        .map(|(&node, &(b, c))| (node, log_add(b, c)))
+        .filter(|&(_, score)| score > f32::NEG_INFINITY)
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))?;
+
+    let text = lexicon.words[lexicon.nodes[best].word.unwrap()].clone();
+    let labels = text.chars().map(|ch| char_to_class[&ch]).collect::<Vec<_>>();
+    let char_confidence = align(&probabilities, &labels, blank);
+    let confidence = char_confidence.iter().sum::<f32>() / char_confidence.len() as f32;
+
+    Some(TextWithConfidence {
+        text,
+        char_confidence,
+        confidence,
+    })
+}
+
+/// Greedy decoding, constrained to the lexicon if there is one.
+/// The greedy result is kept if it's empty or already a word, or if no word fits the frames
+pub fn ctc_decode<'a>(
+    time_steps: usize,
+    class_count: usize,
+    value: impl Fn(usize, usize) -> f32,
+    word: impl Fn(usize) -> Option<&'a str>,
+    lexicon: Option<&Lexicon>,
+) -> TextWithConfidence {
+    let greedy = ctc_greedy_decode(time_steps, class_count, &value, &word);
+
+    match lexicon {
+        Some(lexicon) if !greedy.text.is_empty() && !lexicon.contains(&greedy.text) => {
+            ctc_lexicon_decode(time_steps, class_count, value, word, lexicon, LEXICON_BEAM_WIDTH)
+                .unwrap_or(greedy)
+        },
+        _ => greedy,
+    }
+}
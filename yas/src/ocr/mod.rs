@@ -1,10 +1,30 @@
 mod traits;
+mod text_detection;
+mod confidence;
+mod lexicon;
+mod ocr_model_config;
+mod ocr_cache;
+mod inference_backend;
 mod yas_model;
 mod paddle_paddle_model;
 
 pub use yas_model::yas_ocr_model::YasOCRModel;
-pub use yas_model::yas_ocr_model::yas_ocr_model;
-pub use traits::ImageToText;
+pub use crate::yas_ocr_model;
+pub use yas_model::preprocess::{pre_process, to_gray};
+pub use yas_model::fast_preprocess::{Preprocessor, PREPROCESSED_HEIGHT, PREPROCESSED_LEN, PREPROCESSED_WIDTH};
+pub use traits::{ImageToText, ImageTextDetection};
+pub use text_detection::{detect_and_recognize, sort_text_boxes, DetectedText, TextBox};
 pub use paddle_paddle_model::PPOCRModel;
 pub use paddle_paddle_model::PPOCRChV4RecInfer;
-pub use paddle_paddle_model::ppocr_model;
+pub use crate::ppocr_model;
+pub use paddle_paddle_model::{db_boxes, PPOCRDetModel, DBPostProcessConfig};
+pub use confidence::{ctc_greedy_decode, FieldConfidence, TextWithConfidence, LOW_CONFIDENCE_THRESHOLD};
+pub use lexicon::{ctc_decode, ctc_lexicon_decode, FieldLexicons, Lexicon, LEXICON_BEAM_WIDTH};
+pub use ocr_model_config::OCRModelConfig;
+pub use ocr_cache::{cached_image_to_text, CachedOCRModel, OCRCache, OCRCacheStats, DEFAULT_OCR_CACHE_SIZE};
+pub use inference_backend::{load_backend, InferenceBackend, InferenceOptions, InputShape, OCRBackend};
+#[cfg(feature = "ort")]
+pub use inference_backend::OrtBackend;
+#[cfg(feature = "tract_onnx")]
+pub use inference_backend::TractBackend;
+pub use yas_model_manifest::ModelManifest;
@@ -0,0 +1,278 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use image::{ImageBuffer, Luma, RgbImage};
+
+use crate::ocr::{ImageToText, Lexicon, Preprocessor, TextWithConfidence, PREPROCESSED_HEIGHT, PREPROCESSED_LEN, PREPROCESSED_WIDTH};
+
+/// Number of entries kept by default, an entry takes about 2KB
+pub const DEFAULT_OCR_CACHE_SIZE: usize = 4096;
+
+/// A preprocessed image along with the lexicon it's decoded with.
+/// Preprocessed images are binary, so the pixels are packed into bits and equal keys mean equal tensors
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    pixels: Box<[u64]>,
+    lexicon: Option<u64>,
+}
+
+impl CacheKey {
+    fn new(tensor: &[f32], lexicon: Option<&Lexicon>) -> CacheKey {
+        let pixels = tensor.chunks(64)
+            .map(|chunk| chunk.iter()
+                .enumerate()
+                .fold(0_u64, |bits, (i, &p)| if p != 0.0 { bits | (1 << i) } else { bits }))
+            .collect();
+
+        CacheKey {
+            pixels,
+            lexicon: lexicon.map(|l| l.fingerprint()),
+        }
+    }
+}
+
+/// Least recently used entries are evicted once the capacity is reached
+struct LruMap {
+    capacity: usize,
+    entries: HashMap<Arc<CacheKey>, (TextWithConfidence, u64)>,
+    // last use of each entry, the first one is evicted
+    order: BTreeMap<u64, Arc<CacheKey>>,
+    clock: u64,
+}
+
+impl LruMap {
+    fn new(capacity: usize) -> LruMap {
+        LruMap {
+            capacity,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    fn get(&mut self, key: &CacheKey) -> Option<TextWithConfidence> {
+        self.clock += 1;
+        let clock = self.clock;
+
+        let (key, (value, last_use)) = self.entries.get_key_value(key)?;
+        let (key, value, last_use) = (key.clone(), value.clone(), *last_use);
+        self.order.remove(&last_use);
+        self.order.insert(clock, key.clone());
+        self.entries.get_mut(&key).unwrap().1 = clock;
+
+        Some(value)
+    }
+
+    /// Returns true if an entry was evicted
+    fn insert(&mut self, key: CacheKey, value: TextWithConfidence) -> bool {
+        if self.capacity == 0 {
+            return false;
+        }
+        self.clock += 1;
+
+        if let Some((_, last_use)) = self.entries.get(&key) {
+            self.order.remove(last_use);
+        }
+
+        let mut evicted = false;
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.order.pop_first() {
+                self.entries.remove(&oldest);
+                evicted = true;
+            }
+        }
+
+        let key = Arc::new(key);
+        self.order.insert(self.clock, key.clone());
+        self.entries.insert(key, (value, self.clock));
+
+        evicted
+    }
+}
+
+/// Recognition results of preprocessed images, shared by the recognition threads.
+/// Many crops are the same between items (stat names, set names, empty rows), they're recognized only once
+pub struct OCRCache {
+    map: Mutex<LruMap>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+    evictions: AtomicUsize,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OCRCacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub evictions: usize,
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+impl OCRCacheStats {
+    pub fn lookups(&self) -> usize {
+        self.hits + self.misses
+    }
+
+    pub fn hit_rate(&self) -> f64 {
+        if self.lookups() == 0 {
+            0.0
+        } else {
+            self.hits as f64 / self.lookups() as f64
+        }
+    }
+}
+
+impl fmt::Display for OCRCacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "命中 {} 次，未命中 {} 次，命中率 {:.1}%，缓存 {}/{} 条，淘汰 {} 条",
+            self.hits, self.misses, self.hit_rate() * 100.0, self.entries, self.capacity, self.evictions,
+        )
+    }
+}
+
+impl OCRCache {
+    pub fn new(capacity: usize) -> OCRCache {
+        OCRCache {
+            map: Mutex::new(LruMap::new(capacity)),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+            evictions: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn stats(&self) -> OCRCacheStats {
+        let map = self.map.lock().unwrap();
+        OCRCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: map.entries.len(),
+            capacity: map.capacity,
+        }
+    }
+
+    fn get(&self, key: &CacheKey) -> Option<TextWithConfidence> {
+        let result = self.map.lock().unwrap().get(key);
+        match result {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+        result
+    }
+
+    fn insert(&self, key: CacheKey, value: TextWithConfidence) {
+        if self.map.lock().unwrap().insert(key, value) {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Looks up the results of a model in an `OCRCache` before running it.
+/// The images are preprocessed here for the key, and then passed to the model as preprocessed images,
+/// so the model has to take the images produced by `Preprocessor`, such as `YasOCRModel`
+pub struct CachedOCRModel<M> {
+    model: M,
+    cache: Arc<OCRCache>,
+    preprocessor: RefCell<Preprocessor>,
+}
+
+impl<M> CachedOCRModel<M> {
+    pub fn new(model: M, cache: Arc<OCRCache>) -> CachedOCRModel<M> {
+        CachedOCRModel {
+            model,
+            cache,
+            preprocessor: RefCell::new(Preprocessor::new()),
+        }
+    }
+
+    pub fn cache(&self) -> &Arc<OCRCache> {
+        &self.cache
+    }
+}
+
+/// Wrap the model with the cache if there is one
+pub fn cached_image_to_text<M>(model: M, cache: Option<&Arc<OCRCache>>) -> Box<dyn ImageToText<RgbImage> + Send>
+where
+    M: ImageToText<RgbImage> + ImageToText<ImageBuffer<Luma<f32>, Vec<f32>>> + Send + 'static
+{
+    match cache {
+        Some(cache) => Box::new(CachedOCRModel::new(model, cache.clone())),
+        None => Box::new(model),
+    }
+}
+
+impl<M> ImageToText<RgbImage> for CachedOCRModel<M> where M: ImageToText<ImageBuffer<Luma<f32>, Vec<f32>>> {
+    fn image_to_text_with_confidence(&self, image: &RgbImage, is_preprocessed: bool) -> Result<TextWithConfidence> {
+        let mut result = self.image_to_text_batch_with_lexicon(std::slice::from_ref(image), is_preprocessed, &[None])?;
+        Ok(result.remove(0))
+    }
+
+    fn image_to_text_batch_with_confidence(&self, images: &[RgbImage], is_preprocessed: bool) -> Result<Vec<TextWithConfidence>> {
+        self.image_to_text_batch_with_lexicon(images, is_preprocessed, &vec![None; images.len()])
+    }
+
+    fn image_to_text_with_lexicon(&self, image: &RgbImage, is_preprocessed: bool, lexicon: &Lexicon) -> Result<TextWithConfidence> {
+        let mut result = self.image_to_text_batch_with_lexicon(std::slice::from_ref(image), is_preprocessed, &[Some(lexicon)])?;
+        Ok(result.remove(0))
+    }
+
+    fn image_to_text_batch_with_lexicon(&self, images: &[RgbImage], is_preprocessed: bool, lexicons: &[Option<&Lexicon>]) -> Result<Vec<TextWithConfidence>> {
+        assert!(!is_preprocessed);
+
+        let mut results: Vec<Option<TextWithConfidence>> = vec![None; images.len()];
+        // images which are not in the cache, an image which appears more than once in the batch is recognized once
+        let mut miss_keys: Vec<CacheKey> = Vec::new();
+        let mut miss_indices: Vec<Vec<usize>> = Vec::new();
+        let mut miss_images = Vec::new();
+        let mut miss_lexicons = Vec::new();
+
+        {
+            let mut preprocessor = self.preprocessor.borrow_mut();
+            let mut tensor = vec![0.0; PREPROCESSED_LEN];
+            for (i, image) in images.iter().enumerate() {
+                if !preprocessor.pre_process_rgb(image.as_raw(), image.width() as usize, image.height() as usize, &mut tensor) {
+                    results[i] = Some(TextWithConfidence::blank());
+                    continue;
+                }
+
+                let key = CacheKey::new(&tensor, lexicons[i]);
+                if let Some(j) = miss_keys.iter().position(|k| *k == key) {
+                    self.cache.hits.fetch_add(1, Ordering::Relaxed);
+                    miss_indices[j].push(i);
+                } else if let Some(result) = self.cache.get(&key) {
+                    results[i] = Some(result);
+                } else {
+                    miss_keys.push(key);
+                    miss_indices.push(vec![i]);
+                    miss_images.push(ImageBuffer::from_vec(PREPROCESSED_WIDTH as u32, PREPROCESSED_HEIGHT as u32, tensor.clone()).unwrap());
+                    miss_lexicons.push(lexicons[i]);
+                }
+            }
+        }
+
+        let recognized = if miss_images.is_empty() {
+            Vec::new()
+        } else {
+            self.model.image_to_text_batch_with_lexicon(&miss_images, true, &miss_lexicons)?
+        };
+        for ((key, indices), result) in miss_keys.into_iter().zip(miss_indices).zip(recognized) {
+            for &i in indices.iter() {
+                results[i] = Some(result.clone());
+            }
+            self.cache.insert(key, result);
+        }
+
+        Ok(results.into_iter().map(|r| r.unwrap()).collect())
+    }
+
+    fn get_average_inference_time(&self) -> Option<Duration> {
+        self.model.get_average_inference_time()
+    }
+}
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use image::RgbImage;
+
+use crate::ocr::{cached_image_to_text, ImageToText, InferenceOptions, OCRBackend, OCRCache, YasOCRModel, DEFAULT_OCR_CACHE_SIZE};
+
+#[derive(Clone, clap::Args, Default)]
+pub struct OCRModelConfig {
+    /// Load the OCR model from a file instead of the builtin one.
+    /// If there is a `manifest.json` in the same directory, the model is checked against it
+    #[arg(id = "ocr-model", long = "ocr-model", help = "从文件加载 OCR 模型，代替内置模型", value_name = "FILE", requires = "ocr-dict")]
+    pub ocr_model: Option<PathBuf>,
+
+    /// The `index_2_word.json` of the model given by `--ocr-model`
+    #[arg(id = "ocr-dict", long = "ocr-dict", help = "OCR 模型的字典（index_2_word.json）", value_name = "FILE", requires = "ocr-model")]
+    pub ocr_dict: Option<PathBuf>,
+
+    #[arg(id = "ocr-backend", long = "ocr-backend", help = "OCR 推理后端，auto 优先使用 ONNX Runtime，不可用时使用 tract", default_value_t = OCRBackend::Auto)]
+    #[arg(value_enum)]
+    pub ocr_backend: OCRBackend,
+
+    /// Intra-op threads of ONNX Runtime, tract always runs on a single thread
+    #[arg(id = "ocr-threads", long = "ocr-threads", help = "OCR 推理线程数（仅 ONNX Runtime）", value_name = "THREADS")]
+    pub ocr_threads: Option<usize>,
+
+    /// Number of recognition threads, each runs its own model instance
+    #[arg(id = "ocr-workers", long = "ocr-workers", help = "OCR 识别线程数（每个线程加载一个模型），默认根据 CPU 核数决定", value_name = "WORKERS")]
+    pub ocr_workers: Option<usize>,
+
+    /// Number of recognition results kept in the cache shared by the recognition threads, 0 disables the cache
+    #[arg(id = "ocr-cache-size", long = "ocr-cache-size", help = "OCR 识别结果缓存条数，0 为不缓存", value_name = "SIZE", default_value_t = DEFAULT_OCR_CACHE_SIZE)]
+    pub ocr_cache_size: usize,
+}
+
+impl OCRModelConfig {
+    pub fn inference_options(&self) -> InferenceOptions {
+        InferenceOptions {
+            backend: self.ocr_backend,
+            intra_threads: self.ocr_threads,
+        }
+    }
+
+    /// Half of the cores by default, as the backend uses more than one thread per model, at most 4
+    pub fn worker_count(&self) -> usize {
+        match self.ocr_workers {
+            Some(n) => n.max(1),
+            None => {
+                let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+                (cores / 2).clamp(1, 4)
+            }
+        }
+    }
+
+    /// The cache of the recognition threads of a scan, `None` if it's disabled
+    pub fn ocr_cache(&self) -> Option<Arc<OCRCache>> {
+        if self.ocr_cache_size == 0 {
+            None
+        } else {
+            Some(Arc::new(OCRCache::new(self.ocr_cache_size)))
+        }
+    }
+
+    /// The model given in the command line, or `None` to use the builtin one
+    pub fn load_model(&self) -> Result<Option<YasOCRModel>> {
+        match (&self.ocr_model, &self.ocr_dict) {
+            (Some(model), Some(dict)) => Ok(Some(YasOCRModel::new_from_file(model, dict, &self.inference_options())?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// The model of a recognition thread, `builtin` loads the model of the game when none is given in the command line.
+    /// It's wrapped with the cache of the scan if there is one, the cache is shared by all the threads
+    pub fn image_to_text<F>(&self, ocr_cache: Option<&Arc<OCRCache>>, builtin: F) -> Result<Box<dyn ImageToText<RgbImage> + Send>>
+    where
+        F: FnOnce(&InferenceOptions) -> Result<YasOCRModel>
+    {
+        let model = match self.load_model()? {
+            Some(model) => model,
+            None => builtin(&self.inference_options())?,
+        };
+        Ok(cached_image_to_text(model, ocr_cache))
+    }
+}
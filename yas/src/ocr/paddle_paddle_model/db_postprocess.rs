@@ -0,0 +1,101 @@
+use std::collections::VecDeque;
+
+use crate::positioning::Rect;
+
+/// Thresholds of the DB (Differentiable Binarization) post processing, the defaults are those of PaddleOCR
+#[derive(Debug, Clone)]
+pub struct DBPostProcessConfig {
+    /// Pixels above this probability are text
+    pub thresh: f32,
+    /// Boxes with a lower mean probability are dropped
+    pub box_thresh: f32,
+    /// How much a box is expanded, as the model predicts the shrunk core of the text
+    pub unclip_ratio: f64,
+    /// Boxes with a shorter side are dropped, in pixels of the probability map
+    pub min_size: f64,
+    pub max_candidates: usize,
+}
+
+impl Default for DBPostProcessConfig {
+    fn default() -> Self {
+        Self {
+            thresh: 0.3,
+            box_thresh: 0.6,
+            unclip_ratio: 1.5,
+            min_size: 3.0,
+            max_candidates: 1000,
+        }
+    }
+}
+
+/// Boxes of the connected text regions of a `width x height` probability map, along with their scores.
+/// The boxes are axis aligned, as text in game ui is horizontal
+pub fn db_boxes(probability: &[f32], width: usize, height: usize, config: &DBPostProcessConfig) -> Vec<(Rect<f64>, f32)> {
+    let mut visited = vec![false; width * height];
+    let mut result = Vec::new();
+
+    for start in 0..width * height {
+        if visited[start] || probability[start] <= config.thresh {
+            continue;
+        }
+        if result.len() >= config.max_candidates {
+            break;
+        }
+
+        // flood fill an 8-connected region
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (usize::MAX, usize::MAX, 0, 0);
+        let mut queue = VecDeque::from([start]);
+        visited[start] = true;
+        while let Some(i) = queue.pop_front() {
+            let (x, y) = (i % width, i / width);
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+
+            for dy in -1_i64..=1 {
+                for dx in -1_i64..=1 {
+                    let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                    if nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64 {
+                        continue;
+                    }
+                    let j = ny as usize * width + nx as usize;
+                    if !visited[j] && probability[j] > config.thresh {
+                        visited[j] = true;
+                        queue.push_back(j);
+                    }
+                }
+            }
+        }
+
+        let box_width = (max_x - min_x + 1) as f64;
+        let box_height = (max_y - min_y + 1) as f64;
+        if box_width.min(box_height) < config.min_size {
+            continue;
+        }
+
+        let mut sum = 0.0;
+        for y in min_y..=max_y {
+            sum += probability[y * width + min_x..=y * width + max_x].iter().sum::<f32>();
+        }
+        let score = sum / (box_width * box_height) as f32;
+        if score < config.box_thresh {
+            continue;
+        }
+
+        // expand by area * ratio / perimeter on each side, as PaddleOCR does with the polygon
+        let distance = box_width * box_height * config.unclip_ratio / (2.0 * (box_width + box_height));
+        let left = (min_x as f64 - distance).max(0.0);
+        let top = (min_y as f64 - distance).max(0.0);
+        let right = (max_x as f64 + 1.0 + distance).min(width as f64);
+        let bottom = (max_y as f64 + 1.0 + distance).min(height as f64);
+        let rect = Rect::new(left, top, right - left, bottom - top);
+        if rect.width.min(rect.height) < config.min_size + 2.0 {
+            continue;
+        }
+
+        result.push((rect, score));
+    }
+
+    result
+}
@@ -0,0 +1,116 @@
+use std::cell::RefCell;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, Result};
+use image::RgbImage;
+use ndarray::Ix4;
+
+use crate::ocr::{load_backend, sort_text_boxes, ImageTextDetection, InferenceBackend, InferenceOptions, InputShape, TextBox};
+use crate::positioning::Rect;
+use super::db_postprocess::{db_boxes, DBPostProcessConfig};
+use super::preprocess::{normalize_image_for_detection, resize_img_for_detection};
+
+/// A DB text detection model of PaddleOCR, such as `ch_PP-OCRv4_det_infer.onnx`,
+/// whose output is the text probability of each pixel
+pub struct PPOCRDetModel {
+    model: Box<dyn InferenceBackend>,
+    /// The longer side of the input is limited to this
+    limit_side_len: u32,
+    postprocess_config: DBPostProcessConfig,
+
+    inference_count: RefCell<usize>,
+    inference_time: RefCell<Duration>,
+}
+
+impl PPOCRDetModel {
+    pub const DEFAULT_LIMIT_SIDE_LEN: u32 = 960;
+
+    pub fn new(onnx: &[u8]) -> Result<Self> {
+        Self::new_with_options(onnx, &InferenceOptions::default())
+    }
+
+    pub fn new_with_options(onnx: &[u8], options: &InferenceOptions) -> Result<Self> {
+        // the input size varies with the image
+        let model = load_backend(onnx, &InputShape::Partial(vec![Some(1), Some(3), None, None]), options)?;
+
+        Ok(Self {
+            model,
+            limit_side_len: Self::DEFAULT_LIMIT_SIDE_LEN,
+            postprocess_config: DBPostProcessConfig::default(),
+            inference_count: RefCell::new(0),
+            inference_time: RefCell::new(Duration::new(0, 0)),
+        })
+    }
+
+    pub fn new_from_file<P: AsRef<Path>>(onnx_file: P, options: &InferenceOptions) -> Result<Self> {
+        let onnx_file = onnx_file.as_ref();
+        let onnx = std::fs::read(onnx_file)
+            .map_err(|e| anyhow!("无法读取文本检测模型 {}：{}", onnx_file.display(), e))?;
+        Self::new_with_options(&onnx, options)
+    }
+
+    pub fn with_limit_side_len(mut self, limit_side_len: u32) -> Self {
+        self.limit_side_len = limit_side_len;
+        self
+    }
+
+    pub fn with_postprocess_config(mut self, config: DBPostProcessConfig) -> Self {
+        self.postprocess_config = config;
+        self
+    }
+
+    pub fn get_average_inference_time(&self) -> Option<Duration> {
+        let count = *self.inference_count.borrow();
+        if count == 0 {
+            None
+        } else {
+            Some(self.inference_time.borrow().div_f64(count as f64))
+        }
+    }
+}
+
+impl ImageTextDetection<RgbImage> for PPOCRDetModel {
+    fn detect(&self, image: &RgbImage) -> Result<Vec<TextBox>> {
+        if image.width() == 0 || image.height() == 0 {
+            return Ok(Vec::new());
+        }
+        let start_time = SystemTime::now();
+
+        let resized_image = resize_img_for_detection(image, self.limit_side_len);
+        let tensor = normalize_image_for_detection(&resized_image);
+
+        let arr = self.model.run(tensor.into_dyn())?
+            .into_dimensionality::<Ix4>()
+            .map_err(|_| anyhow!("文本检测模型输出应为 [batch, 1, height, width] 四维张量"))?;
+        let (map_height, map_width) = (arr.shape()[2], arr.shape()[3]);
+        let probability = arr.iter().take(map_width * map_height).copied().collect::<Vec<_>>();
+
+        let scale_x = image.width() as f64 / map_width as f64;
+        let scale_y = image.height() as f64 / map_height as f64;
+        let mut boxes = db_boxes(&probability, map_width, map_height, &self.postprocess_config)
+            .into_iter()
+            .map(|(rect, score)| {
+                let left = (rect.left * scale_x).round() as u32;
+                let top = (rect.top * scale_y).round() as u32;
+                let right = (((rect.left + rect.width) * scale_x).round() as u32).min(image.width());
+                let bottom = (((rect.top + rect.height) * scale_y).round() as u32).min(image.height());
+                TextBox {
+                    rect: Rect::new(left, top, right.saturating_sub(left), bottom.saturating_sub(top)),
+                    score,
+                }
+            })
+            .filter(|b| b.rect.width > 0 && b.rect.height > 0)
+            .collect::<Vec<_>>();
+        sort_text_boxes(&mut boxes);
+
+        *self.inference_time.borrow_mut() += start_time.elapsed()?;
+        *self.inference_count.borrow_mut() += 1;
+
+        Ok(boxes)
+    }
+
+    fn get_average_inference_time(&self) -> Option<Duration> {
+        self.get_average_inference_time()
+    }
+}
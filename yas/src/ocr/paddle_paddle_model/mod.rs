@@ -1,6 +1,9 @@
 mod model;
+mod det_model;
+mod db_postprocess;
 mod preprocess;
 
 pub use model::PPOCRModel;
-pub use model::ppocr_model;
 pub use model::PPOCRChV4RecInfer;
+pub use det_model::PPOCRDetModel;
+pub use db_postprocess::{db_boxes, DBPostProcessConfig};
@@ -1,35 +1,18 @@
 use std::cell::RefCell;
 use std::path::Path;
 use std::time::{Duration, SystemTime};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use image::{EncodableLayout, RgbImage};
-#[cfg(feature = "tract_onnx")]
-use tract_onnx::tract_hir::shapefactoid;
-use crate::ocr::ImageToText;
+use ndarray::Ix3;
+use crate::ocr::{ctc_decode, load_backend, ImageToText, InferenceBackend, InferenceOptions, InputShape, Lexicon, TextWithConfidence};
 use crate::ocr::paddle_paddle_model::preprocess::resize_img;
 use crate::positioning::Shape3D;
 use crate::utils::read_file_to_string;
-#[cfg(feature = "ort")]
-use ort::GraphOptimizationLevel;
-#[cfg(feature = "tract_onnx")]
-use tract_onnx::prelude::*;
-#[cfg(feature = "tract_onnx")]
-use tract_onnx::tract_hir::infer::InferenceOp;
-
-#[cfg(feature = "tract_onnx")]
-use super::preprocess::normalize_image_to_tensor;
-#[cfg(feature = "ort")]
 use super::preprocess::normalize_image_to_ndarray;
 
-#[cfg(feature = "tract_onnx")]
-type ModelType = RunnableModel<InferenceFact, Box<dyn InferenceOp>, Graph<InferenceFact, Box<dyn InferenceOp>>>;
-
 pub struct PPOCRModel {
     index_to_word: Vec<String>,
-    #[cfg(feature = "tract_onnx")]
-    model: ModelType,
-    #[cfg(feature = "ort")]
-    model: ort::Session,
+    model: Box<dyn InferenceBackend>,
 
     inference_count: RefCell<usize>,
     inference_time: RefCell<Duration>,
@@ -47,54 +30,21 @@ fn parse_index_to_word(s: &str, use_whitespace: bool) -> Vec<String> {
 }
 
 impl PPOCRModel {
-    pub fn new_from_file<P1, P2>(onnx_file: P1, words_file: P2) -> Result<PPOCRModel> where P1: AsRef<Path>, P2: AsRef<Path> {
+    pub fn new_from_file<P1, P2>(onnx_file: P1, words_file: P2, options: &InferenceOptions) -> Result<PPOCRModel> where P1: AsRef<Path>, P2: AsRef<Path> {
         let words_str = std::fs::read_to_string(words_file)?;
         let index_to_word = parse_index_to_word(&words_str, true);
+        let onnx = std::fs::read(onnx_file)?;
 
-        #[cfg(feature = "ort")]
-        let model = ort::Session::builder()?
-            .with_optimization_level(GraphOptimizationLevel::Level3)?
-            .with_intra_threads(4)?
-            .commit_from_file(onnx_file)?;
-
-        #[cfg(feature = "tract_onnx")]
-        let model = {
-            let fact = InferenceFact::new().with_datum_type(DatumType::F32)
-                .with_shape(shapefactoid!(_, 3, _, _));
-
-            tract_onnx::onnx()
-                .model_for_path(onnx_file)?
-                .with_input_fact(0, fact)?
-                // .into_optimized()?
-                .into_runnable()?
-        };
-
-        Ok(Self {
-            index_to_word,
-            model,
-            inference_count: RefCell::new(0),
-            inference_time: RefCell::new(Duration::new(0, 0)),
-        })
+        Self::new_with_options(&onnx, index_to_word, options)
     }
 
     pub fn new(onnx: &[u8], index_to_word: Vec<String>) -> Result<Self> {
-        #[cfg(feature = "ort")]
-        let model = ort::Session::builder()?
-            .with_optimization_level(GraphOptimizationLevel::Level3)?
-            .with_intra_threads(4)?
-            .commit_from_memory(onnx)?;
-
-        #[cfg(feature = "tract_onnx")]
-        let model = {
-            let fact = InferenceFact::new().with_datum_type(DatumType::F32)
-                .with_shape(shapefactoid!(_, 3, _, _));
-
-            tract_onnx::onnx()
-                .model_for_read(&mut onnx.as_bytes())?
-                .with_input_fact(0, fact)?
-                // .into_optimized()?
-                .into_runnable()?
-        };
+        Self::new_with_options(onnx, index_to_word, &InferenceOptions::default())
+    }
+
+    pub fn new_with_options(onnx: &[u8], index_to_word: Vec<String>, options: &InferenceOptions) -> Result<Self> {
+        // the width of the input varies with the image
+        let model = load_backend(onnx, &InputShape::Partial(vec![None, Some(3), None, None]), options)?;
 
         Ok(Self {
             index_to_word,
@@ -115,70 +65,45 @@ impl PPOCRModel {
     }
 }
 
-impl ImageToText<RgbImage> for PPOCRModel {
-    fn image_to_text(&self, image: &RgbImage, _is_preprocessed: bool) -> Result<String> {
+impl PPOCRModel {
+    fn recognize(&self, image: &RgbImage, lexicon: Option<&Lexicon>) -> Result<TextWithConfidence> {
         let start_time = SystemTime::now();
 
         let resized_image = resize_img(Shape3D::new(3, 48, 320), image);
 
-        #[cfg(feature = "ort")]
         let tensor = normalize_image_to_ndarray(&resized_image);
-        #[cfg(feature = "tract_onnx")]
-        let tensor = normalize_image_to_tensor(&resized_image);
-
-        #[cfg(feature = "ort")]
-        let result = self.model.run(ort::inputs![tensor]?)?;
-        #[cfg(feature = "tract_onnx")]
-        let result = self.model.run(tvec!(tensor.into()))?;
 
-        #[cfg(feature = "ort")]
-        let arr = result[0].try_extract_tensor()?;
-        #[cfg(feature = "tract_onnx")]
-        let arr = result[0].to_array_view::<f32>()?;
+        let arr = self.model.run(tensor.into_dyn())?
+            .into_dimensionality::<Ix3>()
+            .map_err(|_| anyhow!("模型输出应为 [batch, time, class] 三维张量"))?;
 
         let shape = arr.shape();
         // println!("{:?}", shape);
 
-        let mut text_index = Vec::new();
-
-        for i in 0..shape[1] {
-            let mut max_index = 0;
-            let mut max_value = -f32::INFINITY;
-            for j in 0..shape[2] {
-                let value = arr[[0, i, j]];
-                // println!("{}", value);
-                if value > max_value {
-                    max_value = value;
-                    max_index = j;
-                }
-            }
-            text_index.push(max_index);
-        }
-
-        let mut indices = Vec::new();
-        if text_index[0] != 0 {
-            indices.push(text_index[0]);
-        }
-        for i in 1..text_index.len() {
-            if text_index[i] != text_index[i - 1] && text_index[i] != 0 {
-                indices.push(text_index[i]);
-            }
-        }
-
-        let mut s = String::new();
-        for &index in indices.iter() {
-            s.push_str(&self.index_to_word[index - 1]);
-        }
-
-        // println!("{:?}", text_index);
-
-        // let s = format!("{:?}", shape);
+        // index 0 is the blank
+        let result = ctc_decode(
+            shape[1],
+            shape[2],
+            |t, c| arr[[0, t, c]],
+            |c| if c == 0 { None } else { Some(self.index_to_word[c - 1].as_str()) },
+            lexicon,
+        );
 
         let elapsed_time = start_time.elapsed()?;
         *self.inference_time.borrow_mut() += elapsed_time;
         *self.inference_count.borrow_mut() += 1;
 
-        Ok(s)
+        Ok(result)
+    }
+}
+
+impl ImageToText<RgbImage> for PPOCRModel {
+    fn image_to_text_with_confidence(&self, image: &RgbImage, _is_preprocessed: bool) -> Result<TextWithConfidence> {
+        self.recognize(image, None)
+    }
+
+    fn image_to_text_with_lexicon(&self, image: &RgbImage, _is_preprocessed: bool, lexicon: &Lexicon) -> Result<TextWithConfidence> {
+        self.recognize(image, Some(lexicon))
     }
 
     fn get_average_inference_time(&self) -> Option<Duration> {
@@ -186,21 +111,27 @@ impl ImageToText<RgbImage> for PPOCRModel {
     }
 }
 
-pub macro ppocr_model($onnx:literal, $index_to_word:literal) {
-    {
-        let model_bytes = include_bytes!($onnx);
-        let index_to_word_str = include_str!($index_to_word);
+#[macro_export]
+macro_rules! ppocr_model {
+    ($onnx:literal, $index_to_word:literal) => {
+        $crate::ppocr_model!($onnx, $index_to_word, &$crate::ocr::InferenceOptions::default())
+    };
+    ($onnx:literal, $index_to_word:literal, $options:expr) => {
+        {
+            let model_bytes = include_bytes!($onnx);
+            let index_to_word_str = include_str!($index_to_word);
+
+            let mut index_to_word_vec: Vec<String> = Vec::new();
+            for line in index_to_word_str.lines() {
+                index_to_word_vec.push(String::from(line));
+            }
+            index_to_word_vec.push(String::from(" "));
 
-        let mut index_to_word_vec: Vec<String> = Vec::new();
-        for line in index_to_word_str.lines() {
-            index_to_word_vec.push(String::from(line));
+            $crate::ocr::PPOCRModel::new_with_options(
+                model_bytes, index_to_word_vec, $options,
+            )
         }
-        index_to_word_vec.push(String::from(" "));
-
-        PPOCRModel::new(
-            model_bytes, index_to_word_vec,
-        )
-    }
+    };
 }
 
 pub struct PPOCRChV4RecInfer {
@@ -209,15 +140,23 @@ pub struct PPOCRChV4RecInfer {
 
 impl PPOCRChV4RecInfer {
     pub fn new() -> Result<Self> {
+        Self::new_with_options(&InferenceOptions::default())
+    }
+
+    pub fn new_with_options(options: &InferenceOptions) -> Result<Self> {
         Ok(Self {
-            model: ppocr_model!("./ch_PP-OCRv4_rec_infer.onnx", "./ppocr_keys_v1.txt")?
+            model: ppocr_model!("./ch_PP-OCRv4_rec_infer.onnx", "./ppocr_keys_v1.txt", options)?
         })
     }
 }
 
 impl ImageToText<RgbImage> for PPOCRChV4RecInfer {
-    fn image_to_text(&self, image: &RgbImage, is_preprocessed: bool) -> Result<String> {
-        self.model.image_to_text(image, is_preprocessed)
+    fn image_to_text_with_confidence(&self, image: &RgbImage, is_preprocessed: bool) -> Result<TextWithConfidence> {
+        self.model.image_to_text_with_confidence(image, is_preprocessed)
+    }
+
+    fn image_to_text_with_lexicon(&self, image: &RgbImage, is_preprocessed: bool, lexicon: &Lexicon) -> Result<TextWithConfidence> {
+        self.model.image_to_text_with_lexicon(image, is_preprocessed, lexicon)
     }
 
     fn get_average_inference_time(&self) -> Option<Duration> {
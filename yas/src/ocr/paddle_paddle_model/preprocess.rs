@@ -2,8 +2,6 @@ use image::{ImageBuffer, RgbImage};
 use crate::positioning::Shape3D;
 use anyhow::Result;
 use image::imageops::{FilterType, resize};
-#[cfg(feature = "tract_onnx")]
-use tract_onnx::prelude::{Tensor, tract_ndarray};
 
 /// Resize an image to the expected height, but the width can vary
 /// rec_image_shape: the expected shape to feed into the onnx model. CHW
@@ -20,7 +18,6 @@ pub fn resize_img(rec_image_shape: Shape3D<u32>, img: &RgbImage) -> RgbImage {
     resized_image
 }
 
-#[cfg(feature = "ort")]
 pub fn normalize_image_to_ndarray(img: &RgbImage) -> ndarray::Array4<f32> {
     let height = img.height() as usize;
     let width = img.width() as usize;
@@ -39,14 +36,28 @@ pub fn normalize_image_to_ndarray(img: &RgbImage) -> ndarray::Array4<f32> {
     arr
 }
 
-#[cfg(feature = "tract_onnx")]
-pub fn normalize_image_to_tensor(img: &RgbImage) -> Tensor {
+/// Resize an image for the detection model, the longer side is limited to `limit_side_len`
+/// and both sides are rounded to multiples of 32
+pub fn resize_img_for_detection(img: &RgbImage, limit_side_len: u32) -> RgbImage {
+    let width = img.width();
+    let height = img.height();
+    let max_side = width.max(height);
+    let ratio = if max_side > limit_side_len { limit_side_len as f64 / max_side as f64 } else { 1.0 };
+
+    let round_32 = |v: u32| (((v as f64 * ratio / 32.0).round() as u32) * 32).max(32);
+    resize(img, round_32(width), round_32(height), FilterType::Triangle)
+}
+
+/// The detection model is normalized with the ImageNet mean and std, in BGR order as PaddleOCR reads images
+pub fn normalize_image_for_detection(img: &RgbImage) -> ndarray::Array4<f32> {
     let height = img.height() as usize;
     let width = img.width() as usize;
-    let tensor: Tensor = tract_ndarray::Array4::from_shape_fn((1, 3, height, width), |(_, c, y, x)| {
-        let pix = img.get_pixel(x as u32, y as u32)[c];
+    let mean = [0.485_f32, 0.456, 0.406];
+    let std = [0.229_f32, 0.224, 0.225];
+
+    ndarray::Array4::from_shape_fn((1, 3, height, width), |(_, c, y, x)| {
+        let pix = img.get_pixel(x as u32, y as u32)[2 - c];
         let v = pix as f32 / 255.0_f32;
-        (v - 0.5) / 0.5
-    }).into();
-    tensor
-}
\ No newline at end of file
+        (v - mean[c]) / std[c]
+    })
+}
@@ -0,0 +1,66 @@
+use image::{GenericImageView, RgbImage};
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::ocr::{ImageTextDetection, ImageToText, TextWithConfidence};
+use crate::positioning::Rect;
+
+/// A line of text located by a detector, in pixels of the image
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TextBox {
+    pub rect: Rect<u32>,
+    /// Mean text probability inside the box
+    pub score: f32,
+}
+
+/// A detected line of text along with what it reads
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DetectedText {
+    pub rect: Rect<u32>,
+    pub detection_score: f32,
+    pub text: TextWithConfidence,
+}
+
+/// Sort boxes top to bottom, and left to right for boxes on the same line.
+/// Two boxes are on the same line if their tops are closer than half of the lower box
+pub fn sort_text_boxes(boxes: &mut [TextBox]) {
+    boxes.sort_by_key(|b| (b.rect.top, b.rect.left));
+
+    // a box slightly higher but to the right of the previous one is still after it
+    for i in 1..boxes.len() {
+        let mut j = i;
+        while j > 0 {
+            let (a, b) = (&boxes[j - 1].rect, &boxes[j].rect);
+            let same_line = b.top.abs_diff(a.top) < a.height.min(b.height) / 2;
+            if same_line && b.left < a.left {
+                boxes.swap(j - 1, j);
+                j -= 1;
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+/// Locate the lines of text in the image and recognize each of them, in reading order.
+/// Used for regions without a fixed layout, e.g. a list of stats whose length varies
+pub fn detect_and_recognize(
+    detector: &dyn ImageTextDetection<RgbImage>,
+    recognizer: &dyn ImageToText<RgbImage>,
+    image: &RgbImage,
+) -> Result<Vec<DetectedText>> {
+    let boxes = detector.detect(image)?;
+    let crops = boxes.iter()
+        .map(|b| image.view(b.rect.left, b.rect.top, b.rect.width, b.rect.height).to_image())
+        .collect::<Vec<_>>();
+    let texts = recognizer.image_to_text_batch_with_confidence(&crops, false)?;
+
+    Ok(boxes.into_iter()
+        .zip(texts)
+        .map(|(b, text)| DetectedText {
+            rect: b.rect,
+            detection_score: b.score,
+            text,
+        })
+        .collect())
+}
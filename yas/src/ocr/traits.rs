@@ -2,12 +2,53 @@ use std::time::Duration;
 
 use anyhow::Result;
 
+use crate::ocr::{Lexicon, TextBox, TextWithConfidence};
+
 pub trait ImageToText<ImageType> {
-    fn image_to_text(&self, image: &ImageType, is_preprocessed: bool) -> Result<String>;
+    /// Recognize the text along with the confidence of each character
+    fn image_to_text_with_confidence(&self, image: &ImageType, is_preprocessed: bool) -> Result<TextWithConfidence>;
+
+    fn image_to_text(&self, image: &ImageType, is_preprocessed: bool) -> Result<String> {
+        Ok(self.image_to_text_with_confidence(image, is_preprocessed)?.text)
+    }
+
+    /// Recognize several images, the results are in the same order as the images.
+    /// Models which support batching run them through the model at once, otherwise they are recognized one by one
+    fn image_to_text_batch_with_confidence(&self, images: &[ImageType], is_preprocessed: bool) -> Result<Vec<TextWithConfidence>> {
+        images.iter().map(|image| self.image_to_text_with_confidence(image, is_preprocessed)).collect()
+    }
+
+    fn image_to_text_batch(&self, images: &[ImageType], is_preprocessed: bool) -> Result<Vec<String>> {
+        Ok(self.image_to_text_batch_with_confidence(images, is_preprocessed)?
+            .into_iter()
+            .map(|r| r.text)
+            .collect())
+    }
+
+    /// Recognize text which is known to be one of the words of the lexicon, the most probable word is returned.
+    /// If no word fits, the unconstrained result is kept.
+    /// By default the result is corrected to the closest word, models with access to their raw output decode with the lexicon instead
+    fn image_to_text_with_lexicon(&self, image: &ImageType, is_preprocessed: bool, lexicon: &Lexicon) -> Result<TextWithConfidence> {
+        Ok(lexicon.correct(self.image_to_text_with_confidence(image, is_preprocessed)?))
+    }
+
+    /// Recognize several images, each one is constrained to its lexicon if there is one
+    fn image_to_text_batch_with_lexicon(&self, images: &[ImageType], is_preprocessed: bool, lexicons: &[Option<&Lexicon>]) -> Result<Vec<TextWithConfidence>> {
+        images.iter()
+            .zip(lexicons.iter())
+            .map(|(image, lexicon)| match lexicon {
+                Some(lexicon) => self.image_to_text_with_lexicon(image, is_preprocessed, lexicon),
+                None => self.image_to_text_with_confidence(image, is_preprocessed),
+            })
+            .collect()
+    }
 
     fn get_average_inference_time(&self) -> Option<Duration>;
 }
 
-// pub trait ImageTextDetection<ImageType> {
-//
-// }
+pub trait ImageTextDetection<ImageType> {
+    /// Locate the lines of text in the image, in reading order
+    fn detect(&self, image: &ImageType) -> Result<Vec<TextBox>>;
+
+    fn get_average_inference_time(&self) -> Option<Duration>;
+}
@@ -0,0 +1,367 @@
+//! Preprocessing on contiguous row-major buffers.
+//!
+//! Produces exactly the same values as `preprocess::pre_process`, but writes the `32x384` image
+//! straight into a slice of the input tensor, and reuses its buffers between images.
+//! The resizing reproduces the triangle filter of `image::imageops::resize` operation by operation,
+//! so that the results are bit-identical.
+
+use log::warn;
+
+/// Width of a preprocessed image
+pub const PREPROCESSED_WIDTH: usize = 384;
+/// Height of a preprocessed image
+pub const PREPROCESSED_HEIGHT: usize = 32;
+/// Number of values of a preprocessed image, i.e. one `[1, 1, 32, 384]` slice of the input tensor
+pub const PREPROCESSED_LEN: usize = PREPROCESSED_WIDTH * PREPROCESSED_HEIGHT;
+
+/// A rectangle of the gray buffer
+#[derive(Clone, Copy)]
+struct Region {
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Region {
+    fn row<'a>(&self, buffer: &'a [f32], stride: usize, y: usize) -> &'a [f32] {
+        let start = (self.top + y) * stride + self.left;
+        &buffer[start..start + self.width]
+    }
+
+    fn row_mut<'a>(&self, buffer: &'a mut [f32], stride: usize, y: usize) -> &'a mut [f32] {
+        let start = (self.top + y) * stride + self.left;
+        &mut buffer[start..start + self.width]
+    }
+}
+
+/// Source pixels `left..left + len` contribute to an output pixel, with weights `weights[start..start + len]`
+#[derive(Clone, Copy)]
+struct Taps {
+    left: usize,
+    start: usize,
+    len: usize,
+}
+
+/// Triangle filter weights for resizing `src_len` pixels to `dst_len` pixels, computed the same way as `image` does
+fn triangle_taps(src_len: usize, dst_len: usize, taps: &mut Vec<Taps>, weights: &mut Vec<f32>) {
+    taps.clear();
+    weights.clear();
+
+    let ratio = src_len as f32 / dst_len as f32;
+    let sratio = if ratio < 1.0 { 1.0 } else { ratio };
+    // the triangle filter has a support of 1
+    let support = sratio;
+
+    for out in 0..dst_len {
+        let input = (out as f32 + 0.5) * ratio;
+
+        let left = ((input - support).floor() as i64).clamp(0, src_len as i64 - 1) as usize;
+        let right = ((input + support).ceil() as i64).clamp(left as i64 + 1, src_len as i64) as usize;
+
+        let input = input - 0.5;
+
+        let start = weights.len();
+        let mut sum = 0.0;
+        for i in left..right {
+            let x = ((i as f32 - input) / sratio).abs();
+            let w = if x < 1.0 { 1.0 - x } else { 0.0 };
+            weights.push(w);
+            sum += w;
+        }
+        weights[start..].iter_mut().for_each(|w| *w /= sum);
+
+        taps.push(Taps { left, start, len: right - left });
+    }
+}
+
+/// Preprocesses images for the yas OCR model, keeping its buffers for the next image
+#[derive(Default)]
+pub struct Preprocessor {
+    gray: Vec<f32>,
+    vertical: Vec<f32>,
+    vertical_taps: Vec<Taps>,
+    vertical_weights: Vec<f32>,
+    horizontal_taps: Vec<Taps>,
+    horizontal_weights: Vec<f32>,
+}
+
+impl Preprocessor {
+    pub fn new() -> Preprocessor {
+        Default::default()
+    }
+
+    /// `rgb` is a row-major RGB buffer of `width * height` pixels, `out` is one `32x384` image.
+    /// Returns false if the image has only one color, then `out` is left untouched
+    pub fn pre_process_rgb(&mut self, rgb: &[u8], width: usize, height: usize, out: &mut [f32]) -> bool {
+        assert_eq!(rgb.len(), width * height * 3);
+
+        self.gray.clear();
+        self.gray.resize(width * height, 0.0);
+        kernels::rgb_to_gray(rgb, &mut self.gray);
+
+        self.pre_process_loaded(width, height, out)
+    }
+
+    /// Same as `pre_process_rgb`, `gray` is a row-major f32 gray buffer of `width * height` pixels
+    pub fn pre_process_gray(&mut self, gray: &[f32], width: usize, height: usize, out: &mut [f32]) -> bool {
+        assert_eq!(gray.len(), width * height);
+
+        self.gray.clear();
+        self.gray.extend_from_slice(gray);
+
+        self.pre_process_loaded(width, height, out)
+    }
+
+    fn pre_process_loaded(&mut self, width: usize, height: usize, out: &mut [f32]) -> bool {
+        assert_eq!(out.len(), PREPROCESSED_LEN);
+
+        let full = Region { left: 0, top: 0, width, height };
+        if !normalize(&mut self.gray, width, full, true) {
+            return false;
+        }
+
+        let region = crop(&self.gray, width, full);
+        normalize(&mut self.gray, width, region, false);
+
+        self.resize_and_pad(region, width, out);
+        kernels::threshold(out, 0.53);
+
+        true
+    }
+
+    /// Resize `region` of the gray buffer into the top left of `out`, the rest is zero
+    fn resize_and_pad(&mut self, region: Region, stride: usize, out: &mut [f32]) {
+        let w = region.width;
+        let h = region.height;
+
+        let new_width = if w as f64 / (h as f64) > 384.0 / 32.0 {
+            PREPROCESSED_WIDTH
+        } else {
+            std::cmp::min((32.0 / h as f64 * w as f64) as usize, PREPROCESSED_WIDTH)
+        };
+        let new_height = std::cmp::min((384.0 / w as f64 * h as f64) as usize, PREPROCESSED_HEIGHT);
+
+        out.fill(0.0);
+        if new_width == 0 || new_height == 0 {
+            return;
+        }
+
+        // `image` copies the image when the size does not change
+        if (new_width, new_height) == (w, h) {
+            for y in 0..h {
+                out[y * PREPROCESSED_WIDTH..y * PREPROCESSED_WIDTH + w].copy_from_slice(region.row(&self.gray, stride, y));
+            }
+            return;
+        }
+
+        // vertical pass first, into a `w x new_height` buffer
+        triangle_taps(h, new_height, &mut self.vertical_taps, &mut self.vertical_weights);
+        self.vertical.clear();
+        self.vertical.resize(w * new_height, 0.0);
+        for (y, taps) in self.vertical_taps.iter().enumerate() {
+            let dst = &mut self.vertical[y * w..(y + 1) * w];
+            let weights = &self.vertical_weights[taps.start..taps.start + taps.len];
+            for (i, &weight) in weights.iter().enumerate() {
+                kernels::accumulate(dst, region.row(&self.gray, stride, taps.left + i), weight);
+            }
+        }
+
+        triangle_taps(w, new_width, &mut self.horizontal_taps, &mut self.horizontal_weights);
+        for y in 0..new_height {
+            let src = &self.vertical[y * w..(y + 1) * w];
+            let dst = &mut out[y * PREPROCESSED_WIDTH..y * PREPROCESSED_WIDTH + new_width];
+            for (x, taps) in self.horizontal_taps.iter().enumerate() {
+                let weights = &self.horizontal_weights[taps.start..taps.start + taps.len];
+                let mut t = 0.0_f32;
+                for (i, &weight) in weights.iter().enumerate() {
+                    t += src[taps.left + i] * weight;
+                }
+                dst[x] = t.clamp(0.0, 1.0);
+            }
+        }
+    }
+}
+
+/// Same as `preprocess::normalize`, on `region` of the buffer
+fn normalize(buffer: &mut [f32], stride: usize, region: Region, auto_inverse: bool) -> bool {
+    if region.width == 0 || region.height == 0 {
+        warn!("wrong width or height");
+        return false;
+    }
+
+    let mut max: f32 = 0.0;
+    let mut min: f32 = 256.0;
+    for y in 0..region.height {
+        for &p in region.row(buffer, stride, y) {
+            if p > max {
+                max = p;
+            }
+            if p < min {
+                min = p;
+            }
+        }
+    }
+
+    if max == min {
+        return false;
+    }
+
+    let flag_x = if region.width >= 2 { region.width - 2 } else { region.width - 1 };
+    let flag_pixel = region.row(buffer, stride, region.height - 1)[flag_x];
+    let flag_pixel = (flag_pixel - min) / (max - min);
+
+    let inverse = auto_inverse && flag_pixel > 0.5;
+    for y in 0..region.height {
+        kernels::normalize(region.row_mut(buffer, stride, y), min, max - min, inverse);
+    }
+
+    true
+}
+
+/// Same as `preprocess::crop`, scanning rows instead of columns
+fn crop(buffer: &[f32], stride: usize, region: Region) -> Region {
+    let mut min_col = region.width - 1;
+    let mut max_col = 0;
+    let mut min_row = region.height - 1;
+    let mut max_row = 0;
+
+    for y in 0..region.height {
+        let row = region.row(buffer, stride, y);
+        if let Some(first) = row.iter().position(|&p| p > 0.7) {
+            let last = row.iter().rposition(|&p| p > 0.7).unwrap();
+            min_col = min_col.min(first);
+            max_col = max_col.max(last);
+            min_row = min_row.min(y);
+            max_row = max_row.max(y);
+        }
+    }
+
+    if min_col > max_col || min_row > max_row {
+        return region;
+    }
+
+    Region {
+        left: region.left + min_col,
+        top: region.top + min_row,
+        width: max_col - min_col + 1,
+        height: max_row - min_row + 1,
+    }
+}
+
+/// Per pixel operations. The SIMD versions do the same f32 operations in the same order for each lane
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+mod kernels {
+    pub fn rgb_to_gray(rgb: &[u8], out: &mut [f32]) {
+        for (p, gray) in rgb.chunks_exact(3).zip(out.iter_mut()) {
+            let r = p[0] as f32 / 255.0;
+            let g = p[1] as f32 / 255.0;
+            let b = p[2] as f32 / 255.0;
+            *gray = r * 0.2989 + g * 0.5870 + b * 0.1140;
+        }
+    }
+
+    pub fn normalize(row: &mut [f32], min: f32, range: f32, inverse: bool) {
+        for p in row.iter_mut() {
+            let v = (*p - min) / range;
+            *p = if inverse { 1.0 - v } else { v };
+        }
+    }
+
+    /// `dst += src * weight`
+    pub fn accumulate(dst: &mut [f32], src: &[f32], weight: f32) {
+        for (d, &s) in dst.iter_mut().zip(src.iter()) {
+            *d += s * weight;
+        }
+    }
+
+    pub fn threshold(buffer: &mut [f32], threshold: f32) {
+        for p in buffer.iter_mut() {
+            *p = if *p < threshold { 0.0 } else { 1.0 };
+        }
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod kernels {
+    use std::arch::x86_64::*;
+
+    pub fn rgb_to_gray(rgb: &[u8], out: &mut [f32]) {
+        let pixels = rgb.chunks_exact(12);
+        let rest = pixels.remainder();
+        let mut out_chunks = out.chunks_exact_mut(4);
+
+        // SSE2 is always available on x86_64
+        unsafe {
+            let scale = _mm_set1_ps(255.0);
+            let (wr, wg, wb) = (_mm_set1_ps(0.2989), _mm_set1_ps(0.5870), _mm_set1_ps(0.1140));
+            for (p, o) in pixels.zip(&mut out_chunks) {
+                let r = _mm_div_ps(_mm_setr_ps(p[0] as f32, p[3] as f32, p[6] as f32, p[9] as f32), scale);
+                let g = _mm_div_ps(_mm_setr_ps(p[1] as f32, p[4] as f32, p[7] as f32, p[10] as f32), scale);
+                let b = _mm_div_ps(_mm_setr_ps(p[2] as f32, p[5] as f32, p[8] as f32, p[11] as f32), scale);
+                let gray = _mm_add_ps(_mm_add_ps(_mm_mul_ps(r, wr), _mm_mul_ps(g, wg)), _mm_mul_ps(b, wb));
+                _mm_storeu_ps(o.as_mut_ptr(), gray);
+            }
+        }
+
+        for (p, gray) in rest.chunks_exact(3).zip(out_chunks.into_remainder().iter_mut()) {
+            let r = p[0] as f32 / 255.0;
+            let g = p[1] as f32 / 255.0;
+            let b = p[2] as f32 / 255.0;
+            *gray = r * 0.2989 + g * 0.5870 + b * 0.1140;
+        }
+    }
+
+    pub fn normalize(row: &mut [f32], min: f32, range: f32, inverse: bool) {
+        let mut chunks = row.chunks_exact_mut(4);
+        unsafe {
+            let (vmin, vrange, one) = (_mm_set1_ps(min), _mm_set1_ps(range), _mm_set1_ps(1.0));
+            for c in &mut chunks {
+                let mut v = _mm_div_ps(_mm_sub_ps(_mm_loadu_ps(c.as_ptr()), vmin), vrange);
+                if inverse {
+                    v = _mm_sub_ps(one, v);
+                }
+                _mm_storeu_ps(c.as_mut_ptr(), v);
+            }
+        }
+
+        for p in chunks.into_remainder() {
+            let v = (*p - min) / range;
+            *p = if inverse { 1.0 - v } else { v };
+        }
+    }
+
+    /// `dst += src * weight`
+    pub fn accumulate(dst: &mut [f32], src: &[f32], weight: f32) {
+        let len = dst.len().min(src.len());
+        let (dst, src) = (&mut dst[..len], &src[..len]);
+        let mut dst_chunks = dst.chunks_exact_mut(4);
+        let mut src_chunks = src.chunks_exact(4);
+        unsafe {
+            let w = _mm_set1_ps(weight);
+            for (d, s) in (&mut dst_chunks).zip(&mut src_chunks) {
+                let v = _mm_add_ps(_mm_loadu_ps(d.as_ptr()), _mm_mul_ps(_mm_loadu_ps(s.as_ptr()), w));
+                _mm_storeu_ps(d.as_mut_ptr(), v);
+            }
+        }
+
+        for (d, &s) in dst_chunks.into_remainder().iter_mut().zip(src_chunks.remainder()) {
+            *d += s * weight;
+        }
+    }
+
+    pub fn threshold(buffer: &mut [f32], threshold: f32) {
+        let mut chunks = buffer.chunks_exact_mut(4);
+        unsafe {
+            let (t, one) = (_mm_set1_ps(threshold), _mm_set1_ps(1.0));
+            for c in &mut chunks {
+                let below = _mm_cmplt_ps(_mm_loadu_ps(c.as_ptr()), t);
+                _mm_storeu_ps(c.as_mut_ptr(), _mm_andnot_ps(below, one));
+            }
+        }
+
+        for p in chunks.into_remainder() {
+            *p = if *p < threshold { 0.0 } else { 1.0 };
+        }
+    }
+}
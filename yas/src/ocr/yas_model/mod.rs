@@ -1,4 +1,5 @@
 pub mod preprocess;
+pub mod fast_preprocess;
 pub mod yas_ocr_model;
 
 // pub use preprocess::to_gray;
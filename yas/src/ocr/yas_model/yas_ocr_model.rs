@@ -1,23 +1,26 @@
-use std::{cell::RefCell, time::Duration};
+use std::{cell::{Cell, RefCell}, time::Duration};
+use std::path::Path;
 use std::time::SystemTime;
 use image::{EncodableLayout, GrayImage, ImageBuffer, Luma, RgbImage};
+use log::{info, warn};
+use ndarray::{s, Array4, Ix3};
 // use tract_onnx::prelude::*;
 use crate::ocr::traits::ImageToText;
-use super::preprocess;
-use anyhow::Result;
+use crate::ocr::{ctc_decode, load_backend, InferenceBackend, InferenceOptions, InputShape, Lexicon, ModelManifest, TextWithConfidence};
+use super::fast_preprocess::{Preprocessor, PREPROCESSED_HEIGHT, PREPROCESSED_LEN, PREPROCESSED_WIDTH};
+use anyhow::{anyhow, bail, Result};
+use yas_model_manifest::{dict_words, LfsPointer};
 use crate::common::image_ext::*;
-#[cfg(feature = "tract_onnx")]
-use tract_onnx::prelude::*;
 
-#[cfg(feature = "tract_onnx")]
-type ModelType = RunnableModel<TypedFact, Box<dyn TypedOp>, Graph<TypedFact, Box<dyn TypedOp>>>;
+/// Input shape of a single image, NCHW
+const INPUT_SHAPE: [usize; 4] = [1, 1, 32, 384];
 
 pub struct YasOCRModel {
-    #[cfg(feature = "ort")]
-    model: ort::Session,
-    #[cfg(feature = "tract_onnx")]
-    model: ModelType,
+    model: Box<dyn InferenceBackend>,
     index_to_word: Vec<String>,
+    // cleared when the model turns out to have a fixed batch size of 1
+    batch_supported: Cell<bool>,
+    preprocessor: RefCell<Preprocessor>,
 
     inference_time: RefCell<Duration>,   // in seconds
     invoke_count: RefCell<usize>,
@@ -36,107 +39,215 @@ impl YasOCRModel {
     }
 
     pub fn new(model: &[u8], content: &str) -> Result<YasOCRModel> {
-        #[cfg(feature = "ort")]
-        let model = ort::Session::builder()?
-            .with_optimization_level(ort::GraphOptimizationLevel::Level3)?
-            .with_intra_threads(4)?
-            .commit_from_memory(model)?;
-        #[cfg(feature = "tract_onnx")]
-        let model = tract_onnx::onnx()
-            .model_for_read(&mut model.as_bytes())?
-            .with_input_fact(0, f32::fact([1, 1, 32, 384]).into())?
-            .into_optimized()?
-            .into_runnable()?;
-
-        let json = serde_json::from_str::<serde_json::Value>(content)?;
-
-        let mut index_to_word = json
-            .as_object()
-            .unwrap()
-            .iter()
-            .map(|(k, v)| (k.parse::<usize>().unwrap(), v.as_str().unwrap().to_string()))
-            .collect::<Vec<(usize, String)>>();
-
-        index_to_word.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
-
-        let index_to_word = index_to_word.into_iter().map(|(_, v)| v).collect();
-
-        Ok(YasOCRModel {
+        Self::new_with_options(model, content, &InferenceOptions::default())
+    }
+
+    pub fn new_with_options(model: &[u8], content: &str, options: &InferenceOptions) -> Result<YasOCRModel> {
+        check_not_lfs_pointer(model)?;
+        let index_to_word = dict_words(content)?;
+
+        // the shape declared in the model has a dynamic batch dimension when exported so,
+        // otherwise the input is fixed to a single image
+        let (model, batch_supported) = match load_backend(model, &InputShape::Declared, options) {
+            Ok(m) => (m, true),
+            Err(e) => {
+                warn!("OCR 模型不支持批量推理：{}", e);
+                (load_backend(model, &InputShape::Fixed(INPUT_SHAPE.to_vec()), options)?, false)
+            }
+        };
+        info!("OCR 推理后端：{}", model.name());
+
+        let result = YasOCRModel {
             model,
             index_to_word,
+            batch_supported: Cell::new(batch_supported),
+            preprocessor: RefCell::new(Preprocessor::new()),
             inference_time: RefCell::new(Duration::new(0, 0)),
             invoke_count: RefCell::new(0),
-        })
+        };
+
+        // a blank image, so that a model which does not fit the input shape or the dictionary fails now rather than while scanning
+        let output = result.model.run(Array4::<f32>::zeros(INPUT_SHAPE).into_dyn())
+            .map_err(|e| anyhow!("OCR 模型无法运行：{}", e))?;
+        // [time, batch, class]
+        let class_count = match output.shape() {
+            &[_, _, classes] => classes,
+            shape => bail!("模型输出应为 [time, batch, class] 三维张量，实际形状为 {:?}", shape),
+        };
+        if class_count != result.index_to_word.len() {
+            bail!("模型输出 {} 类，与字典大小 {} 不符，字典可能不属于该模型", class_count, result.index_to_word.len());
+        }
+
+        Ok(result)
     }
 
-    pub fn inference_string(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>) -> Result<String> {
-        let now = SystemTime::now();
+    /// Check the model and the dictionary against the manifest before loading
+    pub fn new_with_manifest(model: &[u8], content: &str, manifest: &ModelManifest, options: &InferenceOptions) -> Result<YasOCRModel> {
+        check_not_lfs_pointer(model)?;
+        manifest.verify(model, content)?;
+        if manifest.input_shape != INPUT_SHAPE {
+            bail!("模型输入形状为 {:?}，仅支持 {:?}", manifest.input_shape, INPUT_SHAPE);
+        }
+
+        Self::new_with_options(model, content, options)
+    }
+
+    /// Load the model and its dictionary from files. If there is a `manifest.json` in the directory of the model,
+    /// it's checked against the manifest
+    pub fn new_from_file<P1, P2>(model_file: P1, dict_file: P2, options: &InferenceOptions) -> Result<YasOCRModel> where P1: AsRef<Path>, P2: AsRef<Path> {
+        let model_file = model_file.as_ref();
+        let model = std::fs::read(model_file)
+            .map_err(|e| anyhow!("无法读取 OCR 模型 {}：{}", model_file.display(), e))?;
+        let content = std::fs::read_to_string(dict_file.as_ref())
+            .map_err(|e| anyhow!("无法读取字典 {}：{}", dict_file.as_ref().display(), e))?;
+
+        let manifest_file = model_file.with_file_name("manifest.json");
+        if manifest_file.is_file() {
+            let manifest = ModelManifest::load(&manifest_file)?;
+            Self::new_with_manifest(&model, &content, &manifest, options)
+        } else {
+            Self::new_with_options(&model, &content, options)
+        }
+    }
+
+    /// CTC decoding of one image, `value(t, c)` is the score of class `c` at time step `t`.
+    /// The model output is in `[time, batch, class]`
+    fn decode(&self, time_steps: usize, value: impl Fn(usize, usize) -> f32, lexicon: Option<&Lexicon>) -> TextWithConfidence {
+        ctc_decode(
+            time_steps,
+            self.index_to_word.len(),
+            value,
+            |c| {
+                let word = self.index_to_word[c].as_str();
+                if word == "-" { None } else { Some(word) }
+            },
+            lexicon,
+        )
+    }
+
+    /// Run an `[N, 1, 32, 384]` tensor of preprocessed images through the model, each image is decoded with its lexicon
+    fn run_tensor(&self, tensor: Array4<f32>, lexicons: &[Option<&Lexicon>]) -> Result<Vec<TextWithConfidence>> {
+        let n = tensor.shape()[0];
 
-        #[cfg(feature = "ort")]
-        let tensor = ndarray::Array4::from_shape_fn((1, 1, 32, 384), |(_, _, y, x)| {
-            img.get_pixel(x as u32, y as u32)[0]
-        });
-        #[cfg(feature = "tract_onnx")]
-        let tensor: Tensor =
-            tract_ndarray::Array4::from_shape_fn((1, 1, 32, 384), |(_, _, y, x)| {
-                img.get_pixel(x as u32, y as u32)[0]
-            }).into();
-
-        #[cfg(feature = "ort")]
-        let result = self.model.run(ort::inputs![tensor]?)?;
-        #[cfg(feature = "tract_onnx")]
-        let result = self.model.run(tvec!(tensor.into()))?;
-
-        #[cfg(feature = "ort")]
-        let arr = result[0].try_extract_tensor()?;
-        #[cfg(feature = "tract_onnx")]
-        let arr = result[0].to_array_view::<f32>()?;
+        let arr = self.model.run(tensor.into_dyn())?
+            .into_dimensionality::<Ix3>()
+            .map_err(|_| anyhow!("模型输出应为 [time, batch, class] 三维张量"))?;
 
         let shape = arr.shape();
+        if shape[2] != self.index_to_word.len() {
+            bail!("模型输出 {} 类，与字典大小 {} 不符", shape[2], self.index_to_word.len());
+        }
 
-        let mut ans = String::new();
-        let mut last_word = String::new();
-        for i in 0..shape[0] {
-            let mut max_index = 0;
-            let mut max_value = -1.0_f32;
-            for j in 0..self.index_to_word.len() {
-                let value = arr[[i, 0, j]];
-                if value > max_value {
-                    max_value = value;
-                    max_index = j;
-                }
-            }
-            let word = &self.index_to_word[max_index];
-            if *word != last_word && word != "-" {
-                ans = ans + word;
-            }
+        Ok((0..n).map(|b| self.decode(shape[0], |i, j| arr[[i, b, j]], lexicons[b])).collect())
+    }
+
+    pub fn inference_string(&self, img: &ImageBuffer<Luma<f32>, Vec<f32>>) -> Result<String> {
+        let mut result = self.inference_batch_with_confidence(&[img])?;
+        Ok(result.remove(0).text)
+    }
+
+    /// Recognize preprocessed images with a single model run if the model supports batching
+    pub fn inference_batch_with_confidence(&self, images: &[&ImageBuffer<Luma<f32>, Vec<f32>>]) -> Result<Vec<TextWithConfidence>> {
+        self.inference_batch_with_lexicon(images, &vec![None; images.len()])
+    }
 
-            last_word.clone_from(word);
+    /// Same as `inference_batch_with_confidence`, each image is constrained to its lexicon if there is one
+    pub fn inference_batch_with_lexicon(&self, images: &[&ImageBuffer<Luma<f32>, Vec<f32>>], lexicons: &[Option<&Lexicon>]) -> Result<Vec<TextWithConfidence>> {
+        self.inference_tensor_with_lexicon(images_to_tensor(images)?, lexicons)
+    }
+
+    /// Same as `inference_batch_with_lexicon`, on an `[N, 1, 32, 384]` tensor of preprocessed images
+    fn inference_tensor_with_lexicon(&self, tensor: Array4<f32>, lexicons: &[Option<&Lexicon>]) -> Result<Vec<TextWithConfidence>> {
+        let n = tensor.shape()[0];
+        if n == 0 {
+            return Ok(Vec::new());
         }
+        let now = SystemTime::now();
+
+        let result = if n == 1 || !self.batch_supported.get() {
+            (0..n)
+                .map(|b| {
+                    let single = tensor.slice(s![b..b + 1, .., .., ..]).to_owned();
+                    self.run_tensor(single, &[lexicons[b]]).map(|mut v| v.remove(0))
+                })
+                .collect::<Result<Vec<_>>>()?
+        } else {
+            match self.run_tensor(tensor.clone(), lexicons) {
+                Ok(v) => v,
+                Err(e) => {
+                    warn!("OCR 模型不支持批量推理，改为逐个识别：{}", e);
+                    self.batch_supported.set(false);
+                    return self.inference_tensor_with_lexicon(tensor, lexicons);
+                }
+            }
+        };
 
         let time = now.elapsed()?;
-        
-        *self.invoke_count.borrow_mut() += 1;
+
+        // the average time is per image
+        *self.invoke_count.borrow_mut() += n;
         *self.inference_time.borrow_mut() += time;
 
-        Ok(ans)
+        Ok(result)
+    }
+
+    /// Preprocess `count` images straight into the input tensor, `preprocess(preprocessor, i, out)` writes the `i`th image into `out`.
+    /// Images which have only one color are blank, and recognized as empty strings without running the model
+    fn inference_preprocessed(
+        &self,
+        count: usize,
+        lexicons: &[Option<&Lexicon>],
+        mut preprocess: impl FnMut(&mut Preprocessor, usize, &mut [f32]) -> bool
+    ) -> Result<Vec<TextWithConfidence>> {
+        let mut data = vec![0.0; count * PREPROCESSED_LEN];
+        let mut present = Vec::with_capacity(count);
+        {
+            let mut preprocessor = self.preprocessor.borrow_mut();
+            for i in 0..count {
+                let offset = present.len() * PREPROCESSED_LEN;
+                if preprocess(&mut preprocessor, i, &mut data[offset..offset + PREPROCESSED_LEN]) {
+                    present.push(i);
+                }
+            }
+        }
+        data.truncate(present.len() * PREPROCESSED_LEN);
+
+        let tensor = Array4::from_shape_vec((present.len(), 1, PREPROCESSED_HEIGHT, PREPROCESSED_WIDTH), data)?;
+        let present_lexicons = present.iter().map(|&i| lexicons[i]).collect::<Vec<_>>();
+        let mut results = self.inference_tensor_with_lexicon(tensor, &present_lexicons)?.into_iter();
+
+        let mut present = present.into_iter().peekable();
+        Ok((0..count)
+            .map(|i| match present.next_if_eq(&i) {
+                Some(_) => results.next().unwrap(),
+                None => TextWithConfidence::blank(),
+            })
+            .collect())
     }
 }
 
 impl ImageToText<RgbImage> for YasOCRModel {
-    fn image_to_text(&self, image: &RgbImage, is_preprocessed: bool) -> Result<String> {
-        assert!(!is_preprocessed);
+    fn image_to_text_with_confidence(&self, image: &RgbImage, is_preprocessed: bool) -> Result<TextWithConfidence> {
+        let mut result = self.image_to_text_batch_with_lexicon(std::slice::from_ref(image), is_preprocessed, &[None])?;
+        Ok(result.remove(0))
+    }
 
-        let gray_image_float = preprocess::to_gray(image);
-        let (result, non_mono) = preprocess::pre_process(gray_image_float);
+    fn image_to_text_batch_with_confidence(&self, images: &[RgbImage], is_preprocessed: bool) -> Result<Vec<TextWithConfidence>> {
+        self.image_to_text_batch_with_lexicon(images, is_preprocessed, &vec![None; images.len()])
+    }
 
-        if !non_mono {
-            return Ok(String::new());
-        }
+    fn image_to_text_with_lexicon(&self, image: &RgbImage, is_preprocessed: bool, lexicon: &Lexicon) -> Result<TextWithConfidence> {
+        let mut result = self.image_to_text_batch_with_lexicon(std::slice::from_ref(image), is_preprocessed, &[Some(lexicon)])?;
+        Ok(result.remove(0))
+    }
 
-        let string_result = self.inference_string(&result)?;
+    fn image_to_text_batch_with_lexicon(&self, images: &[RgbImage], is_preprocessed: bool, lexicons: &[Option<&Lexicon>]) -> Result<Vec<TextWithConfidence>> {
+        assert!(!is_preprocessed);
 
-        Ok(string_result)
+        self.inference_preprocessed(images.len(), lexicons, |preprocessor, i, out| {
+            let image = &images[i];
+            preprocessor.pre_process_rgb(image.as_raw(), image.width() as usize, image.height() as usize, out)
+        })
     }
 
     fn get_average_inference_time(&self) -> Option<Duration> {
@@ -145,21 +256,29 @@ impl ImageToText<RgbImage> for YasOCRModel {
 }
 
 impl ImageToText<ImageBuffer<Luma<f32>, Vec<f32>>> for YasOCRModel {
-    fn image_to_text(&self, image: &ImageBuffer<Luma<f32>, Vec<f32>>, is_preprocessed: bool) -> Result<String> {
-        if is_preprocessed {
-            let string_result = self.inference_string(image)?;
-            Ok(string_result)
-        } else {
-            let im = image.clone();
-            let (preprocess_result, non_mono) = preprocess::pre_process(im);
+    fn image_to_text_with_confidence(&self, image: &ImageBuffer<Luma<f32>, Vec<f32>>, is_preprocessed: bool) -> Result<TextWithConfidence> {
+        let mut result = self.image_to_text_batch_with_lexicon(std::slice::from_ref(image), is_preprocessed, &[None])?;
+        Ok(result.remove(0))
+    }
 
-            if !non_mono {
-                return Ok(String::new());
-            }
+    fn image_to_text_batch_with_confidence(&self, images: &[ImageBuffer<Luma<f32>, Vec<f32>>], is_preprocessed: bool) -> Result<Vec<TextWithConfidence>> {
+        self.image_to_text_batch_with_lexicon(images, is_preprocessed, &vec![None; images.len()])
+    }
 
-            let string_result = self.inference_string(&preprocess_result)?;
-            Ok(string_result)
+    fn image_to_text_with_lexicon(&self, image: &ImageBuffer<Luma<f32>, Vec<f32>>, is_preprocessed: bool, lexicon: &Lexicon) -> Result<TextWithConfidence> {
+        let mut result = self.image_to_text_batch_with_lexicon(std::slice::from_ref(image), is_preprocessed, &[Some(lexicon)])?;
+        Ok(result.remove(0))
+    }
+
+    fn image_to_text_batch_with_lexicon(&self, images: &[ImageBuffer<Luma<f32>, Vec<f32>>], is_preprocessed: bool, lexicons: &[Option<&Lexicon>]) -> Result<Vec<TextWithConfidence>> {
+        if is_preprocessed {
+            return self.inference_batch_with_lexicon(&images.iter().collect::<Vec<_>>(), lexicons);
         }
+
+        self.inference_preprocessed(images.len(), lexicons, |preprocessor, i, out| {
+            let image = &images[i];
+            preprocessor.pre_process_gray(image.as_raw(), image.width() as usize, image.height() as usize, out)
+        })
     }
 
     fn get_average_inference_time(&self) -> Option<Duration> {
@@ -168,9 +287,24 @@ impl ImageToText<ImageBuffer<Luma<f32>, Vec<f32>>> for YasOCRModel {
 }
 
 impl ImageToText<GrayImage> for YasOCRModel {
-    fn image_to_text(&self, im: &GrayImage, is_preprocessed: bool) -> Result<String> {
+    fn image_to_text_with_confidence(&self, im: &GrayImage, is_preprocessed: bool) -> Result<TextWithConfidence> {
+        let gray_f32_image: ImageBuffer<Luma<f32>, Vec<f32>> = im.to_f32_gray_image();
+        self.image_to_text_with_confidence(&gray_f32_image, is_preprocessed)
+    }
+
+    fn image_to_text_batch_with_confidence(&self, images: &[GrayImage], is_preprocessed: bool) -> Result<Vec<TextWithConfidence>> {
+        let gray_f32_images = images.iter().map(|im| im.to_f32_gray_image()).collect::<Vec<_>>();
+        self.image_to_text_batch_with_confidence(&gray_f32_images, is_preprocessed)
+    }
+
+    fn image_to_text_with_lexicon(&self, im: &GrayImage, is_preprocessed: bool, lexicon: &Lexicon) -> Result<TextWithConfidence> {
         let gray_f32_image: ImageBuffer<Luma<f32>, Vec<f32>> = im.to_f32_gray_image();
-        self.image_to_text(&gray_f32_image, is_preprocessed)
+        self.image_to_text_with_lexicon(&gray_f32_image, is_preprocessed, lexicon)
+    }
+
+    fn image_to_text_batch_with_lexicon(&self, images: &[GrayImage], is_preprocessed: bool, lexicons: &[Option<&Lexicon>]) -> Result<Vec<TextWithConfidence>> {
+        let gray_f32_images = images.iter().map(|im| im.to_f32_gray_image()).collect::<Vec<_>>();
+        self.image_to_text_batch_with_lexicon(&gray_f32_images, is_preprocessed, lexicons)
     }
 
     fn get_average_inference_time(&self) -> Option<Duration> {
@@ -178,13 +312,60 @@ impl ImageToText<GrayImage> for YasOCRModel {
     }
 }
 
-pub macro yas_ocr_model($model_name:literal, $index_to_word:literal) {
-    {
-        let model_bytes = include_bytes!($model_name);
-        let index_to_word = include_str!($index_to_word);
+/// A checkout without Git LFS has pointer files in place of the models
+fn check_not_lfs_pointer(model: &[u8]) -> Result<()> {
+    if let Some(pointer) = LfsPointer::parse(model) {
+        bail!("OCR 模型文件是 Git LFS 指针文件（oid {}）而不是模型本身。请安装 Git LFS 并运行 `git lfs pull`", pointer.oid);
+    }
+    Ok(())
+}
 
-        YasOCRModel::new(
-            model_bytes, index_to_word,
-        )
+/// Stack preprocessed `32x384` images into an `[N, 1, 32, 384]` tensor
+fn images_to_tensor(images: &[&ImageBuffer<Luma<f32>, Vec<f32>>]) -> Result<Array4<f32>> {
+    let mut data = Vec::with_capacity(images.len() * PREPROCESSED_LEN);
+    for image in images {
+        if image.dimensions() != (PREPROCESSED_WIDTH as u32, PREPROCESSED_HEIGHT as u32) {
+            bail!("预处理后的图片大小应为 {}x{}，实际为 {}x{}", PREPROCESSED_WIDTH, PREPROCESSED_HEIGHT, image.width(), image.height());
+        }
+        data.extend_from_slice(image.as_raw());
     }
+    Ok(Array4::from_shape_vec((images.len(), 1, PREPROCESSED_HEIGHT, PREPROCESSED_WIDTH), data)?)
+}
+
+#[macro_export]
+macro_rules! yas_ocr_model {
+    ($model_name:literal, $index_to_word:literal) => {
+        {
+            let model_bytes = include_bytes!($model_name);
+            let index_to_word = include_str!($index_to_word);
+
+            $crate::ocr::YasOCRModel::new(
+                model_bytes, index_to_word,
+            )
+        }
+    };
+    ($model_name:literal, $index_to_word:literal, $manifest:literal) => {
+        {
+            let model_bytes = include_bytes!($model_name);
+            let index_to_word = include_str!($index_to_word);
+
+            $crate::ocr::ModelManifest::from_json(include_str!($manifest)).and_then(|manifest| {
+                $crate::ocr::YasOCRModel::new_with_manifest(
+                    model_bytes, index_to_word, &manifest, &$crate::ocr::InferenceOptions::default(),
+                )
+            })
+        }
+    };
+    ($model_name:literal, $index_to_word:literal, $manifest:literal, $options:expr) => {
+        {
+            let model_bytes = include_bytes!($model_name);
+            let index_to_word = include_str!($index_to_word);
+
+            $crate::ocr::ModelManifest::from_json(include_str!($manifest)).and_then(|manifest| {
+                $crate::ocr::YasOCRModel::new_with_manifest(
+                    model_bytes, index_to_word, &manifest, $options,
+                )
+            })
+        }
+    };
 }
@@ -0,0 +1,83 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+
+pub struct LabelledSample {
+    pub image: PathBuf,
+    pub text: String,
+}
+
+/// Crops of text along with the expected text
+pub struct LabelledDataset {
+    pub samples: Vec<LabelledSample>,
+}
+
+impl LabelledDataset {
+    /// Load from a label file or a directory, see `OCREvaluationConfig::dataset`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let dataset = if path.is_dir() {
+            Self::load_dir(path)?
+        } else {
+            Self::load_label_file(path)?
+        };
+
+        if dataset.samples.is_empty() {
+            bail!("数据集 {:?} 中没有样本", path);
+        }
+        Ok(dataset)
+    }
+
+    /// Empty lines and lines starting with `#` are skipped
+    fn load_label_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("无法读取标注文件 {:?}：{}", path, e))?;
+        let base = path.parent().unwrap_or(Path::new("."));
+
+        let mut samples = Vec::new();
+        for (line_number, line) in content.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (image, text) = line.split_once('\t')
+                .ok_or_else(|| anyhow!("标注文件 {:?} 第 {} 行格式错误，应为“图片路径<TAB>文本”", path, line_number + 1))?;
+            samples.push(LabelledSample {
+                image: base.join(image),
+                text: String::from(text),
+            });
+        }
+
+        Ok(Self { samples })
+    }
+
+    fn load_dir(dir: &Path) -> Result<Self> {
+        let mut images = std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        images.retain(|p| p.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("png")));
+        images.sort();
+
+        let mut samples = Vec::new();
+        for image in images {
+            let label = image.with_extension("txt");
+            let text = std::fs::read_to_string(&label)
+                .map_err(|e| anyhow!("无法读取 {:?} 的标注 {:?}：{}", image, label, e))?;
+            samples.push(LabelledSample {
+                image,
+                text: String::from(text.trim_end_matches(['\r', '\n'])),
+            });
+        }
+
+        Ok(Self { samples })
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Result};
+use image::RgbImage;
+use log::info;
+use prettytable::{row, Table};
+use serde::Serialize;
+
+use crate::ocr::ImageToText;
+use crate::ocr_evaluation::LabelledDataset;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub enum CharEdit {
+    Match(char),
+    /// The expected char, and the char recognized instead
+    Substitute(char, char),
+    /// An expected char which is not recognized
+    Delete(char),
+    /// A recognized char which is not expected
+    Insert(char),
+}
+
+impl fmt::Display for CharEdit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CharEdit::Match(c) => write!(f, "{} → {}", c, c),
+            CharEdit::Substitute(e, r) => write!(f, "{} → {}", e, r),
+            CharEdit::Delete(e) => write!(f, "{} → ∅", e),
+            CharEdit::Insert(r) => write!(f, "∅ → {}", r),
+        }
+    }
+}
+
+/// Align the recognized text to the expected one with the fewest edits (Levenshtein distance)
+pub fn align_chars(expected: &str, recognized: &str) -> Vec<CharEdit> {
+    let expected = expected.chars().collect::<Vec<_>>();
+    let recognized = recognized.chars().collect::<Vec<_>>();
+    let n = expected.len();
+    let m = recognized.len();
+
+    let mut distance = vec![vec![0_usize; m + 1]; n + 1];
+    for (i, row) in distance.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, d) in distance[0].iter_mut().enumerate() {
+        *d = j;
+    }
+    for i in 1..=n {
+        for j in 1..=m {
+            let cost = if expected[i - 1] == recognized[j - 1] { 0 } else { 1 };
+            distance[i][j] = (distance[i - 1][j - 1] + cost)
+                .min(distance[i - 1][j] + 1)
+                .min(distance[i][j - 1] + 1);
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 {
+            let (e, r) = (expected[i - 1], recognized[j - 1]);
+            let cost = if e == r { 0 } else { 1 };
+            if distance[i][j] == distance[i - 1][j - 1] + cost {
+                edits.push(if cost == 0 { CharEdit::Match(e) } else { CharEdit::Substitute(e, r) });
+                i -= 1;
+                j -= 1;
+                continue;
+            }
+        }
+        if i > 0 && distance[i][j] == distance[i - 1][j] + 1 {
+            edits.push(CharEdit::Delete(expected[i - 1]));
+            i -= 1;
+        } else {
+            edits.push(CharEdit::Insert(recognized[j - 1]));
+            j -= 1;
+        }
+    }
+
+    edits.reverse();
+    edits
+}
+
+#[derive(Serialize)]
+pub struct EvaluationFailure {
+    pub image: PathBuf,
+    pub expected: String,
+    pub recognized: String,
+    pub confidence: f32,
+}
+
+#[derive(Serialize)]
+pub struct OCREvaluationReport {
+    pub sample_count: usize,
+    pub exact_match_count: usize,
+    /// Chars of the expected texts
+    pub char_count: usize,
+    /// Total edit distance between the expected and the recognized texts
+    pub char_error_count: usize,
+    /// Edits other than matches, the most frequent first
+    pub confusions: Vec<(CharEdit, usize)>,
+    pub failures: Vec<EvaluationFailure>,
+    /// Per image, as reported by the model
+    pub average_inference_ms: Option<f64>,
+    /// Including loading the images and preprocessing
+    pub total_ms: f64,
+}
+
+/// Runs a model over a labelled dataset
+pub struct OCREvaluator<'a> {
+    model: &'a dyn ImageToText<RgbImage>,
+}
+
+impl<'a> OCREvaluator<'a> {
+    pub fn new(model: &'a dyn ImageToText<RgbImage>) -> Self {
+        Self { model }
+    }
+
+    pub fn evaluate(&self, dataset: &LabelledDataset) -> Result<OCREvaluationReport> {
+        let now = SystemTime::now();
+
+        let mut exact_match_count = 0;
+        let mut char_count = 0;
+        let mut char_error_count = 0;
+        let mut confusions: HashMap<CharEdit, usize> = HashMap::new();
+        let mut failures = Vec::new();
+
+        for (i, sample) in dataset.samples.iter().enumerate() {
+            let image = image::open(&sample.image)
+                .map_err(|e| anyhow!("无法读取图片 {:?}：{}", sample.image, e))?
+                .to_rgb8();
+            let result = self.model.image_to_text_with_confidence(&image, false)?;
+
+            char_count += sample.text.chars().count();
+            if result.text == sample.text {
+                exact_match_count += 1;
+            } else {
+                for edit in align_chars(&sample.text, &result.text) {
+                    if !matches!(edit, CharEdit::Match(_)) {
+                        char_error_count += 1;
+                        *confusions.entry(edit).or_insert(0) += 1;
+                    }
+                }
+                failures.push(EvaluationFailure {
+                    image: sample.image.clone(),
+                    expected: sample.text.clone(),
+                    recognized: result.text,
+                    confidence: result.confidence,
+                });
+            }
+
+            if (i + 1) % 500 == 0 {
+                info!("已评估 {}/{}", i + 1, dataset.len());
+            }
+        }
+
+        let mut confusions = confusions.into_iter().collect::<Vec<_>>();
+        confusions.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        Ok(OCREvaluationReport {
+            sample_count: dataset.len(),
+            exact_match_count,
+            char_count,
+            char_error_count,
+            confusions,
+            failures,
+            average_inference_ms: self.model.get_average_inference_time().map(|t| t.as_secs_f64() * 1000.0),
+            total_ms: now.elapsed()?.as_secs_f64() * 1000.0,
+        })
+    }
+}
+
+impl OCREvaluationReport {
+    pub fn exact_match_rate(&self) -> f64 {
+        if self.sample_count == 0 {
+            0.0
+        } else {
+            self.exact_match_count as f64 / self.sample_count as f64
+        }
+    }
+
+    /// Edit distance over the length of the expected texts, which can exceed 1 if many extra chars are recognized
+    pub fn char_error_rate(&self) -> f64 {
+        if self.char_count == 0 {
+            0.0
+        } else {
+            self.char_error_count as f64 / self.char_count as f64
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn get_confusion_table(&self, count: usize) -> Table {
+        let mut table = Table::new();
+
+        table.add_row(row!["Expected → Recognized", "Count"]);
+        for (edit, n) in self.confusions.iter().take(count) {
+            table.add_row(row![edit, n]);
+        }
+
+        table
+    }
+
+    pub fn get_failure_table(&self, count: usize) -> Table {
+        let mut table = Table::new();
+
+        table.add_row(row!["Image", "Expected", "Recognized", "Confidence"]);
+        for failure in self.failures.iter().take(count) {
+            table.add_row(row![failure.image.display(), failure.expected, failure.recognized, format!("{:.3}", failure.confidence)]);
+        }
+
+        table
+    }
+}
+
+impl fmt::Display for OCREvaluationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let average = self.average_inference_ms.map(|t| format!("{:.2}ms", t)).unwrap_or(String::from("-"));
+        write!(
+            f,
+            "{} samples: exact match {:.2}% ({}), CER {:.2}% ({}/{}), average inference {}, total {:.0}ms",
+            self.sample_count,
+            self.exact_match_rate() * 100.0,
+            self.exact_match_count,
+            self.char_error_rate() * 100.0,
+            self.char_error_count,
+            self.char_count,
+            average,
+            self.total_ms,
+        )
+    }
+}
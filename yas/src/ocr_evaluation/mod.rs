@@ -0,0 +1,7 @@
+mod ocr_evaluation_config;
+mod dataset;
+mod evaluator;
+
+pub use ocr_evaluation_config::{OCREvaluationConfig, EvaluationModel};
+pub use dataset::{LabelledDataset, LabelledSample};
+pub use evaluator::{align_chars, CharEdit, OCREvaluator, OCREvaluationReport, EvaluationFailure};
@@ -0,0 +1,32 @@
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum EvaluationModel {
+    /// A Yas model given by `--ocr-model` and `--ocr-dict`
+    Yas,
+    /// The builtin PaddleOCR v4 recognition model
+    PPOCR,
+}
+
+#[derive(Clone, clap::Args)]
+pub struct OCREvaluationConfig {
+    /// Either a label file, each line is `<png path>\t<expected text>` with the path relative to the file,
+    /// or a directory of PNGs, each with the expected text in a `.txt` file of the same name
+    #[arg(id = "dataset", long = "dataset", help = "标注数据集：标注文件（每行为“图片路径<TAB>文本”）或包含 PNG 及同名 TXT 的目录", value_name = "PATH")]
+    pub dataset: PathBuf,
+
+    #[arg(id = "model", long = "model", help = "评估的模型，yas 需要通过 --ocr-model 和 --ocr-dict 指定", default_value_t = EvaluationModel::Yas)]
+    #[arg(value_enum)]
+    pub model: EvaluationModel,
+
+    #[arg(id = "output", long = "output", short = 'o', help = "将评估结果保存为 JSON 文件", value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    #[arg(id = "top-confusions", long = "top-confusions", help = "显示最常见的字符混淆数量", default_value_t = 20)]
+    pub top_confusions: usize,
+
+    #[arg(id = "show-failures", long = "show-failures", help = "显示识别错误的样本数量", default_value_t = 20)]
+    pub show_failures: usize,
+}
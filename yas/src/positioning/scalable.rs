@@ -8,12 +8,14 @@ impl Scalable for f64 {
     }
 }
 
-macro impl_int_scale($t:ty) {
-    impl Scalable for $t {
-        fn scale(&self, factor: f64) -> Self {
-            ((*self as f64) * factor) as $t
+macro_rules! impl_int_scale {
+    ($t:ty) => {
+        impl Scalable for $t {
+            fn scale(&self, factor: f64) -> Self {
+                ((*self as f64) * factor) as $t
+            }
         }
-    }
+    };
 }
 
 impl_int_scale!(i32);
@@ -0,0 +1,35 @@
+use image::RgbImage;
+
+/// Detects a switch of the selected item from captures of the pool rect
+pub trait ChangeDetector {
+    /// A value of the captured pool rect, which differs between items
+    fn pool(&self, image: &RgbImage) -> f64;
+
+    /// Whether the pool is of a blank panel, which is shown while the game switches items.
+    /// A switch is complete as soon as the pool changes after a blank one
+    fn is_blank(&self, _pool: f64) -> bool {
+        false
+    }
+}
+
+/// Sum of the red channel
+pub struct RedChannelSum;
+
+impl ChangeDetector for RedChannelSum {
+    fn pool(&self, image: &RgbImage) -> f64 {
+        image.pixels().map(|p| p.0[0] as f64).sum()
+    }
+}
+
+/// Count of the white pixels, the panel is blank when there is none
+pub struct WhitePixelCount;
+
+impl ChangeDetector for WhitePixelCount {
+    fn pool(&self, image: &RgbImage) -> f64 {
+        image.pixels().filter(|p| p.0 == [255, 255, 255]).count() as f64
+    }
+
+    fn is_blank(&self, pool: f64) -> bool {
+        pool == 0.0
+    }
+}
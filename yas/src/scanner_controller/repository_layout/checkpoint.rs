@@ -0,0 +1,50 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Where a grid scan stopped, enough to scroll back and continue with the next item
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GridScanProgress {
+    /// Items of the inventory when the scan started, a resumed scan has to see the same inventory
+    pub item_count: usize,
+    /// Row and col in the inventory of the next item to scan
+    pub row: usize,
+    pub col: usize,
+
+    /// Learned scrolling of the controller, so pages are scrolled by the estimate right away
+    pub avg_scroll_one_row: f64,
+    pub scrolled_rows: u32,
+}
+
+impl GridScanProgress {
+    /// How many items were scanned, in an inventory of `col` columns
+    pub fn scanned_count(&self, col: usize) -> usize {
+        self.row * col + self.col
+    }
+}
+
+/// The progress of a scan along with the results parsed so far, saved when a scan stops early
+#[derive(Serialize, Deserialize)]
+pub struct ScanCheckpoint<T> {
+    pub progress: GridScanProgress,
+    pub results: Vec<T>,
+}
+
+impl<T: Serialize> ScanCheckpoint<T> {
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let s = serde_json::to_string(self)?;
+        fs::write(path, s).map_err(|e| anyhow!("无法写入检查点 {:?}：{}", path, e))
+    }
+}
+
+impl<T: DeserializeOwned> ScanCheckpoint<T> {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let s = fs::read_to_string(path).map_err(|e| anyhow!("无法读取检查点 {:?}：{}", path, e))?;
+        serde_json::from_str(&s).map_err(|e| anyhow!("检查点 {:?} 格式错误：{}", path, e))
+    }
+}
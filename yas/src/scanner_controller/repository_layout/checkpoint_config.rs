@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use log::{error, info, warn};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::scanner_controller::repository_layout::{GridScanProgress, ScanCheckpoint};
+
+#[derive(Clone, clap::Args)]
+pub struct ScanCheckpointConfig {
+    /// Where the progress and the results so far are saved, if the scan fails or is interrupted
+    #[arg(id = "checkpoint", long = "checkpoint", help = "扫描失败或中断时，保存扫描进度的文件", value_name = "FILE", default_value = "yas_checkpoint.json")]
+    pub checkpoint: PathBuf,
+
+    /// Continue a scan from a saved checkpoint
+    #[arg(id = "resume", long = "resume", help = "从保存的扫描进度继续扫描，并与之前的结果合并", value_name = "CHECKPOINT")]
+    pub resume: Option<PathBuf>,
+}
+
+impl Default for ScanCheckpointConfig {
+    fn default() -> Self {
+        ScanCheckpointConfig {
+            checkpoint: PathBuf::from("yas_checkpoint.json"),
+            resume: None,
+        }
+    }
+}
+
+impl ScanCheckpointConfig {
+    /// The checkpoint to resume from, if any
+    pub fn load<T: DeserializeOwned>(&self) -> Result<Option<ScanCheckpoint<T>>> {
+        self.resume.as_ref().map(ScanCheckpoint::load).transpose()
+    }
+
+    /// Save the progress along with the results so far, so that the scan can be resumed
+    pub fn save<T: Serialize + Clone>(&self, progress: GridScanProgress, results: &[T]) {
+        let path = &self.checkpoint;
+        let checkpoint = ScanCheckpoint { progress, results: results.to_vec() };
+        match checkpoint.save(path) {
+            Ok(_) => info!("扫描进度已保存至 {:?}，使用 --resume {:?} 继续扫描", path, path),
+            Err(e) => error!("保存扫描进度失败：{}", e),
+        }
+    }
+
+    /// Save a checkpoint if the scan stopped early with `progress`.
+    /// Otherwise the scan is complete, and the checkpoint resumed from is removed so it's not resumed again
+    pub fn update<T: Serialize + Clone>(&self, progress: Option<GridScanProgress>, results: &[T]) {
+        match (progress, &self.resume) {
+            (Some(progress), _) => self.save(progress, results),
+            (None, Some(path)) => match std::fs::remove_file(path) {
+                Ok(_) => info!("扫描已完成，删除检查点 {:?}", path),
+                Err(e) => warn!("无法删除检查点 {:?}：{}", path, e),
+            },
+            (None, None) => (),
+        }
+    }
+}
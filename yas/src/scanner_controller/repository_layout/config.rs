@@ -1,7 +1,7 @@
 use clap::arg;
 
-#[derive(clap::Args, Clone)]
-pub struct WWRepositoryLayoutConfig {
+#[derive(Clone, clap::Args)]
+pub struct GridScanControllerConfig {
     /// Max rows to scan
     #[arg(id = "max-row", long = "max-row", help = "最大扫描行数")]
     pub max_row: Option<usize>,
@@ -10,9 +10,6 @@ pub struct WWRepositoryLayoutConfig {
     #[arg(id = "scroll-delay", long = "scroll-delay", help = "翻页时滚轮停顿时间（ms）（翻页不正确可以考虑加大该选项）", default_value_t = 80)]
     pub scroll_delay: i32,
 
-    /// Dump the captured image
-    // pub dump_mode: bool,
-
     /// The maximum time to wait for switching to the next item
     #[arg(id = "max-wait-switch-item", long = "max-wait-switch-item", help = "切换物品最大等待时间（ms）", default_value_t = 800)]
     pub max_wait_switch_item: i32,
@@ -22,13 +19,13 @@ pub struct WWRepositoryLayoutConfig {
     pub cloud_wait_switch_item: i32,
 }
 
-impl Default for WWRepositoryLayoutConfig {
+impl Default for GridScanControllerConfig {
     fn default() -> Self {
-        Self {
+        GridScanControllerConfig {
             max_row: None,
             scroll_delay: 80,
             max_wait_switch_item: 800,
-            cloud_wait_switch_item: 800,
+            cloud_wait_switch_item: 300,
         }
     }
 }
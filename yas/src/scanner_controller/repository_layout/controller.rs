@@ -0,0 +1,635 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Result};
+use clap::{ArgMatches, FromArgMatches};
+use image::RgbImage;
+use log::{error, info};
+
+use crate::capture::Capturer;
+use crate::game_info::GameInfo;
+use crate::positioning::Pos;
+use crate::profiler::Profiler;
+use crate::scanner_controller::repository_layout::{ChangeDetector, GridScanControllerConfig, GridScanProgress, GridWindowInfo, RowFlag, RowScroll, ScrollResult};
+use crate::system_control::SystemControl;
+use crate::utils;
+use crate::window_info::{FromWindowInfoRepository, WindowInfoRepository};
+
+/// Scans a grid of items page by page: clicks each item, waits until it's displayed, and scrolls to the next page.
+/// The layout, and how switches and aligned rows are detected, come from the window info `W` of the game
+pub struct GridScanController<W> {
+    /// A value computed from the pool rect, to detect whether an item changes
+    pool: f64,
+
+    /// How many rows were scrolled row by row
+    scrolled_rows: u32,
+    /// Average wheel events to scroll a row
+    avg_scroll_one_row: f64,
+
+    /// Average time for an item to be switched and fully displayed
+    avg_switch_time: f64,
+    /// How many items were scanned
+    scanned_count: usize,
+
+    game_info: GameInfo,
+
+    /// How many rows/cols a page have
+    row: usize,
+    col: usize,
+
+    config: GridScanControllerConfig,
+    window_info: W,
+    change_detector: Box<dyn ChangeDetector>,
+    row_flag: Box<dyn RowFlag>,
+    row_scroll: RowScroll,
+
+    system_control: Box<dyn SystemControl>,
+    capturer: Rc<dyn Capturer<RgbImage>>,
+
+    pub profiler: RefCell<Profiler>,
+}
+
+pub enum ReturnResult {
+    Interrupted,
+    Finished,
+}
+
+impl<W: GridWindowInfo + FromWindowInfoRepository> GridScanController<W> {
+    pub fn new(
+        window_info_repo: &WindowInfoRepository,
+        config: GridScanControllerConfig,
+        game_info: GameInfo,
+        capturer: Rc<dyn Capturer<RgbImage>>,
+        system_control: Box<dyn SystemControl>,
+    ) -> Result<Self> {
+        let window_info = W::from_window_info_repository(
+            game_info.window.to_rect_usize().size(),
+            game_info.ui,
+            game_info.platform,
+            window_info_repo,
+        )?;
+
+        Ok(Self::from_window_info(window_info, config, game_info, capturer, system_control))
+    }
+
+    pub fn from_arg_matches(
+        window_info_repo: &WindowInfoRepository,
+        arg_matches: &ArgMatches,
+        game_info: GameInfo,
+        capturer: Rc<dyn Capturer<RgbImage>>,
+        system_control: Box<dyn SystemControl>,
+    ) -> Result<Self> {
+        Self::new(
+            window_info_repo,
+            GridScanControllerConfig::from_arg_matches(arg_matches)?,
+            game_info,
+            capturer,
+            system_control,
+        )
+    }
+}
+
+impl<W: GridWindowInfo> GridScanController<W> {
+    pub fn from_window_info(
+        window_info: W,
+        config: GridScanControllerConfig,
+        game_info: GameInfo,
+        capturer: Rc<dyn Capturer<RgbImage>>,
+        system_control: Box<dyn SystemControl>,
+    ) -> Self {
+        GridScanController {
+            row: window_info.row(),
+            col: window_info.col(),
+            change_detector: window_info.change_detector(),
+            row_flag: window_info.row_flag(),
+            row_scroll: window_info.row_scroll(),
+            window_info,
+
+            config,
+
+            pool: 0.0,
+
+            scrolled_rows: 0,
+            avg_scroll_one_row: 0.0,
+
+            avg_switch_time: 0.0,
+            scanned_count: 0,
+
+            game_info,
+
+            system_control,
+            capturer,
+            profiler: RefCell::new(Profiler::new()),
+        }
+    }
+
+    pub fn window_info(&self) -> &W {
+        &self.window_info
+    }
+
+    /// Get a generator, which stops once an item is switched and displayed
+    pub fn get_generator(object: Rc<RefCell<Self>>, item_count: usize) -> GridScanGenerator<W> {
+        GridScanGenerator::new(object, item_count, 0)
+    }
+
+    /// Get a generator which scrolls to where a previous scan stopped, and goes on from the next item
+    pub fn get_generator_from_progress(object: Rc<RefCell<Self>>, item_count: usize, progress: &GridScanProgress) -> Result<GridScanGenerator<W>> {
+        if progress.item_count != item_count {
+            return Err(anyhow!("物品数量 {} 与检查点记录的 {} 不一致，无法继续扫描", item_count, progress.item_count));
+        }
+
+        let col = object.borrow().col;
+        if progress.col >= col {
+            return Err(anyhow!("检查点记录的列 {} 超出每行物品数 {}", progress.col, col));
+        }
+        let start_index = progress.scanned_count(col);
+        if start_index > item_count {
+            return Err(anyhow!("检查点记录的位置超出物品数量 {}", item_count));
+        }
+
+        object.borrow_mut().avg_scroll_one_row = progress.avg_scroll_one_row;
+        object.borrow_mut().scrolled_rows = progress.scrolled_rows;
+
+        info!("从第 {} 行第 {} 列继续扫描", progress.row + 1, progress.col + 1);
+
+        Ok(GridScanGenerator::new(object, item_count, start_index))
+    }
+
+    fn window_origin(&self) -> Pos<f64> {
+        self.game_info.window.to_rect_f64().origin()
+    }
+
+    pub fn sample_initial_flag(&mut self) -> Result<()> {
+        let origin = self.window_origin();
+        self.row_flag.sample(self.capturer.as_ref(), origin)
+    }
+
+    pub fn is_aligned(&self) -> Result<bool> {
+        self.profiler.borrow_mut().begin("capture_flag");
+        let aligned = self.row_flag.is_aligned(self.capturer.as_ref(), self.window_origin());
+        self.profiler.borrow_mut().end("capture_flag")?;
+
+        aligned
+    }
+
+    pub fn align_row(&mut self) -> Result<()> {
+        for _ in 0..10 {
+            if self.is_aligned()? {
+                break;
+            }
+
+            self.system_control.mouse_scroll(1, false)?;
+            utils::sleep(self.config.scroll_delay.try_into()?);
+        }
+        Ok(())
+    }
+
+    /// Set cursor to the specified item
+    pub fn move_to(&mut self, row: usize, col: usize) -> Result<()> {
+        let origin = self.window_origin();
+        let pos = self.window_info.item_pos(row, col);
+
+        self.system_control.mouse_move_to((origin.x + pos.x) as i32, (origin.y + pos.y) as i32)?;
+
+        #[cfg(target_os = "macos")]
+        utils::sleep(20);
+
+        Ok(())
+    }
+
+    pub fn scroll_one_row(&mut self) -> Result<ScrollResult> {
+        match self.row_scroll {
+            RowScroll::Fixed { wheel } => {
+                for _ in 0..wheel {
+                    if utils::is_abort_requested() {
+                        return Ok(ScrollResult::Interrupt);
+                    }
+
+                    self.system_control.mouse_scroll(1, false)?;
+                    utils::sleep(self.config.scroll_delay.try_into()?);
+                }
+
+                Ok(ScrollResult::Success)
+            },
+            RowScroll::Detect { max_wheel, .. } => {
+                // 0: aligned, 1: the flag has changed
+                let mut state = 0;
+                let mut count = 0;
+
+                while count < max_wheel {
+                    if utils::is_abort_requested() {
+                        return Ok(ScrollResult::Interrupt);
+                    }
+
+                    self.system_control.mouse_scroll(1, false)?;
+                    utils::sleep(self.config.scroll_delay.try_into()?);
+                    count += 1;
+
+                    let aligned = match self.is_aligned() {
+                        Ok(aligned) => aligned,
+                        Err(_) => return Ok(ScrollResult::Failed),
+                    };
+
+                    if state == 0 && !aligned {
+                        state = 1;
+                    } else if state == 1 && aligned {
+                        self.update_avg_row(count);
+                        return Ok(ScrollResult::Success);
+                    }
+                }
+
+                Ok(ScrollResult::TimeLimitExceeded)
+            },
+        }
+    }
+
+    pub fn scroll_rows(&mut self, count: i32) -> Result<ScrollResult> {
+        if let RowScroll::Detect { estimate_margin, .. } = self.row_scroll {
+            if cfg!(not(target_os = "macos")) && self.scrolled_rows >= 5 {
+                let length = self.estimate_scroll_length(count, estimate_margin);
+
+                for _ in 0..length {
+                    self.system_control.mouse_scroll(1, false)?;
+                }
+
+                utils::sleep(self.config.scroll_delay.try_into()?);
+
+                self.align_row()?;
+                return Ok(ScrollResult::Skip);
+            }
+        }
+
+        for _ in 0..count {
+            match self.scroll_one_row()? {
+                ScrollResult::Success | ScrollResult::Skip => continue,
+                ScrollResult::Interrupt => return Ok(ScrollResult::Interrupt),
+                v => {
+                    error!("Scrolling failed: {:?}", v);
+                    return Ok(v);
+                },
+            }
+        }
+
+        Ok(ScrollResult::Success)
+    }
+
+    fn capture_pool(&self) -> Result<f64> {
+        self.profiler.borrow_mut().begin("capture_pool");
+        let im = self.capturer.capture_relative_to(
+            self.window_info.pool_rect().to_rect_i32(),
+            self.game_info.window.origin(),
+        )?;
+        self.profiler.borrow_mut().end("capture_pool")?;
+
+        Ok(self.change_detector.pool(&im))
+    }
+
+    /// Remember the pool of the displayed item, so that it isn't taken for the next one
+    pub fn sample_pool(&mut self) -> Result<()> {
+        self.pool = self.capture_pool()?;
+        Ok(())
+    }
+
+    /// Returns false if nothing changes in time, the scan goes on anyway
+    pub fn wait_until_switched(&mut self) -> Result<bool> {
+        if self.game_info.is_cloud {
+            utils::sleep(self.config.cloud_wait_switch_item.try_into()?);
+            return Ok(true);
+        }
+
+        self.profiler.borrow_mut().begin("wait_until_switched");
+
+        let now = SystemTime::now();
+        let mut diff_flag = false;
+        let mut is_last_blank = false;
+
+        while now.elapsed()?.as_millis() < self.config.max_wait_switch_item as u128 {
+            let pool = self.capture_pool()?;
+
+            if (pool - self.pool).abs() > 0.000001 {
+                self.pool = pool;
+                diff_flag = true;
+
+                if is_last_blank {
+                    self.profiler.borrow_mut().end("wait_until_switched")?;
+                    return Ok(true);
+                }
+            } else if diff_flag {
+                self.avg_switch_time = (self.avg_switch_time * self.scanned_count as f64
+                    + now.elapsed()?.as_millis() as f64)
+                    / (self.scanned_count as f64 + 1.0);
+                self.scanned_count += 1;
+
+                self.profiler.borrow_mut().end("wait_until_switched")?;
+                return Ok(true);
+            }
+
+            is_last_blank = self.change_detector.is_blank(pool);
+        }
+
+        self.profiler.borrow_mut().end("wait_until_switched")?;
+
+        Ok(false)
+    }
+
+    fn update_avg_row(&mut self, count: i32) {
+        let current = self.avg_scroll_one_row * self.scrolled_rows as f64 + count as f64;
+        self.scrolled_rows += 1;
+        self.avg_scroll_one_row = current / self.scrolled_rows as f64;
+
+        info!(
+            "avg scroll one row: {} ({})",
+            self.avg_scroll_one_row, self.scrolled_rows
+        );
+    }
+
+    fn estimate_scroll_length(&self, count: i32, margin: i32) -> i32 {
+        ((self.avg_scroll_one_row * count as f64 - margin as f64).round() as i32).max(0)
+    }
+}
+
+/// What a generator does when resumed
+pub enum ScanState {
+    /// An item is switched and displayed, the controller is not borrowed until the next resume
+    Yielded,
+    /// The scan is over, further resumes complete again without doing anything
+    Complete(Result<ReturnResult>),
+}
+
+/// The item a generator yielded
+#[derive(Clone, Copy, Debug)]
+pub struct GridItem {
+    /// Index in the inventory
+    pub index: usize,
+    /// Position in the page
+    pub row: usize,
+    pub col: usize,
+    /// Whether it's the first item scanned since the page was scrolled to
+    pub is_page_first: bool,
+}
+
+enum GeneratorState {
+    Start,
+    /// At an item of the page, which is clicked when resumed
+    Scanning,
+    /// The item at the position was yielded
+    Yielded,
+    Done,
+}
+
+/// Clicks the items one by one and scrolls page by page, stopping at every item so the caller can capture it.
+/// The state of the loops is kept here, so that it's driven by `resume` with no coroutine
+pub struct GridScanGenerator<W> {
+    controller: Rc<RefCell<GridScanController<W>>>,
+    state: GeneratorState,
+
+    item_count: usize,
+    total_row: usize,
+    /// Items of the last row
+    last_row_col: usize,
+    /// Rows shown by a page
+    page_row: usize,
+    /// Items before it were scanned by a previous scan
+    start_index: usize,
+
+    scanned_row: usize,
+    scanned_count: usize,
+
+    /// Position in the page of the current item
+    row: usize,
+    col: usize,
+    /// No item was yielded since the page was scrolled to
+    is_page_first: bool,
+}
+
+impl<W: GridWindowInfo> GridScanGenerator<W> {
+    pub fn new(controller: Rc<RefCell<GridScanController<W>>>, item_count: usize, start_index: usize) -> Self {
+        let col = controller.borrow().col;
+        let total_row = item_count.div_ceil(col);
+        let last_row_col = if item_count.is_multiple_of(col) {
+            col
+        } else {
+            item_count % col
+        };
+        let page_row = controller.borrow().row.min(total_row);
+
+        GridScanGenerator {
+            controller,
+            state: GeneratorState::Start,
+
+            item_count,
+            total_row,
+            last_row_col,
+            page_row,
+            start_index,
+
+            scanned_row: start_index / col,
+            scanned_count: start_index,
+
+            row: 0,
+            col: 0,
+            is_page_first: true,
+        }
+    }
+
+    pub fn resume(&mut self) -> ScanState {
+        if let GeneratorState::Done = self.state {
+            return ScanState::Complete(Ok(ReturnResult::Finished));
+        }
+
+        match self.step() {
+            Ok(None) => {
+                self.state = GeneratorState::Yielded;
+                ScanState::Yielded
+            },
+            Ok(Some(value)) => {
+                self.state = GeneratorState::Done;
+                ScanState::Complete(Ok(value))
+            },
+            Err(e) => {
+                self.state = GeneratorState::Done;
+                ScanState::Complete(Err(e))
+            },
+        }
+    }
+
+    /// The item yielded last
+    pub fn item(&self) -> GridItem {
+        GridItem {
+            index: self.scanned_count,
+            row: self.row,
+            col: self.col,
+            is_page_first: self.is_page_first,
+        }
+    }
+
+    /// Index of the first item to scan, items before it were scanned by the scan resumed from
+    pub fn start_index(&self) -> usize {
+        self.start_index
+    }
+
+    /// Where the scan is, a scan from this progress goes on with the item after the last yielded one
+    pub fn progress(&self) -> GridScanProgress {
+        let next = match self.state {
+            GeneratorState::Yielded => self.scanned_count + 1,
+            _ => self.scanned_count,
+        };
+        self.progress_at(next)
+    }
+
+    /// A scan from this progress starts with the yielded item again, e.g. when it could not be captured
+    pub fn progress_retrying_item(&self) -> GridScanProgress {
+        self.progress_at(self.scanned_count)
+    }
+
+    /// A scan from this progress starts with the item at `next` of the inventory, e.g. the first one whose recognition failed
+    pub fn progress_at(&self, next: usize) -> GridScanProgress {
+        let controller = self.controller.borrow();
+
+        GridScanProgress {
+            item_count: self.item_count,
+            row: next / controller.col,
+            col: next % controller.col,
+            avg_scroll_one_row: controller.avg_scroll_one_row,
+            scrolled_rows: controller.scrolled_rows,
+        }
+    }
+
+    /// Scroll the page to the row of `start_index`, as a scan from the top would
+    fn skip_scanned(&mut self, controller: &mut GridScanController<W>) -> Result<Option<ReturnResult>> {
+        let start_row = self.start_index / controller.col;
+        let mut scroll_row = start_row.min(self.total_row - self.page_row);
+
+        self.row = start_row - scroll_row;
+        self.col = self.start_index % controller.col;
+
+        // The first item is displayed, which would pass for a switch to the next one
+        controller.sample_pool()?;
+
+        while scroll_row > 0 {
+            let count = scroll_row.min(controller.row);
+            match controller.scroll_rows(count as i32)? {
+                ScrollResult::TimeLimitExceeded => {
+                    return Err(anyhow!("翻页超时，扫描终止……"));
+                },
+                ScrollResult::Interrupt => {
+                    return Ok(Some(ReturnResult::Interrupted));
+                },
+                _ => (),
+            }
+            scroll_row -= count;
+
+            utils::sleep(100);
+        }
+
+        Ok(None)
+    }
+
+    /// Go on until the next item is displayed, or returns the result if the scan is over
+    fn step(&mut self) -> Result<Option<ReturnResult>> {
+        let controller = self.controller.clone();
+        let mut controller = controller.borrow_mut();
+
+        match self.state {
+            GeneratorState::Start => {
+                info!(
+                    "扫描任务共 {} 个物品，共计 {} 行，尾行 {} 个",
+                    self.item_count, self.total_row, self.last_row_col
+                );
+                if self.start_index > 0 && self.start_index >= self.item_count {
+                    return Ok(Some(ReturnResult::Finished));
+                }
+
+                // Set cursor to the first item and sleep for a few time
+                controller.move_to(0, 0)?;
+                controller.system_control.mouse_click()?;
+                utils::sleep(1000);
+
+                // Sample initial flag, for scroll determination
+                controller.sample_initial_flag()?;
+
+                self.state = GeneratorState::Scanning;
+
+                if self.start_index > 0 {
+                    if let Some(result) = self.skip_scanned(&mut controller)? {
+                        return Ok(Some(result));
+                    }
+                }
+            },
+            GeneratorState::Yielded => {
+                self.scanned_count += 1;
+                controller.scanned_count = self.scanned_count;
+                self.col += 1;
+                self.is_page_first = false;
+            },
+            _ => (),
+        }
+        loop {
+            if self.row < self.page_row {
+                // Determine how many items this row have
+                let row_item_count = if self.scanned_row == self.total_row - 1 {
+                    self.last_row_col
+                } else {
+                    controller.col
+                };
+
+                if self.col < row_item_count {
+                    // Exit if right mouse button is down, or if we've scanned more than the maximum count
+                    if utils::is_abort_requested() {
+                        return Ok(Some(ReturnResult::Interrupted));
+                    }
+                    if self.scanned_count > self.item_count {
+                        return Ok(Some(ReturnResult::Finished));
+                    }
+
+                    controller.move_to(self.row, self.col)?;
+                    controller.system_control.mouse_click()?;
+
+                    #[cfg(target_os = "macos")]
+                    utils::sleep(20);
+
+                    controller.wait_until_switched()?;
+
+                    return Ok(None);
+                }
+
+                self.scanned_row += 1;
+                self.row += 1;
+                self.col = 0;
+
+                if let Some(max_row) = controller.config.max_row {
+                    if self.scanned_row >= max_row {
+                        info!("到达最大行数，准备退出……");
+                        return Ok(Some(ReturnResult::Finished));
+                    }
+                }
+
+                continue;
+            }
+
+            // The page is done
+            if self.scanned_count >= self.item_count {
+                return Ok(Some(ReturnResult::Finished));
+            }
+
+            let remain = self.item_count - self.scanned_count;
+            let remain_row = remain.div_ceil(controller.col);
+            let scroll_row = remain_row.min(controller.row);
+            self.row = controller.row - scroll_row;
+
+            match controller.scroll_rows(scroll_row as i32)? {
+                ScrollResult::TimeLimitExceeded => {
+                    return Err(anyhow!("翻页超时，扫描终止……"));
+                },
+                ScrollResult::Interrupt => {
+                    return Ok(Some(ReturnResult::Interrupted));
+                },
+                _ => (),
+            }
+            self.is_page_first = true;
+
+            utils::sleep(100);
+        }
+    }
+}
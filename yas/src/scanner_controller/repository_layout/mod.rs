@@ -0,0 +1,17 @@
+pub use change_detector::{ChangeDetector, RedChannelSum, WhitePixelCount};
+pub use checkpoint::{GridScanProgress, ScanCheckpoint};
+pub use checkpoint_config::ScanCheckpointConfig;
+pub use config::GridScanControllerConfig;
+pub use controller::{GridItem, GridScanController, GridScanGenerator, ReturnResult, ScanState};
+pub use row_flag::{ColumnFlag, PixelFlag, RowFlag, RowScroll};
+pub use scroll_result::ScrollResult;
+pub use window_info::GridWindowInfo;
+
+mod change_detector;
+mod checkpoint;
+mod checkpoint_config;
+mod config;
+mod controller;
+mod row_flag;
+mod scroll_result;
+mod window_info;
@@ -0,0 +1,111 @@
+use anyhow::Result;
+use image::{Rgb, RgbImage};
+
+use crate::capture::Capturer;
+use crate::positioning::{Pos, Rect};
+use crate::utils::color_distance;
+
+/// Detects whether the rows are aligned, from pixels which show the gap between rows only when they are
+pub trait RowFlag {
+    /// Remember the flag of the aligned rows, which later captures are compared with
+    fn sample(&mut self, capturer: &dyn Capturer<RgbImage>, origin: Pos<f64>) -> Result<()>;
+
+    /// Whether the rows are aligned as when sampled
+    fn is_aligned(&self, capturer: &dyn Capturer<RgbImage>, origin: Pos<f64>) -> Result<bool>;
+}
+
+/// A single pixel, aligned if its color is within `threshold` (squared distance) of the sampled one
+pub struct PixelFlag {
+    pos: Pos<f64>,
+    threshold: usize,
+    initial: Rgb<u8>,
+}
+
+impl PixelFlag {
+    pub fn new(pos: Pos<f64>, threshold: usize) -> PixelFlag {
+        PixelFlag {
+            pos,
+            threshold,
+            initial: Rgb([0, 0, 0]),
+        }
+    }
+
+    fn capture(&self, capturer: &dyn Capturer<RgbImage>, origin: Pos<f64>) -> Result<Rgb<u8>> {
+        capturer.capture_color(Pos {
+            x: (origin.x + self.pos.x) as i32,
+            y: (origin.y + self.pos.y) as i32,
+        })
+    }
+}
+
+impl RowFlag for PixelFlag {
+    fn sample(&mut self, capturer: &dyn Capturer<RgbImage>, origin: Pos<f64>) -> Result<()> {
+        self.initial = self.capture(capturer, origin)?;
+        Ok(())
+    }
+
+    fn is_aligned(&self, capturer: &dyn Capturer<RgbImage>, origin: Pos<f64>) -> Result<bool> {
+        let color = self.capture(capturer, origin)?;
+        Ok(color_distance(&self.initial, &color) < self.threshold)
+    }
+}
+
+/// A column of pixels, aligned if any of them is within `threshold` of the sampled one.
+/// Used where the gap between rows is thin, or moves a little between pages
+pub struct ColumnFlag {
+    rect: Rect<f64>,
+    threshold: usize,
+    initial: Vec<Rgb<u8>>,
+}
+
+impl ColumnFlag {
+    pub fn new(rect: Rect<f64>, threshold: usize) -> ColumnFlag {
+        ColumnFlag {
+            rect,
+            threshold,
+            initial: Vec::new(),
+        }
+    }
+
+    fn capture(&self, capturer: &dyn Capturer<RgbImage>, origin: Pos<f64>) -> Result<Vec<Rgb<u8>>> {
+        let im = capturer.capture_rect(self.rect.translate(origin).to_rect_i32())?;
+        Ok((0..im.height()).map(|y| *im.get_pixel(0, y)).collect())
+    }
+}
+
+impl RowFlag for ColumnFlag {
+    fn sample(&mut self, capturer: &dyn Capturer<RgbImage>, origin: Pos<f64>) -> Result<()> {
+        self.initial = self.capture(capturer, origin)?;
+        Ok(())
+    }
+
+    fn is_aligned(&self, capturer: &dyn Capturer<RgbImage>, origin: Pos<f64>) -> Result<bool> {
+        let flag = self.capture(capturer, origin)?;
+        Ok(self.initial.iter().zip(flag.iter()).any(|(a, b)| color_distance(a, b) < self.threshold))
+    }
+}
+
+/// How the controller scrolls a row
+#[derive(Clone, Copy, Debug)]
+pub enum RowScroll {
+    /// Scroll until the flag changes and is aligned again, which learns how many wheel events a row takes.
+    /// Once 5 rows are learned, pages are scrolled by the estimate, stopping `estimate_margin` wheel events short,
+    /// and then aligned by the flag
+    Detect {
+        max_wheel: i32,
+        estimate_margin: i32,
+    },
+    /// Scroll a fixed amount of wheel events, without looking at the flag
+    Fixed {
+        wheel: i32,
+    },
+}
+
+impl Default for RowScroll {
+    fn default() -> Self {
+        RowScroll::Detect {
+            max_wheel: 25,
+            estimate_margin: 3,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use crate::positioning::{Pos, Rect, Size};
+use crate::scanner_controller::repository_layout::{ChangeDetector, RedChannelSum, RowFlag, RowScroll};
+
+/// Layout of a grid of items, implemented by the window info of the repository of a game.
+/// Besides the geometry, it decides how switches and aligned rows are detected in the game
+pub trait GridWindowInfo {
+    /// Top-left corner of the first item
+    fn scan_margin_pos(&self) -> Pos<f64>;
+    fn item_size(&self) -> Size<f64>;
+    fn item_gap_size(&self) -> Size<f64>;
+    /// Rows and cols of a page
+    fn row(&self) -> usize;
+    fn col(&self) -> usize;
+    /// A region of the panel of the selected item, captured to detect switches
+    fn pool_rect(&self) -> Rect<f64>;
+
+    /// Where an item is clicked, the centre of the item by default
+    fn item_pos(&self, row: usize, col: usize) -> Pos<f64> {
+        let margin = self.scan_margin_pos();
+        let gap = self.item_gap_size();
+        let size = self.item_size();
+
+        Pos {
+            x: margin.x + (gap.width + size.width) * (col as f64) + size.width / 2.0,
+            y: margin.y + (gap.height + size.height) * (row as f64) + size.height / 2.0,
+        }
+    }
+
+    fn change_detector(&self) -> Box<dyn ChangeDetector> {
+        Box::new(RedChannelSum)
+    }
+
+    fn row_flag(&self) -> Box<dyn RowFlag>;
+
+    fn row_scroll(&self) -> RowScroll {
+        RowScroll::default()
+    }
+}
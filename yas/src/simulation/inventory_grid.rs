@@ -0,0 +1,253 @@
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use anyhow::Result;
+use image::{Rgb, RgbImage};
+
+use crate::capture::Capturer;
+use crate::positioning::{Pos, Rect, Size};
+use crate::system_control::SystemControl;
+
+/// Color of the gaps between items, and of everything else which is not an item or the panel
+pub const GAP_COLOR: Rgb<u8> = Rgb([40, 40, 40]);
+pub const ITEM_COLOR: Rgb<u8> = Rgb([190, 170, 140]);
+const PANEL_COLOR: Rgb<u8> = Rgb([20, 20, 20]);
+const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+
+/// Geometry of an inventory page, in the coordinates of the window
+#[derive(Clone, Debug)]
+pub struct InventoryLayout {
+    /// Top-left corner of the first item
+    pub margin: Pos<f64>,
+    pub item_size: Size<f64>,
+    pub gap: Size<f64>,
+    /// Rows and columns of a page
+    pub row: usize,
+    pub col: usize,
+    /// Where the selected item is displayed
+    pub panel_rect: Rect<f64>,
+    /// Pixels scrolled by one wheel event
+    pub scroll_per_wheel: f64,
+}
+
+impl InventoryLayout {
+    fn pitch(&self) -> Size<f64> {
+        Size::new(self.item_size.width + self.gap.width, self.item_size.height + self.gap.height)
+    }
+}
+
+struct InventoryState {
+    layout: InventoryLayout,
+    item_count: usize,
+    /// Position of the window on the screen
+    origin: Pos<i32>,
+
+    cursor: Pos<i32>,
+    /// Pixels scrolled from the first row
+    scroll: f64,
+    selected: Option<usize>,
+
+    /// A clicked item is displayed after this many captures
+    switch_delay: usize,
+    pending: Option<(usize, usize)>,
+
+    wheel_events: usize,
+
+    /// Where the window is saved after every input, and its size
+    recording: Option<(PathBuf, Size<u32>)>,
+    recorded_frames: usize,
+}
+
+impl InventoryState {
+    fn max_scroll(&self) -> f64 {
+        let total_row = self.item_count.div_ceil(self.layout.col);
+        total_row.saturating_sub(self.layout.row) as f64 * self.layout.pitch().height
+    }
+
+    /// The item at a position of the window, the page is clipped at the bottom but not at the top,
+    /// so that a flag above the first row sees the items scrolling by
+    fn item_at(&self, x: f64, y: f64) -> Option<usize> {
+        let layout = &self.layout;
+        let pitch = layout.pitch();
+        if y >= layout.margin.y + pitch.height * layout.row as f64 {
+            return None;
+        }
+
+        let content_x = x - layout.margin.x;
+        let content_y = y - layout.margin.y + self.scroll;
+        if content_x < 0.0 || content_y < 0.0 {
+            return None;
+        }
+
+        let col = (content_x / pitch.width).floor();
+        let row = (content_y / pitch.height).floor();
+        if content_x - col * pitch.width >= layout.item_size.width || content_y - row * pitch.height >= layout.item_size.height {
+            return None;
+        }
+
+        let (row, col) = (row as usize, col as usize);
+        let index = row * layout.col + col;
+        (col < layout.col && index < self.item_count).then_some(index)
+    }
+
+    /// Each item shows a different amount of white pixels in the panel
+    fn panel_pixel(&self, x: i32, y: i32) -> Rgb<u8> {
+        match self.selected {
+            Some(index) if (x + y) as usize % 16 <= index % 15 => WHITE,
+            _ => PANEL_COLOR,
+        }
+    }
+
+    fn pixel(&self, x: i32, y: i32) -> Rgb<u8> {
+        let panel = self.layout.panel_rect.to_rect_i32();
+        let (x, y) = (x - self.origin.x, y - self.origin.y);
+
+        if x >= panel.left && x < panel.left + panel.width && y >= panel.top && y < panel.top + panel.height {
+            self.panel_pixel(x - panel.left, y - panel.top)
+        } else if self.item_at(x as f64, y as f64).is_some() {
+            ITEM_COLOR
+        } else {
+            GAP_COLOR
+        }
+    }
+
+    fn render(&self, rect: Rect<i32>) -> RgbImage {
+        RgbImage::from_fn(rect.width as u32, rect.height as u32, |x, y| {
+            self.pixel(rect.left + x as i32, rect.top + y as i32)
+        })
+    }
+
+    /// Save the window as the next frame of the recording, if recording
+    fn record_frame(&mut self) -> Result<()> {
+        if let Some((dir, size)) = &self.recording {
+            let window = Rect::new(self.origin.x, self.origin.y, size.width as i32, size.height as i32);
+            let path = dir.join(format!("{:05}.png", self.recorded_frames));
+            self.render(window).save(path)?;
+            self.recorded_frames += 1;
+        }
+        Ok(())
+    }
+
+    fn next_frame(&mut self) {
+        if let Some((index, remaining)) = self.pending {
+            if remaining == 0 {
+                self.selected = Some(index);
+                self.pending = None;
+            } else {
+                self.pending = Some((index, remaining - 1));
+            }
+        }
+    }
+}
+
+/// A fake game showing a scrollable grid of items, along with a panel which displays the selected one.
+/// It's both the screen and the mouse, so a repository controller can be driven without the game.
+/// Clones share the same game
+#[derive(Clone)]
+pub struct SimulatedInventory {
+    state: Rc<RefCell<InventoryState>>,
+}
+
+impl SimulatedInventory {
+    /// The first item is selected, as in the game when the inventory is opened
+    pub fn new(layout: InventoryLayout, item_count: usize, origin: Pos<i32>) -> SimulatedInventory {
+        let state = InventoryState {
+            layout,
+            item_count,
+            origin,
+            cursor: Pos::new(0, 0),
+            scroll: 0.0,
+            selected: (item_count > 0).then_some(0),
+            switch_delay: 0,
+            pending: None,
+            wheel_events: 0,
+            recording: None,
+            recorded_frames: 0,
+        };
+
+        SimulatedInventory {
+            state: Rc::new(RefCell::new(state)),
+        }
+    }
+
+    /// Display a clicked item only after `captures` captures, like the animation of the game
+    pub fn set_switch_delay(&self, captures: usize) {
+        self.state.borrow_mut().switch_delay = captures;
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.state.borrow().selected
+    }
+
+    pub fn scroll(&self) -> f64 {
+        self.state.borrow().scroll
+    }
+
+    pub fn wheel_events(&self) -> usize {
+        self.state.borrow().wheel_events
+    }
+
+    /// Frames saved since `record`
+    pub fn recorded_frames(&self) -> usize {
+        self.state.borrow().recorded_frames
+    }
+
+    /// Save the window of `window_size` into `dir` now and after every click and wheel event, as `ReplayCapturer` replays it.
+    /// The frame of a click shows the item it selects, so record with no switch delay
+    pub fn record<P: AsRef<Path>>(&self, dir: P, window_size: Size<u32>) -> Result<()> {
+        let mut state = self.state.borrow_mut();
+        state.recording = Some((dir.as_ref().to_path_buf(), window_size));
+        state.record_frame()
+    }
+
+    pub fn capturer(&self) -> Rc<dyn Capturer<RgbImage>> {
+        Rc::new(self.clone())
+    }
+
+    pub fn system_control(&self) -> Box<dyn SystemControl> {
+        Box::new(self.clone())
+    }
+}
+
+impl Capturer<RgbImage> for SimulatedInventory {
+    fn capture_rect(&self, rect: Rect<i32>) -> Result<RgbImage> {
+        let mut state = self.state.borrow_mut();
+        state.next_frame();
+
+        Ok(state.render(rect))
+    }
+}
+
+impl SystemControl for SimulatedInventory {
+    fn mouse_move_to(&mut self, x: i32, y: i32) -> Result<()> {
+        self.state.borrow_mut().cursor = Pos::new(x, y);
+        Ok(())
+    }
+
+    fn mouse_click(&mut self) -> Result<()> {
+        let mut state = self.state.borrow_mut();
+        let x = (state.cursor.x - state.origin.x) as f64;
+        let y = (state.cursor.y - state.origin.y) as f64;
+
+        if let Some(index) = state.item_at(x, y) {
+            if state.switch_delay == 0 {
+                state.selected = Some(index);
+            } else {
+                state.pending = Some((index, state.switch_delay));
+            }
+        }
+        state.record_frame()
+    }
+
+    fn mouse_scroll(&mut self, amount: i32, _try_find: bool) -> Result<()> {
+        let mut state = self.state.borrow_mut();
+        let scroll = state.scroll + amount as f64 * state.layout.scroll_per_wheel;
+        state.scroll = scroll.clamp(0.0, state.max_scroll());
+        state.wheel_events += amount.unsigned_abs() as usize;
+        for _ in 0..amount.abs() {
+            state.record_frame()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,5 @@
+pub use inventory_grid::{InventoryLayout, SimulatedInventory, GAP_COLOR, ITEM_COLOR};
+pub use scan_harness::{all_items, run_generator};
+
+mod inventory_grid;
+mod scan_harness;
@@ -0,0 +1,66 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use anyhow::Result;
+
+use crate::game_info::GameInfo;
+use crate::scanner_controller::repository_layout::{GridScanController, GridScanControllerConfig, GridScanGenerator, GridWindowInfo, ReturnResult, ScanState};
+use crate::simulation::{InventoryLayout, SimulatedInventory};
+
+impl InventoryLayout {
+    /// The layout a grid controller of `window_info` expects, the panel is where the controller detects switches
+    pub fn from_window_info<W: GridWindowInfo>(window_info: &W, scroll_per_wheel: f64) -> InventoryLayout {
+        InventoryLayout {
+            margin: window_info.scan_margin_pos(),
+            item_size: window_info.item_size(),
+            gap: window_info.item_gap_size(),
+            row: window_info.row(),
+            col: window_info.col(),
+            panel_rect: window_info.pool_rect(),
+            scroll_per_wheel,
+        }
+    }
+}
+
+impl SimulatedInventory {
+    /// An inventory of the window of the game, laid out for a grid controller of `window_info`
+    pub fn from_window_info<W: GridWindowInfo>(window_info: &W, game_info: &GameInfo, item_count: usize, scroll_per_wheel: f64) -> SimulatedInventory {
+        let layout = InventoryLayout::from_window_info(window_info, scroll_per_wheel);
+        SimulatedInventory::new(layout, item_count, game_info.window.origin())
+    }
+
+    /// A grid controller which sees and clicks this inventory
+    pub fn controller<W: GridWindowInfo>(&self, window_info: W, config: GridScanControllerConfig, game_info: GameInfo) -> Rc<RefCell<GridScanController<W>>> {
+        let controller = GridScanController::from_window_info(window_info, config, game_info, self.capturer(), self.system_control());
+        Rc::new(RefCell::new(controller))
+    }
+
+    /// Scan the whole inventory with the controller, returns the item selected at each yield
+    pub fn scan<W: GridWindowInfo>(&self, controller: Rc<RefCell<GridScanController<W>>>, item_count: usize) -> (Vec<Option<usize>>, Result<ReturnResult>) {
+        let mut generator = GridScanController::get_generator(controller, item_count);
+        let (selected, result) = run_generator(&mut generator, usize::MAX, || self.selected());
+        (selected, result.unwrap())
+    }
+}
+
+/// Run the generator until it completes or `limit` items are yielded.
+/// Returns what `selected` tells at each yield, and the result if it completes
+pub fn run_generator<W: GridWindowInfo>(
+    generator: &mut GridScanGenerator<W>,
+    limit: usize,
+    mut selected: impl FnMut() -> Option<usize>,
+) -> (Vec<Option<usize>>, Option<Result<ReturnResult>>) {
+    let mut items = Vec::new();
+    while items.len() < limit {
+        match generator.resume() {
+            ScanState::Yielded => items.push(selected()),
+            ScanState::Complete(result) => return (items, Some(result)),
+        }
+    }
+    (items, None)
+}
+
+/// What a scan of `item_count` items selects when it goes right
+pub fn all_items(item_count: usize) -> Vec<Option<usize>> {
+    (0..item_count).map(Some).collect()
+}
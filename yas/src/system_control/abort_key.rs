@@ -0,0 +1,49 @@
+use std::sync::Mutex;
+
+use clap::ValueEnum;
+use log::{info, warn};
+
+/// Keys which can interrupt a scan, besides the right mouse button
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, ValueEnum, strum_macros::Display)]
+pub enum AbortKey {
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    Escape,
+    Pause,
+    ScrollLock,
+    Insert,
+    Delete,
+    Home,
+    End,
+}
+
+static ABORT_KEY: Mutex<Option<AbortKey>> = Mutex::new(None);
+
+pub fn set_abort_key(key: Option<AbortKey>) {
+    *ABORT_KEY.lock().unwrap() = key;
+}
+
+pub fn abort_key() -> Option<AbortKey> {
+    *ABORT_KEY.lock().unwrap()
+}
+
+#[derive(Clone, clap::Args, Default)]
+pub struct AbortKeyConfig {
+    /// A key which interrupts the scan like the right mouse button
+    #[arg(id = "abort-key", long = "abort-key", help = "中断扫描的按键（鼠标右键总是可以中断扫描）", value_name = "KEY")]
+    #[arg(value_enum)]
+    pub abort_key: Option<AbortKey>,
+}
+
+impl AbortKeyConfig {
+    pub fn apply(&self) {
+        set_abort_key(self.abort_key);
+
+        if let Some(key) = self.abort_key {
+            if cfg!(target_os = "macos") {
+                warn!("macOS 暂不支持中断扫描的按键，请使用鼠标右键中断扫描");
+            } else {
+                info!("按下 {} 或鼠标右键中断扫描", key);
+            }
+        }
+    }
+}
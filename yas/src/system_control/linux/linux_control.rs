@@ -1,31 +1,45 @@
-use enigo::{Enigo, MouseButton, MouseControllable};
+use anyhow::Result;
+
+use crate::system_control::linux::x11_input::{X11Input, BUTTON_LEFT, BUTTON_WHEEL_DOWN, BUTTON_WHEEL_UP};
+use crate::system_control::SystemControl;
 
 pub struct LinuxControl {
-    enigo: Enigo,
+    // connected on the first input, so that a missing X server is reported as an error of the input
+    input: Option<X11Input>,
 }
 
 impl LinuxControl {
     pub fn new() -> LinuxControl {
         LinuxControl {
-            enigo: Enigo::new(),
+            input: None,
         }
     }
 
-    pub fn mouse_move_to(&mut self, x: i32, y: i32) -> anyhow::Result<()> {
-        self.enigo.mouse_move_to(x, y);
-
-        anyhow::Ok(())
+    fn input(&mut self) -> Result<&X11Input> {
+        if self.input.is_none() {
+            self.input = Some(X11Input::new()?);
+        }
+        Ok(self.input.as_ref().unwrap())
     }
+}
 
-    pub fn mouse_click(&mut self) -> anyhow::Result<()> {
-        self.enigo.mouse_click(MouseButton::Left);
+impl SystemControl for LinuxControl {
+    fn mouse_move_to(&mut self, x: i32, y: i32) -> Result<()> {
+        self.input()?.move_to(x, y)
+    }
 
-        anyhow::Ok(())
+    fn mouse_click(&mut self) -> Result<()> {
+        self.input()?.click(BUTTON_LEFT)
     }
 
-    pub fn mouse_scroll(&mut self, amount: i32, _try_find: bool) -> anyhow::Result<()> {
-        self.enigo.mouse_scroll_y(amount);
+    fn mouse_scroll(&mut self, amount: i32, _try_find: bool) -> Result<()> {
+        let button = if amount < 0 { BUTTON_WHEEL_UP } else { BUTTON_WHEEL_DOWN };
+
+        let input = self.input()?;
+        for _ in 0..amount.abs() {
+            input.click(button)?;
+        }
 
-        anyhow::Ok(())
+        Ok(())
     }
 }
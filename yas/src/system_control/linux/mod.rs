@@ -1 +1,2 @@
 pub mod linux_control;
+pub mod x11_input;
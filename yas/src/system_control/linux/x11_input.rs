@@ -0,0 +1,107 @@
+use anyhow::{anyhow, bail, Result};
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::xproto::{ConnectionExt as _, KeyButMask, Keycode, Keysym, Window, BUTTON_PRESS_EVENT, BUTTON_RELEASE_EVENT, MOTION_NOTIFY_EVENT};
+use x11rb::protocol::xtest::{self, ConnectionExt as _};
+use x11rb::rust_connection::RustConnection;
+
+pub const BUTTON_LEFT: u8 = 1;
+pub const BUTTON_RIGHT: u8 = 3;
+pub const BUTTON_WHEEL_UP: u8 = 4;
+pub const BUTTON_WHEEL_DOWN: u8 = 5;
+
+/// Mouse and keyboard of an X11 display, which also works for XWayland and Wine.
+/// Input is synthesized with the XTest extension, the state of the buttons and keys is queried from the server
+pub struct X11Input {
+    conn: RustConnection,
+    root: Window,
+    has_xtest: bool,
+}
+
+impl X11Input {
+    pub fn new() -> Result<X11Input> {
+        let (conn, screen_num) = x11rb::connect(None)
+            .map_err(|e| anyhow!("无法连接 X 服务器：{}", e))?;
+        let root = conn.setup().roots[screen_num].root;
+        let has_xtest = conn.extension_information(xtest::X11_EXTENSION_NAME)?.is_some();
+
+        Ok(X11Input {
+            conn,
+            root,
+            has_xtest,
+        })
+    }
+
+    /// Each event is checked, so that an error of the server is reported by the call which caused it
+    fn fake_input(&self, type_: u8, detail: u8, x: i16, y: i16) -> Result<()> {
+        if !self.has_xtest {
+            bail!("X 服务器不支持 XTEST 扩展，无法模拟鼠标输入");
+        }
+
+        self.conn.xtest_fake_input(type_, detail, x11rb::CURRENT_TIME, self.root, x, y, 0)?
+            .check()
+            .map_err(|e| anyhow!("模拟输入失败：{}", e))?;
+        Ok(())
+    }
+
+    /// Move the pointer to `(x, y)` of the root window
+    pub fn move_to(&self, x: i32, y: i32) -> Result<()> {
+        let (root_x, root_y) = match (i16::try_from(x), i16::try_from(y)) {
+            (Ok(x), Ok(y)) => (x, y),
+            _ => bail!("坐标 ({}, {}) 超出 X 服务器的范围", x, y),
+        };
+        self.fake_input(MOTION_NOTIFY_EVENT, 0, root_x, root_y)
+    }
+
+    pub fn press(&self, button: u8) -> Result<()> {
+        self.fake_input(BUTTON_PRESS_EVENT, button, 0, 0)
+    }
+
+    pub fn release(&self, button: u8) -> Result<()> {
+        self.fake_input(BUTTON_RELEASE_EVENT, button, 0, 0)
+    }
+
+    pub fn click(&self, button: u8) -> Result<()> {
+        self.press(button)?;
+        self.release(button)
+    }
+
+    /// The position of the pointer in the root window
+    pub fn pointer_position(&self) -> Result<(i32, i32)> {
+        let reply = self.conn.query_pointer(self.root)?.reply()?;
+        Ok((reply.root_x as i32, reply.root_y as i32))
+    }
+
+    /// Only buttons 1 to 5 are tracked by the core protocol
+    pub fn is_button_down(&self, button: u8) -> Result<bool> {
+        let mask = match button {
+            1 => KeyButMask::BUTTON1,
+            2 => KeyButMask::BUTTON2,
+            3 => KeyButMask::BUTTON3,
+            4 => KeyButMask::BUTTON4,
+            5 => KeyButMask::BUTTON5,
+            _ => bail!("不支持的鼠标按键 {}", button),
+        };
+
+        let reply = self.conn.query_pointer(self.root)?.reply()?;
+        Ok(reply.mask.contains(mask))
+    }
+
+    /// The keycodes which produce the keysym in the current keyboard mapping
+    pub fn keycodes(&self, keysym: Keysym) -> Result<Vec<Keycode>> {
+        let setup = self.conn.setup();
+        let (min, max) = (setup.min_keycode, setup.max_keycode);
+        let reply = self.conn.get_keyboard_mapping(min, max - min + 1)?.reply()?;
+
+        let per_keycode = (reply.keysyms_per_keycode as usize).max(1);
+        Ok(reply.keysyms.chunks(per_keycode)
+            .enumerate()
+            .filter(|(_, keysyms)| keysyms.contains(&keysym))
+            .map(|(i, _)| min + i as Keycode)
+            .collect())
+    }
+
+    pub fn is_any_key_down(&self, keycodes: &[Keycode]) -> Result<bool> {
+        let keys = self.conn.query_keymap()?.reply()?.keys;
+        Ok(keycodes.iter().any(|&k| keys[k as usize / 8] & (1 << (k % 8)) != 0))
+    }
+}
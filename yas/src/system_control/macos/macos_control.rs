@@ -1,4 +1,4 @@
-use enigo::{Enigo, MouseControllable};
+use enigo::{Enigo, MouseButton, MouseControllable};
 
 use crate::system_control::system_control::SystemControl;
 use crate::utils;
@@ -14,24 +14,6 @@ impl MacOSControl {
         }
     }
 
-    pub fn mouse_move_to(&mut self, x: i32, y: i32) -> anyhow::Result<()> {
-        self.enigo.mouse_move_to(x, y);
-
-        anyhow::Ok(())
-    }
-
-    pub fn mouse_click(&mut self) -> anyhow::Result<()> {
-        self.enigo.mouse_click(MouseButton::Left);
-
-        anyhow::Ok(())
-    }
-
-    pub fn mouse_scroll(&mut self, amount: i32) -> anyhow::Result<()> {
-        self.enigo.mouse_scroll_y(-amount);
-
-        anyhow::Ok(())
-    }
-
     pub fn mac_scroll(&mut self, length: i32, delta: i32, times: i32) {
         let enigo = &mut self.enigo;
 
@@ -55,11 +37,32 @@ impl MacOSControl {
         }
     }
     
-    pub fn mac_scroll_fast(length: i32) {
-        mac_scroll(length, 4, 30);
+    pub fn mac_scroll_fast(&mut self, length: i32) {
+        self.mac_scroll(length, 4, 30);
     }
     
-    pub fn mac_scroll_slow(length: i32) {
-        mac_scroll(length, 4, 5);
+    pub fn mac_scroll_slow(&mut self, length: i32) {
+        self.mac_scroll(length, 4, 5);
+    }
+}
+
+impl SystemControl for MacOSControl {
+    fn mouse_move_to(&mut self, x: i32, y: i32) -> anyhow::Result<()> {
+        self.enigo.mouse_move_to(x, y);
+
+        anyhow::Ok(())
+    }
+
+    fn mouse_click(&mut self) -> anyhow::Result<()> {
+        self.enigo.mouse_click(MouseButton::Left);
+
+        anyhow::Ok(())
+    }
+
+    fn mouse_scroll(&mut self, amount: i32, _try_find: bool) -> anyhow::Result<()> {
+        self.enigo.mouse_scroll_y(-amount);
+        utils::sleep(20);
+
+        anyhow::Ok(())
     }
-}
\ No newline at end of file
+}
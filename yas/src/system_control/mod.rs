@@ -4,10 +4,17 @@ pub mod windows;
 pub mod macos;
 #[cfg(target_os = "linux")]
 pub mod linux;
+mod abort_key;
+mod replay_control;
+mod system_control;
+
+pub use abort_key::{abort_key, set_abort_key, AbortKey, AbortKeyConfig};
+pub use replay_control::ReplayControl;
+pub use system_control::SystemControl;
 
 #[cfg(target_os = "windows")]
-pub use windows::windows_control::WindowsSystemControl as SystemControl;
+pub use windows::windows_control::WindowsSystemControl as GenericSystemControl;
 #[cfg(target_os = "macos")]
-pub use macos::macos_control::MacOSControl as SystemControl;
+pub use macos::macos_control::MacOSControl as GenericSystemControl;
 #[cfg(target_os = "linux")]
-pub use linux::linux_control::LinuxControl as SystemControl;
+pub use linux::linux_control::LinuxControl as GenericSystemControl;
@@ -0,0 +1,53 @@
+use std::rc::Rc;
+
+use anyhow::{anyhow, Result};
+
+use crate::capture::ReplayCapturer;
+use crate::system_control::{GenericSystemControl, SystemControl};
+
+/// Input of a replayed scan, nothing is sent to the screen.
+/// Every click and every wheel event moves the replay to the next frame, as the recording holds the window after each input
+pub struct ReplayControl {
+    replay: Rc<ReplayCapturer>,
+}
+
+impl ReplayControl {
+    pub fn new(replay: Rc<ReplayCapturer>) -> ReplayControl {
+        ReplayControl {
+            replay,
+        }
+    }
+
+    /// The system control of a scan, a replayed scan goes through the recording instead of sending input to the game
+    pub fn or_generic(replay: &Option<Rc<ReplayCapturer>>) -> Box<dyn SystemControl> {
+        match replay {
+            Some(replay) => Box::new(ReplayControl::new(replay.clone())),
+            None => Box::new(GenericSystemControl::new()),
+        }
+    }
+
+    fn advance(&self) -> Result<()> {
+        if self.replay.advance() {
+            Ok(())
+        } else {
+            Err(anyhow!("回放截图已用完，共 {} 帧", self.replay.frame_count()))
+        }
+    }
+}
+
+impl SystemControl for ReplayControl {
+    fn mouse_move_to(&mut self, _x: i32, _y: i32) -> Result<()> {
+        Ok(())
+    }
+
+    fn mouse_click(&mut self) -> Result<()> {
+        self.advance()
+    }
+
+    fn mouse_scroll(&mut self, amount: i32, _try_find: bool) -> Result<()> {
+        for _ in 0..amount.abs() {
+            self.advance()?;
+        }
+        Ok(())
+    }
+}
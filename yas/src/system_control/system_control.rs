@@ -0,0 +1,14 @@
+use anyhow::Result;
+
+/// Mouse input which drives the game, implemented for each OS by `GenericSystemControl`
+pub trait SystemControl {
+    /// Move the cursor to `(x, y)` of the screen
+    fn mouse_move_to(&mut self, x: i32, y: i32) -> Result<()>;
+
+    /// Click the left button at the current position
+    fn mouse_click(&mut self) -> Result<()>;
+
+    /// Positive amounts scroll down, one wheel event each.
+    /// `try_find` is a hint that the scroll is searching for a row, which may be done with a faster gesture
+    fn mouse_scroll(&mut self, amount: i32, try_find: bool) -> Result<()>;
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use log::warn;
+use once_cell::sync::Lazy;
+use x11rb::protocol::xproto::{Keycode, Keysym};
+
+use crate::system_control::linux::x11_input::{X11Input, BUTTON_RIGHT};
+use crate::system_control::AbortKey;
+
+/// Connection for polling the mouse and the keyboard, along with the keycodes of the keys asked so far
+struct InputState {
+    input: X11Input,
+    keycodes: HashMap<Keysym, Vec<Keycode>>,
+}
+
+static INPUT_STATE: Lazy<Mutex<Option<InputState>>> = Lazy::new(|| {
+    let state = match X11Input::new() {
+        Ok(input) => Some(InputState { input, keycodes: HashMap::new() }),
+        Err(e) => {
+            warn!("无法读取鼠标键盘状态，无法中断扫描：{}", e);
+            None
+        }
+    };
+    Mutex::new(state)
+});
+
+fn keysym(key: AbortKey) -> Keysym {
+    match key {
+        AbortKey::F1 => 0xffbe,
+        AbortKey::F2 => 0xffbf,
+        AbortKey::F3 => 0xffc0,
+        AbortKey::F4 => 0xffc1,
+        AbortKey::F5 => 0xffc2,
+        AbortKey::F6 => 0xffc3,
+        AbortKey::F7 => 0xffc4,
+        AbortKey::F8 => 0xffc5,
+        AbortKey::F9 => 0xffc6,
+        AbortKey::F10 => 0xffc7,
+        AbortKey::F11 => 0xffc8,
+        AbortKey::F12 => 0xffc9,
+        AbortKey::Escape => 0xff1b,
+        AbortKey::Pause => 0xff13,
+        AbortKey::ScrollLock => 0xff14,
+        AbortKey::Insert => 0xff63,
+        AbortKey::Delete => 0xffff,
+        AbortKey::Home => 0xff50,
+        AbortKey::End => 0xff57,
+    }
+}
+
+pub fn is_rmb_down() -> bool {
+    let state = INPUT_STATE.lock().unwrap();
+    match state.as_ref() {
+        Some(state) => state.input.is_button_down(BUTTON_RIGHT).unwrap_or(false),
+        None => false,
+    }
+}
+
+pub fn is_key_down(key: AbortKey) -> bool {
+    let mut state = INPUT_STATE.lock().unwrap();
+    let Some(state) = state.as_mut() else {
+        return false;
+    };
+
+    let keysym = keysym(key);
+    if !state.keycodes.contains_key(&keysym) {
+        let keycodes = state.input.keycodes(keysym).unwrap_or_else(|e| {
+            warn!("无法读取键盘映射：{}", e);
+            Vec::new()
+        });
+        if keycodes.is_empty() {
+            warn!("键盘上没有 {} 键", key);
+        }
+        state.keycodes.insert(keysym, keycodes);
+    }
+
+    state.input.is_any_key_down(&state.keycodes[&keysym]).unwrap_or(false)
+}
@@ -6,6 +6,7 @@ use std::time::Duration;
 use serde::Deserialize;
 use std::io::stdin;
 use std::process;
+use crate::system_control::abort_key;
 pub use misc::*;
 
 #[cfg(target_os = "macos")]
@@ -18,6 +19,11 @@ mod windows;
 #[cfg(windows)]
 pub use windows::*;
 
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+pub use linux::*;
+
 mod misc;
 
 pub fn sleep(ms: u32) {
@@ -46,11 +52,28 @@ macro_rules! error_and_quit {
     );
 }
 
-#[cfg(not(windows))]
+#[cfg(not(any(windows, target_os = "linux")))]
 pub fn is_rmb_down() -> bool {
     false
 }
 
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn is_key_down(_key: crate::system_control::AbortKey) -> bool {
+    false
+}
+
+/// Whether the user asks to interrupt the scan, by the right mouse button or the abort key
+pub fn is_abort_requested() -> bool {
+    if is_rmb_down() {
+        return true;
+    }
+
+    match abort_key() {
+        Some(key) => is_key_down(key),
+        None => false,
+    }
+}
+
 #[derive(Deserialize)]
 pub struct GithubTag {
     pub name: String,
@@ -16,6 +16,7 @@ use windows_sys::Win32::UI::WindowsAndMessaging::*;
 use windows_sys::Win32::System::SystemServices::*;
 use windows_sys::Win32::System::LibraryLoader::*;
 use crate::positioning::Rect;
+use crate::system_control::AbortKey;
 
 pub fn encode_lpcstr(s: &str) -> Vec<u8> {
     let mut arr: Vec<u8> = s.bytes().map(|x| x as u8).collect();
@@ -133,6 +134,33 @@ pub fn is_rmb_down() -> bool {
     }
 }
 
+pub fn is_key_down(key: AbortKey) -> bool {
+    let vk = match key {
+        AbortKey::F1 => VK_F1,
+        AbortKey::F2 => VK_F2,
+        AbortKey::F3 => VK_F3,
+        AbortKey::F4 => VK_F4,
+        AbortKey::F5 => VK_F5,
+        AbortKey::F6 => VK_F6,
+        AbortKey::F7 => VK_F7,
+        AbortKey::F8 => VK_F8,
+        AbortKey::F9 => VK_F9,
+        AbortKey::F10 => VK_F10,
+        AbortKey::F11 => VK_F11,
+        AbortKey::F12 => VK_F12,
+        AbortKey::Escape => VK_ESCAPE,
+        AbortKey::Pause => VK_PAUSE,
+        AbortKey::ScrollLock => VK_SCROLL,
+        AbortKey::Insert => VK_INSERT,
+        AbortKey::Delete => VK_DELETE,
+        AbortKey::Home => VK_HOME,
+        AbortKey::End => VK_END,
+    };
+
+    // the most significant bit is set while the key is down
+    unsafe { GetAsyncKeyState(vk as i32) as u16 & 0x8000 != 0 }
+}
+
 pub fn set_dpi_awareness() {
     let h_lib = unsafe {
         let utf16 = encode_lpcstr("Shcore.dll");
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+use crate::positioning::Size;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AnchorX {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AnchorY {
+    #[default]
+    Top,
+    Center,
+    Bottom,
+}
+
+/// Which edge (or the centre) of the window a value is positioned relative to.
+/// When the aspect ratio changes, the game UI is scaled to fit and then sticks to its anchor,
+/// e.g. the item panel sticks to the right edge of a 21:9 window
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Anchor {
+    #[serde(default)]
+    pub x: AnchorX,
+    #[serde(default)]
+    pub y: AnchorY,
+}
+
+/// The factor which a UI recorded at `from` is scaled by to fit in `to`
+pub fn fit_factor(from: Size<usize>, to: Size<usize>) -> f64 {
+    let fx = to.width as f64 / from.width as f64;
+    let fy = to.height as f64 / from.height as f64;
+    fx.min(fy)
+}
+
+impl AnchorX {
+    /// Position of the anchor in the window, as a fraction of the width
+    fn fraction(&self) -> f64 {
+        match self {
+            AnchorX::Left => 0.0,
+            AnchorX::Center => 0.5,
+            AnchorX::Right => 1.0,
+        }
+    }
+}
+
+impl AnchorY {
+    /// Position of the anchor in the window, as a fraction of the height
+    fn fraction(&self) -> f64 {
+        match self {
+            AnchorY::Top => 0.0,
+            AnchorY::Center => 0.5,
+            AnchorY::Bottom => 1.0,
+        }
+    }
+}
+
+impl Anchor {
+    /// Map a point recorded in a window of size `from` into a window of size `to`,
+    /// the distance to the anchor is scaled by the fit factor
+    pub fn map(&self, x: f64, y: f64, from: Size<usize>, to: Size<usize>) -> (f64, f64) {
+        let factor = fit_factor(from, to);
+        let ax = self.x.fraction();
+        let ay = self.y.fraction();
+
+        (
+            to.width as f64 * ax + (x - from.width as f64 * ax) * factor,
+            to.height as f64 * ay + (y - from.height as f64 * ay) * factor,
+        )
+    }
+}
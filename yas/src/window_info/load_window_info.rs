@@ -1,4 +1,7 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use crate::game_info::{Platform, UI};
 use crate::positioning::Size;
@@ -15,6 +18,51 @@ pub struct WindowInfoTemplatePerSize {
 }
 
 impl WindowInfoTemplatePerSize {
+    /// Parse a layout file at runtime, errors name the file and the bad key
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("无法读取窗口布局文件 {:?}：{}", path, e))?;
+        Self::from_json_str(&s).map_err(|e| anyhow!("{:?}: {}", path, e))
+    }
+
+    pub fn from_json_str(s: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(s)?;
+        let object = value.as_object().ok_or(anyhow!("layout is not a json object"))?;
+
+        let field = |key: &str| object.get(key).cloned().ok_or(anyhow!("missing key \"{}\"", key));
+        let current_resolution: Size<usize> = serde_json::from_value(field("current_resolution")?)
+            .map_err(|e| anyhow!("invalid \"current_resolution\": {}", e))?;
+        let platform: Platform = serde_json::from_value(field("platform")?)
+            .map_err(|e| anyhow!("invalid \"platform\": {}", e))?;
+        let ui: UI = serde_json::from_value(field("ui")?)
+            .map_err(|e| anyhow!("invalid \"ui\": {}", e))?;
+        if current_resolution.width == 0 || current_resolution.height == 0 {
+            return Err(anyhow!("invalid \"current_resolution\": {}", current_resolution));
+        }
+
+        let mut data = HashMap::new();
+        let raw_data = field("data")?;
+        let raw_data = raw_data.as_object().ok_or(anyhow!("\"data\" is not a json object"))?;
+        for (key, raw_value) in raw_data.iter() {
+            let value: WindowInfoType = serde_json::from_value(raw_value.clone())
+                .map_err(|e| anyhow!("invalid window info \"{}\": {}", key, e))?;
+            if let WindowInfoType::Rect(rect) | WindowInfoType::AnchoredRect { rect, .. } = value {
+                if !(rect.width > 0.0 && rect.height > 0.0) {
+                    return Err(anyhow!("invalid window info \"{}\": empty rect {}", key, rect));
+                }
+            }
+            data.insert(key.clone(), value);
+        }
+
+        Ok(Self {
+            current_resolution,
+            platform,
+            ui,
+            data,
+        })
+    }
+
     pub fn inject_into_window_info_repo(&self, repo: &mut WindowInfoRepository) {
         for (name, value) in self.data.iter() {
             repo.add(name, self.current_resolution, self.ui, self.platform, *value);
@@ -22,17 +70,46 @@ impl WindowInfoTemplatePerSize {
     }
 }
 
-pub macro load_window_info_repo($($filename:literal),+ $(,)?) {
-    {
-        let mut result = WindowInfoRepository::new();
-        $(
-            {
-                let s = include_str!($filename);
-                let f: WindowInfoTemplatePerSize = serde_json::from_str(&s).unwrap();
-                f.inject_into_window_info_repo(&mut result);
+/// Load a layout file, or all the json files in a directory, into a repository
+pub fn load_window_info_repo_from_path<P: AsRef<Path>>(path: P) -> Result<WindowInfoRepository> {
+    let path = path.as_ref();
+    let files: Vec<PathBuf> = if path.is_dir() {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(path)? {
+            let p = entry?.path();
+            if p.is_file() && p.extension().map(|ext| ext == "json").unwrap_or(false) {
+                files.push(p);
             }
-        )*
-        result
+        }
+        // later files override earlier ones
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    let mut result = WindowInfoRepository::new();
+    for file in files.iter() {
+        let f = WindowInfoTemplatePerSize::from_file(file)?;
+        f.inject_into_window_info_repo(&mut result);
     }
+    Ok(result)
+}
+
+#[macro_export]
+macro_rules! load_window_info_repo {
+    ($($filename:literal),+ $(,)?) => {
+        {
+            let mut result = $crate::window_info::WindowInfoRepository::new();
+            $(
+                {
+                    let s = include_str!($filename);
+                    let f = $crate::window_info::WindowInfoTemplatePerSize::from_json_str(s).unwrap();
+                    f.inject_into_window_info_repo(&mut result);
+                }
+            )*
+            result
+        }
+    };
 }
 
@@ -2,8 +2,13 @@ mod window_info_repository;
 mod window_info_type;
 mod load_window_info;
 mod from_window_info_repository;
+mod anchor;
+mod window_info_config;
 
 pub use from_window_info_repository::FromWindowInfoRepository;
-pub use window_info_repository::WindowInfoRepository;
+pub use window_info_repository::{WindowInfoRepository, PlatformFallback, WindowInfoSource};
 pub use window_info_type::WindowInfoType;
-pub use load_window_info::load_window_info_repo;
\ No newline at end of file
+pub use load_window_info::{load_window_info_repo_from_path, WindowInfoTemplatePerSize};
+pub use crate::load_window_info_repo;
+pub use window_info_config::WindowInfoConfig;
+pub use anchor::{Anchor, AnchorX, AnchorY, fit_factor};
\ No newline at end of file
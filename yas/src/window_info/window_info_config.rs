@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use log::{info, warn};
+
+use crate::window_info::{load_window_info_repo_from_path, WindowInfoRepository};
+
+#[derive(Clone, clap::Args, Default)]
+pub struct WindowInfoConfig {
+    /// Layout files or directories, which are loaded at runtime and override the built-in layouts
+    #[arg(id = "window-info", long = "window-info", help = "额外的窗口布局文件或目录（JSON），覆盖内置布局，可指定多次", value_name = "FILE|DIR")]
+    pub window_info: Vec<PathBuf>,
+}
+
+impl WindowInfoConfig {
+    /// Merge the user supplied layouts over the built-in repository
+    pub fn apply(&self, repo: &mut WindowInfoRepository) -> Result<()> {
+        for path in self.window_info.iter() {
+            let user_repo = load_window_info_repo_from_path(path)?;
+
+            let mut keys = user_repo.data.keys().collect::<Vec<_>>();
+            keys.sort();
+            for key in keys {
+                // most likely a typo, it's harmless but will never be used
+                if !repo.data.contains_key(key) {
+                    warn!("{:?}: 未知的窗口信息 \"{}\"", path, key);
+                }
+            }
+
+            repo.merge_inplace(&user_repo);
+            info!("加载窗口布局 {:?}", path);
+        }
+        Ok(())
+    }
+}
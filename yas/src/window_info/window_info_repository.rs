@@ -1,10 +1,48 @@
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 
+use log::{debug, info};
 use serde::{Deserialize, Serialize};
 use crate::game_info::{Platform, UI};
 use crate::positioning::{Pos, Scalable, Size};
 
 use crate::window_info::WindowInfoType;
+use crate::window_info::anchor::fit_factor;
+
+/// If no entry is recorded for `platform` under `ui`, entries of `fallback` are used instead
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlatformFallback {
+    pub ui: UI,
+    pub platform: Platform,
+    pub fallback: Platform,
+}
+
+/// The entry which a lookup actually used
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowInfoSource {
+    /// the window size where the value is recorded
+    pub size: Size<usize>,
+    pub ui: UI,
+    pub platform: Platform,
+    /// the factor which the recorded value is scaled by, 1.0 for an exact match
+    pub scale_factor: f64,
+}
+
+impl WindowInfoSource {
+    /// Tell at the default log level when the entry is of another platform than the requested one,
+    /// e.g. a windows layout is used on linux
+    pub fn report_platform_fallback(&self, name: &str, platform: Platform) {
+        if self.platform != platform {
+            info!("{} 没有 {:?} 的窗口布局，使用 {}", name, platform, self);
+        }
+    }
+}
+
+impl Display for WindowInfoSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {:?} {:?} (x{})", self.size, self.ui, self.platform, self.scale_factor)
+    }
+}
 
 /// Maps a window-info-key to a list of entries
 /// where entries consist of a size where the value is recorded, and accordingly a value
@@ -12,15 +50,49 @@ use crate::window_info::WindowInfoType;
 pub struct WindowInfoRepository {
     /// window info key -> (window size, ui, platform)
     pub data: HashMap<String, HashMap<(Size<usize>, UI, Platform), WindowInfoType>>,
+
+    /// Tried in order when a platform has no entries, e.g. the game runs with wine on linux,
+    /// whose UI is identical to the one on windows
+    #[serde(default)]
+    pub platform_fallbacks: Vec<PlatformFallback>,
 }
 
 impl WindowInfoRepository {
     pub fn new() -> WindowInfoRepository {
         WindowInfoRepository {
             data: HashMap::new(),
+            platform_fallbacks: vec![
+                PlatformFallback { ui: UI::Desktop, platform: Platform::Linux, fallback: Platform::Windows },
+            ],
+        }
+    }
+
+    pub fn add_platform_fallback(&mut self, ui: UI, platform: Platform, fallback: Platform) {
+        let item = PlatformFallback { ui, platform, fallback };
+        if !self.platform_fallbacks.contains(&item) {
+            self.platform_fallbacks.push(item);
         }
     }
 
+    pub fn clear_platform_fallbacks(&mut self) {
+        self.platform_fallbacks.clear();
+    }
+
+    /// The platform itself, followed by its fallbacks (transitively) in the order they are added
+    pub fn platform_chain(&self, ui: UI, platform: Platform) -> Vec<Platform> {
+        let mut chain = vec![platform];
+        let mut i = 0;
+        while i < chain.len() {
+            for item in self.platform_fallbacks.iter() {
+                if item.ui == ui && item.platform == chain[i] && !chain.contains(&item.fallback) {
+                    chain.push(item.fallback);
+                }
+            }
+            i += 1;
+        }
+        chain
+    }
+
     pub fn add(&mut self, name: &str, size: Size<usize>, ui: UI, platform: Platform, value: WindowInfoType) {
         self.data
             .entry(String::from(name))
@@ -56,35 +128,78 @@ impl WindowInfoRepository {
     /// Get window info by name and size
     /// if name or resolution does not exist, then return None
     pub fn get_exact<T>(&self, name: &str, window_size: Size<usize>, ui: UI, platform: Platform) -> Option<T> where WindowInfoType: TryInto<T> {
-        if self.data.contains_key(name) &&
-          self.data[name].contains_key(&(window_size, ui, platform)) {
-            return self.data[name][&(window_size, ui, platform)].try_into().ok();
+        self.get_exact_with_source(name, window_size, ui, platform).map(|x| x.0)
+    }
+
+    /// Same as `get_exact`, also returns the entry which is used
+    pub fn get_exact_with_source<T>(&self, name: &str, window_size: Size<usize>, ui: UI, platform: Platform) -> Option<(T, WindowInfoSource)> where WindowInfoType: TryInto<T> {
+        let entries = self.data.get(name)?;
+
+        for p in self.platform_chain(ui, platform) {
+            if let Some(value) = entries.get(&(window_size, ui, p)) {
+                let source = WindowInfoSource { size: window_size, ui, platform: p, scale_factor: 1.0 };
+                debug!("window info \"{}\": {}", name, source);
+                return (*value).try_into().ok().map(|v| (v, source));
+            }
         }
 
         None
     }
 
     /// Get window info by name and size
-    /// if window size does not exists exactly, this function will search for the same resolution family and scale the result
+    /// if window size does not exists exactly, this function will search for the biggest size of the same aspect ratio and scale the result,
+    /// or derive anchored values from other aspect ratios
     pub fn get_auto_scale<T>(&self, name: &str, window_size: Size<usize>, ui: UI, platform: Platform) -> Option<T> where WindowInfoType: TryInto<T> {
-        if self.data.contains_key(name) {
-            if self.data[name].contains_key(&(window_size, ui, platform)) {
-                return self.data[name][&(window_size, ui, platform)].try_into().ok();
-            } else {
-                // todo find a biggest size which can be scaled, this will reduce error
-                // find if a resolution can be scaled
-                for (k, value) in self.data[name].iter() {
-                    let size = &k.0;
-                    if size.width * window_size.height == size.height * window_size.width
-                        && k.1 == ui && k.2 == platform
-                    {
-                        let factor: f64 = window_size.width as f64 / size.width as f64;
-                        return value.scale(factor).try_into().ok();
-                    }
-                }
+        self.get_auto_scale_with_source(name, window_size, ui, platform).map(|x| x.0)
+    }
+
+    /// Same as `get_auto_scale`, also returns the entry which is used
+    pub fn get_auto_scale_with_source<T>(&self, name: &str, window_size: Size<usize>, ui: UI, platform: Platform) -> Option<(T, WindowInfoSource)> where WindowInfoType: TryInto<T> {
+        let entries = self.data.get(name)?;
+
+        // a platform earlier in the chain always wins, even if a later one has an exact size
+        for p in self.platform_chain(ui, platform) {
+            let candidates = entries.iter()
+                .filter(|(k, _)| k.1 == ui && k.2 == p)
+                .map(|(k, v)| (k.0, v));
+
+            if let Some((size, value)) = Self::find_best_fit(candidates, window_size) {
+                let source = WindowInfoSource { size, ui, platform: p, scale_factor: fit_factor(size, window_size) };
+                debug!("window info \"{}\": {}", name, source);
+                return value.resolve(size, window_size).try_into().ok().map(|v| (v, source));
             }
         }
 
         None
     }
+
+    /// Choose the entry to derive the value for `window_size` from, deterministically:
+    /// 1. the exact size
+    /// 2. the biggest size of the same aspect ratio, which has the smallest scaling error
+    /// 3. values independent of the aspect ratio (e.g. anchored), from the closest aspect ratio, the biggest size first
+    fn find_best_fit<'a>(candidates: impl Iterator<Item = (Size<usize>, &'a WindowInfoType)>, window_size: Size<usize>) -> Option<(Size<usize>, &'a WindowInfoType)> {
+        let candidates = candidates.collect::<Vec<_>>();
+
+        if let Some(item) = candidates.iter().find(|(size, _)| *size == window_size) {
+            return Some(*item);
+        }
+
+        let same_ratio = candidates.iter()
+            .filter(|(size, _)| size.width * window_size.height == size.height * window_size.width)
+            .max_by_key(|(size, _)| (size.width, size.height));
+        if let Some(item) = same_ratio {
+            return Some(*item);
+        }
+
+        let ratio = |size: &Size<usize>| size.width as f64 / size.height as f64;
+        let target_ratio = ratio(&window_size);
+        candidates.into_iter()
+            .filter(|(_, value)| value.is_ratio_independent())
+            .min_by(|(a, _), (b, _)| {
+                let da = (ratio(a) - target_ratio).abs();
+                let db = (ratio(b) - target_ratio).abs();
+                da.total_cmp(&db)
+                    .then((b.width, b.height).cmp(&(a.width, a.height)))
+            })
+    }
 }
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use crate::positioning::{Pos, Rect, Scalable, Size};
+use crate::window_info::anchor::{fit_factor, Anchor};
 use anyhow::anyhow;
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
@@ -11,6 +12,45 @@ pub enum WindowInfoType {
     /// when window size scales, these amount will not scale
     InvariantInt(i32),
     InvariantFloat(f64),
+    /// a rect positioned relative to an edge or the centre of the window, which can be
+    /// derived for a window of different aspect ratio
+    AnchoredRect {
+        anchor: Anchor,
+        rect: Rect<f64>,
+    },
+    AnchoredPos {
+        anchor: Anchor,
+        pos: Pos<f64>,
+    },
+}
+
+impl WindowInfoType {
+    /// Whether the value can be derived for a window of different aspect ratio
+    pub fn is_ratio_independent(&self) -> bool {
+        !matches!(self, WindowInfoType::Rect(_) | WindowInfoType::Pos(_))
+    }
+
+    /// Convert a value recorded in a window of size `from` into a window of size `to`.
+    /// Anchored values become plain values, others are scaled by the fit factor
+    pub fn resolve(&self, from: Size<usize>, to: Size<usize>) -> WindowInfoType {
+        let factor = fit_factor(from, to);
+        match *self {
+            WindowInfoType::AnchoredRect { anchor, rect } => {
+                let (left, top) = anchor.map(rect.left, rect.top, from, to);
+                WindowInfoType::Rect(Rect {
+                    left,
+                    top,
+                    width: rect.width * factor,
+                    height: rect.height * factor,
+                })
+            },
+            WindowInfoType::AnchoredPos { anchor, pos } => {
+                let (x, y) = anchor.map(pos.x, pos.y, from, to);
+                WindowInfoType::Pos(Pos { x, y })
+            },
+            _ => self.scale(factor),
+        }
+    }
 }
 
 // due to orphan rule, we implement TryInto instead of TryFrom
@@ -31,6 +71,7 @@ impl TryInto<Rect<f64>> for WindowInfoType {
     fn try_into(self) -> Result<Rect<f64>, Self::Error> {
         match self {
             WindowInfoType::Rect(rect) => Ok(rect),
+            WindowInfoType::AnchoredRect { rect, .. } => Ok(rect),
             _ => Err(anyhow!(String::from("not a rect type"))),
         }
     }
@@ -42,6 +83,7 @@ impl TryInto<Pos<f64>> for WindowInfoType {
     fn try_into(self) -> Result<Pos<f64>, Self::Error> {
         match self {
             WindowInfoType::Pos(pos) => Ok(pos),
+            WindowInfoType::AnchoredPos { pos, .. } => Ok(pos),
             _ => Err(anyhow!(String::from("not a pos type"))),
         }
     }
@@ -79,6 +121,8 @@ impl Scalable for WindowInfoType {
             WindowInfoType::Float(v) => WindowInfoType::Float(v.scale(factor)),
             WindowInfoType::InvariantInt(v) => WindowInfoType::InvariantInt(v),
             WindowInfoType::InvariantFloat(v) => WindowInfoType::InvariantFloat(v),
+            WindowInfoType::AnchoredRect { anchor, rect } => WindowInfoType::AnchoredRect { anchor, rect: rect.scale(factor) },
+            WindowInfoType::AnchoredPos { anchor, pos } => WindowInfoType::AnchoredPos { anchor, pos: pos.scale(factor) },
         };
         result
     }
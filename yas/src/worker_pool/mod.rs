@@ -0,0 +1,5 @@
+pub use ordered_worker_pool::OrderedWorkerPool;
+pub use recognition::{spawn_recognition, RecognitionConfig, RecognitionResult, Recognized};
+
+mod ordered_worker_pool;
+mod recognition;
@@ -0,0 +1,146 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Receiver};
+use std::thread::JoinHandle;
+
+use anyhow::{anyhow, Result};
+use log::warn;
+
+struct Input<I> {
+    items: I,
+    next_index: usize,
+    // set when the input ends, or the results are not wanted anymore, so that all workers stop
+    finished: bool,
+}
+
+impl<I: Iterator> Input<I> {
+    fn next(&mut self) -> Option<(usize, I::Item)> {
+        if self.finished {
+            return None;
+        }
+
+        match self.items.next() {
+            Some(item) => {
+                let index = self.next_index;
+                self.next_index += 1;
+                Some((index, item))
+            },
+            None => {
+                self.finished = true;
+                None
+            }
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "未知错误"
+    }
+}
+
+/// Runs the items of an input, usually received from a channel, on several threads, each with its own worker
+/// (e.g. a model instance), and yields the results in the order of the input.
+/// If a worker panics, the result of its item is an error and the worker stops, so no index is skipped
+///
+/// If the pool is dropped, the workers stop after their current item, and the input is dropped
+/// (closing its channel) once all of them have stopped
+pub struct OrderedWorkerPool<R> {
+    rx: Receiver<(usize, Result<R>)>,
+    pending: BTreeMap<usize, Result<R>>,
+    next_index: usize,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl<R: Send + 'static> OrderedWorkerPool<R> {
+    /// `worker(index, item)` is called with the index of the item in the input, starting from 0
+    /// For a channel which sends `None` at the end, use `rx.into_iter().map_while(|item| item)`
+    pub fn spawn<I, T, W>(workers: Vec<W>, input: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: Send + 'static,
+        T: Send + 'static,
+        W: FnMut(usize, T) -> R + Send + 'static,
+    {
+        let input = Arc::new(Mutex::new(Input { items: input.into_iter(), next_index: 0, finished: false }));
+        let (result_tx, result_rx) = mpsc::channel();
+
+        let handles = workers.into_iter()
+            .map(|mut worker| {
+                let input = input.clone();
+                let result_tx = result_tx.clone();
+                std::thread::spawn(move || loop {
+                    // the lock is held while receiving, so that the indices follow the order of the input
+                    let next = match input.lock() {
+                        Ok(mut input) => input.next(),
+                        Err(_) => None,
+                    };
+                    let (index, item) = match next {
+                        Some(v) => v,
+                        None => break,
+                    };
+
+                    let result = std::panic::catch_unwind(AssertUnwindSafe(|| worker(index, item)))
+                        .map_err(|payload| anyhow!("识别第 {} 个物品时线程崩溃：{}", index, panic_message(payload.as_ref())));
+                    let panicked = result.is_err();
+
+                    if result_tx.send((index, result)).is_err() {
+                        // the results are not wanted anymore
+                        if let Ok(mut input) = input.lock() {
+                            input.finished = true;
+                        }
+                        break;
+                    }
+                    // the state of the worker is unknown after a panic
+                    if panicked {
+                        break;
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            rx: result_rx,
+            pending: BTreeMap::new(),
+            next_index: 0,
+            handles,
+        }
+    }
+
+    pub fn worker_count(&self) -> usize {
+        self.handles.len()
+    }
+}
+
+impl<R> Iterator for OrderedWorkerPool<R> {
+    type Item = Result<R>;
+
+    fn next(&mut self) -> Option<Result<R>> {
+        loop {
+            if let Some(result) = self.pending.remove(&self.next_index) {
+                self.next_index += 1;
+                return Some(result);
+            }
+
+            match self.rx.recv() {
+                Ok((index, result)) => {
+                    self.pending.insert(index, result);
+                },
+                // all workers have stopped
+                Err(_) => {
+                    if !self.pending.is_empty() {
+                        warn!("识别线程异常退出，丢弃 {} 个识别结果", self.pending.len());
+                        self.pending.clear();
+                    }
+                    return None;
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::thread::JoinHandle;
+
+use anyhow::Result;
+use log::{error, info, warn};
+
+use crate::worker_pool::OrderedWorkerPool;
+
+/// What the game makes of the result of an item
+pub enum Recognized<S> {
+    Item(S),
+    /// This item and all the following ones are not wanted, e.g. below the min level
+    Stop,
+}
+
+/// The distinct items of a recognition
+pub struct RecognitionResult<S> {
+    pub items: Vec<S>,
+    /// Index of the item whose worker panicked, the recognition stopped there.
+    /// A scan resumed from it recognizes the item and the following ones again
+    pub failed_index: Option<usize>,
+}
+
+pub struct RecognitionConfig {
+    pub verbose: bool,
+    /// Too many duplicates in a row usually mean a page was not turned, or the scan did not start at the top.
+    /// The recognition stops after this many of them, `None` to keep going
+    pub max_consecutive_dup: Option<usize>,
+}
+
+/// Recognize the items of `input` with all the workers in parallel, on a new thread.
+/// `handle(index, result)` gets the result of each worker in the order of the items
+pub fn spawn_recognition<I, T, W, R, S, H>(workers: Vec<W>, input: I, config: RecognitionConfig, mut handle: H) -> JoinHandle<RecognitionResult<S>>
+where
+    I: IntoIterator<Item = T> + Send + 'static,
+    I::IntoIter: Send + 'static,
+    T: Send + 'static,
+    W: FnMut(usize, T) -> R + Send + 'static,
+    R: Send + 'static,
+    S: Clone + Eq + Hash + Debug + Send + 'static,
+    H: FnMut(usize, R) -> Result<Recognized<S>> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let mut results = Vec::new();
+        let mut hash = HashSet::new();
+        let mut consecutive_dup_count = 0;
+        let mut failed_index = None;
+
+        let pool = OrderedWorkerPool::spawn(workers, input);
+        info!("识别线程数: {}", pool.worker_count());
+
+        for (index, result) in pool.enumerate() {
+            let result = match result {
+                Ok(v) => v,
+                Err(e) => {
+                    error!("{}", e);
+                    failed_index = Some(index);
+                    break;
+                },
+            };
+
+            let result = match handle(index, result) {
+                Ok(Recognized::Item(v)) => v,
+                Ok(Recognized::Stop) => break,
+                Err(e) => {
+                    error!("识别错误: {}", e);
+                    continue;
+                },
+            };
+
+            if config.verbose {
+                info!("{:?}", result);
+            }
+
+            if hash.contains(&result) {
+                consecutive_dup_count += 1;
+                warn!("识别到重复物品: {:#?}", result);
+            } else {
+                consecutive_dup_count = 0;
+                hash.insert(result.clone());
+                results.push(result);
+            }
+
+            if config.max_consecutive_dup.is_some_and(|max| consecutive_dup_count >= max) {
+                error!("识别到连续多个重复物品，可能为翻页错误，或者为非背包顶部开始扫描");
+                break;
+            }
+        }
+
+        info!("识别结束，非重复物品数量: {}", hash.len());
+
+        RecognitionResult { items: results, failed_index }
+    })
+}
@@ -0,0 +1,242 @@
+//! The grid controller only needs a `GridWindowInfo` of the game, this one is made up and small,
+//! so that recorded frames are quick to save and load
+
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
+
+use anyhow::Result;
+use yas_core::capture::ReplayCapturer;
+use yas_core::game_info::{GameInfo, Platform, ResolutionFamily, UI};
+use yas_core::positioning::{Pos, Rect, Size};
+use yas_core::scanner_controller::repository_layout::{
+    GridScanController, GridScanControllerConfig, GridWindowInfo, PixelFlag, ReturnResult, RowFlag, RowScroll,
+};
+use yas_core::simulation::{all_items, run_generator, SimulatedInventory};
+use yas_core::system_control::ReplayControl;
+
+/// A row takes 10 wheel events
+const SCROLL_PER_WHEEL: f64 = 3.0;
+
+struct TestWindowInfo {
+    row_scroll: RowScroll,
+}
+
+impl GridWindowInfo for TestWindowInfo {
+    fn scan_margin_pos(&self) -> Pos<f64> {
+        Pos::new(25.0, 25.0)
+    }
+
+    fn item_size(&self) -> Size<f64> {
+        Size::new(20.0, 25.0)
+    }
+
+    fn item_gap_size(&self) -> Size<f64> {
+        Size::new(5.0, 5.0)
+    }
+
+    fn row(&self) -> usize {
+        4
+    }
+
+    fn col(&self) -> usize {
+        5
+    }
+
+    fn pool_rect(&self) -> Rect<f64> {
+        Rect::new(175.0, 25.0, 50.0, 75.0)
+    }
+
+    /// In the gap above the first row
+    fn row_flag(&self) -> Box<dyn RowFlag> {
+        Box::new(PixelFlag::new(Pos::new(35.0, 22.0), 10))
+    }
+
+    fn row_scroll(&self) -> RowScroll {
+        self.row_scroll
+    }
+}
+
+fn game_info() -> GameInfo {
+    GameInfo {
+        window: Rect::new(0, 0, 250, 150),
+        resolution_family: ResolutionFamily::Windows16x9,
+        is_cloud: false,
+        ui: UI::Desktop,
+        platform: Platform::Windows,
+    }
+}
+
+fn config() -> GridScanControllerConfig {
+    GridScanControllerConfig {
+        scroll_delay: 0,
+        max_wait_switch_item: 200,
+        ..Default::default()
+    }
+}
+
+fn new_controller(row_scroll: RowScroll, item_count: usize) -> (SimulatedInventory, Rc<RefCell<GridScanController<TestWindowInfo>>>) {
+    new_controller_with(row_scroll, item_count, SCROLL_PER_WHEEL, config())
+}
+
+fn new_controller_with(
+    row_scroll: RowScroll,
+    item_count: usize,
+    scroll_per_wheel: f64,
+    config: GridScanControllerConfig,
+) -> (SimulatedInventory, Rc<RefCell<GridScanController<TestWindowInfo>>>) {
+    let window_info = TestWindowInfo { row_scroll };
+    let inventory = SimulatedInventory::from_window_info(&window_info, &game_info(), item_count, scroll_per_wheel);
+    inventory.set_switch_delay(3);
+    let controller = inventory.controller(window_info, config, game_info());
+
+    (inventory, controller)
+}
+
+/// Run the generator to the end, returns the item selected at each yield
+fn scan(row_scroll: RowScroll, item_count: usize) -> (SimulatedInventory, Vec<Option<usize>>, Result<ReturnResult>) {
+    let (inventory, controller) = new_controller(row_scroll, item_count);
+    let (selected, result) = inventory.scan(controller, item_count);
+    (inventory, selected, result)
+}
+
+/// Stop a scan after `stop_after` items, then continue it from its progress with a new controller
+fn scan_resumed(row_scroll: RowScroll, item_count: usize, stop_after: usize) -> (Vec<Option<usize>>, Vec<Option<usize>>, Result<ReturnResult>) {
+    let (inventory, controller) = new_controller(row_scroll, item_count);
+    let mut generator = GridScanController::get_generator(controller, item_count);
+    let (first, _) = run_generator(&mut generator, stop_after, || inventory.selected());
+    let progress = generator.progress();
+
+    let (inventory, controller) = new_controller(row_scroll, item_count);
+    let mut generator = GridScanController::get_generator_from_progress(controller, item_count, &progress).unwrap();
+    let (second, result) = run_generator(&mut generator, usize::MAX, || inventory.selected());
+    (first, second, result.unwrap())
+}
+
+#[test]
+fn scans_every_item_detecting_rows() {
+    let (_, selected, result) = scan(RowScroll::default(), 47);
+    assert!(matches!(result, Ok(ReturnResult::Finished)));
+    assert_eq!(selected, all_items(47));
+}
+
+#[test]
+fn scans_every_item_scrolling_fixed_rows() {
+    let (inventory, selected, result) = scan(RowScroll::Fixed { wheel: 10 }, 47);
+    assert!(matches!(result, Ok(ReturnResult::Finished)));
+    assert_eq!(selected, all_items(47));
+    assert_eq!(inventory.wheel_events(), 6 * 10);
+}
+
+#[test]
+fn resumes_after_the_last_scanned_item() {
+    for stop_after in [3, 13, 20, 25, 44] {
+        let (first, second, result) = scan_resumed(RowScroll::default(), 47, stop_after);
+        assert!(matches!(result, Ok(ReturnResult::Finished)));
+        assert_eq!(first, all_items(stop_after));
+        assert_eq!(second, all_items(47)[stop_after..]);
+    }
+}
+
+#[test]
+fn resumes_with_fixed_rows() {
+    let (first, second, result) = scan_resumed(RowScroll::Fixed { wheel: 10 }, 47, 27);
+    assert!(matches!(result, Ok(ReturnResult::Finished)));
+    assert_eq!(first, all_items(27));
+    assert_eq!(second, all_items(47)[27..]);
+}
+
+#[test]
+fn resumes_at_an_item_which_failed() {
+    let (inventory, controller) = new_controller(RowScroll::default(), 47);
+    let mut generator = GridScanController::get_generator(controller, 47);
+    let (first, _) = run_generator(&mut generator, 25, || inventory.selected());
+    // the last item could not be captured
+    let progress = generator.progress_retrying_item();
+
+    let (inventory, controller) = new_controller(RowScroll::default(), 47);
+    let mut generator = GridScanController::get_generator_from_progress(controller, 47, &progress).unwrap();
+    assert_eq!(generator.start_index(), 24);
+    let (second, result) = run_generator(&mut generator, usize::MAX, || inventory.selected());
+    assert!(matches!(result, Some(Ok(ReturnResult::Finished))));
+    assert_eq!(first, all_items(25));
+    assert_eq!(second, all_items(47)[24..]);
+}
+
+#[test]
+fn resuming_a_finished_scan_scans_nothing() {
+    let (_, second, result) = scan_resumed(RowScroll::default(), 47, 47);
+    assert!(matches!(result, Ok(ReturnResult::Finished)));
+    assert!(second.is_empty());
+}
+
+#[test]
+fn scans_a_single_page() {
+    let (inventory, selected, result) = scan(RowScroll::default(), 17);
+    assert!(matches!(result, Ok(ReturnResult::Finished)));
+    assert_eq!(selected, all_items(17));
+    assert_eq!(inventory.wheel_events(), 0);
+}
+
+#[test]
+fn stops_at_max_row() {
+    let config = GridScanControllerConfig {
+        max_row: Some(2),
+        ..config()
+    };
+    let (inventory, controller) = new_controller_with(RowScroll::default(), 47, SCROLL_PER_WHEEL, config);
+
+    let (selected, result) = inventory.scan(controller, 47);
+    assert!(matches!(result, Ok(ReturnResult::Finished)));
+    assert_eq!(selected, all_items(10));
+    assert_eq!(inventory.wheel_events(), 0);
+}
+
+#[test]
+fn stuck_scroll_times_out() {
+    let (inventory, controller) = new_controller_with(RowScroll::default(), 47, 0.0, config());
+
+    let (selected, result) = inventory.scan(controller, 47);
+    assert!(result.is_err());
+    assert_eq!(selected, all_items(20));
+    assert_eq!(inventory.wheel_events(), 25);
+}
+
+/// Record a scan of the inventory, then scan the recording as `--replay` does
+#[test]
+fn replays_a_recorded_scan() {
+    let dir = std::env::temp_dir().join(format!("yas_replay_test_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    let window_info = TestWindowInfo { row_scroll: RowScroll::default() };
+    let inventory = SimulatedInventory::from_window_info(&window_info, &game_info(), 47, SCROLL_PER_WHEEL);
+    inventory.record(&dir, Size::new(250, 150)).unwrap();
+    let controller = inventory.controller(window_info, config(), game_info());
+    let mut generator = GridScanController::get_generator(controller, 47);
+    // the frame each item was yielded at
+    let mut recorded_frames = Vec::new();
+    let (selected, result) = run_generator(&mut generator, usize::MAX, || {
+        recorded_frames.push(Some(inventory.recorded_frames() - 1));
+        inventory.selected()
+    });
+    assert!(matches!(result, Some(Ok(ReturnResult::Finished))));
+    assert_eq!(selected, all_items(47));
+
+    let replay = Rc::new(ReplayCapturer::new(&dir).unwrap());
+    let controller = GridScanController::from_window_info(
+        TestWindowInfo { row_scroll: RowScroll::default() },
+        config(),
+        game_info(),
+        replay.clone(),
+        Box::new(ReplayControl::new(replay.clone())),
+    );
+    let mut generator = GridScanController::get_generator(Rc::new(RefCell::new(controller)), 47);
+    let (frames, result) = run_generator(&mut generator, usize::MAX, || Some(replay.frame_index()));
+    fs::remove_dir_all(&dir).unwrap();
+
+    // the replay clicks and scrolls as the recorded scan did, so it stops at the same frames
+    assert!(matches!(result, Some(Ok(ReturnResult::Finished))));
+    assert_eq!(frames, recorded_frames);
+    assert_eq!(replay.frame_index() + 1, inventory.recorded_frames());
+}
@@ -0,0 +1,151 @@
+//! Decoding with a lexicon, on hand-built CTC outputs: each frame is a probability distribution over the classes
+
+use yas_core::ocr::{ctc_decode, ctc_lexicon_decode, Lexicon, TextWithConfidence, LEXICON_BEAM_WIDTH};
+
+/// Class 0 is the blank
+const CLASSES: [&str; 9] = ["", "攻", "击", "力", "刀", "防", "御", "生", "命"];
+
+/// Each frame lists the likely classes by text ("" for the blank), the rest of the probability is spread over the others
+fn frames(spec: &[&[(&str, f32)]]) -> Vec<Vec<f32>> {
+    spec.iter()
+        .map(|frame| {
+            let rest = 1.0 - frame.iter().map(|&(_, p)| p).sum::<f32>();
+            let others = CLASSES.len() - frame.len();
+            CLASSES.iter()
+                .map(|&class| match frame.iter().find(|&&(c, _)| c == class) {
+                    Some(&(_, p)) => p,
+                    None => rest / others as f32,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn word(c: usize) -> Option<&'static str> {
+    if c == 0 { None } else { Some(CLASSES[c]) }
+}
+
+fn decode(frames: &[Vec<f32>], lexicon: Option<&Lexicon>) -> TextWithConfidence {
+    ctc_decode(frames.len(), CLASSES.len(), |t, c| frames[t][c], word, lexicon)
+}
+
+fn lexicon_decode(frames: &[Vec<f32>], lexicon: &Lexicon) -> Option<TextWithConfidence> {
+    ctc_lexicon_decode(frames.len(), CLASSES.len(), |t, c| frames[t][c], word, lexicon, LEXICON_BEAM_WIDTH)
+}
+
+fn assert_close(a: &[f32], b: &[f32]) {
+    assert_eq!(a.len(), b.len(), "{:?} != {:?}", a, b);
+    assert!(a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-4), "{:?} != {:?}", a, b);
+}
+
+#[test]
+fn trie_contains_only_whole_words() {
+    let lexicon = Lexicon::new(["攻击力", "攻击", "防御力", "攻击力", ""]);
+
+    assert_eq!(lexicon.words(), ["攻击力", "攻击", "防御力"]);
+    assert!(lexicon.contains("攻击"));
+    assert!(lexicon.contains("攻击力"));
+    assert!(!lexicon.contains("攻"));
+    assert!(!lexicon.contains("防御"));
+    assert!(!lexicon.contains("攻击力力"));
+    assert!(!lexicon.contains(""));
+}
+
+#[test]
+fn fingerprint_depends_on_the_words() {
+    let a = Lexicon::new(["攻击力", "防御力"]);
+    let b = Lexicon::new(vec![String::from("攻击力"), String::from("防御力")]);
+    let c = Lexicon::new(["攻击力", "生命"]);
+
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    assert_ne!(a.fingerprint(), c.fingerprint());
+}
+
+#[test]
+fn keeps_the_greedy_result_if_it_is_a_word() {
+    let lexicon = Lexicon::new(["攻击力", "防御力"]);
+    let frames = frames(&[&[("防", 0.9)], &[("", 0.9)], &[("御", 0.8)], &[("力", 0.7)]]);
+
+    let result = decode(&frames, Some(&lexicon));
+    assert_eq!(result.text, "防御力");
+    assert_close(&result.char_confidence, &[0.9, 0.8, 0.7]);
+}
+
+#[test]
+fn constrains_a_misread_to_the_lexicon() {
+    let lexicon = Lexicon::new(["攻击力", "防御力"]);
+    // "刀" looks like "力" and wins the last frame by a little
+    let frames = frames(&[&[("攻", 0.9)], &[("击", 0.9)], &[("刀", 0.5), ("力", 0.4)]]);
+
+    assert_eq!(decode(&frames, None).text, "攻击刀");
+
+    let result = decode(&frames, Some(&lexicon));
+    assert_eq!(result.text, "攻击力");
+    // the confidence of the corrected char is its own probability, not the one of the misread
+    assert_close(&result.char_confidence, &[0.9, 0.9, 0.4]);
+    assert!((result.confidence - (0.9 + 0.9 + 0.4) / 3.0).abs() < 1e-4);
+}
+
+#[test]
+fn aligns_chars_spanning_several_frames() {
+    let lexicon = Lexicon::new(["攻击力"]);
+    let frames = frames(&[
+        &[("攻", 0.6)], &[("攻", 0.9)], &[("", 0.9)], &[("击", 0.7)], &[("击", 0.8)], &[("刀", 0.5), ("力", 0.45)],
+    ]);
+
+    let result = lexicon_decode(&frames, &lexicon).unwrap();
+    assert_eq!(result.text, "攻击力");
+    // the highest probability among the frames aligned to each char
+    assert_close(&result.char_confidence, &[0.9, 0.8, 0.45]);
+}
+
+#[test]
+fn repeated_chars_need_a_blank_in_between() {
+    let lexicon = Lexicon::new(["生命", "生生命"]);
+
+    let merged = frames(&[&[("生", 0.9)], &[("生", 0.9)], &[("命", 0.9)]]);
+    assert_eq!(lexicon_decode(&merged, &lexicon).unwrap().text, "生命");
+
+    let separated = frames(&[&[("生", 0.9)], &[("", 0.9)], &[("生", 0.9)], &[("命", 0.9)]]);
+    assert_eq!(lexicon_decode(&separated, &lexicon).unwrap().text, "生生命");
+}
+
+#[test]
+fn falls_back_to_greedy_if_no_word_fits() {
+    // none of the chars is a class of the model
+    let lexicon = Lexicon::new(["暴击率"]);
+    let frames = frames(&[&[("攻", 0.9)], &[("击", 0.9)], &[("刀", 0.9)]]);
+
+    assert!(lexicon_decode(&frames, &lexicon).is_none());
+    assert_eq!(decode(&frames, Some(&lexicon)).text, "攻击刀");
+}
+
+#[test]
+fn keeps_an_empty_text() {
+    let lexicon = Lexicon::new(["攻击力"]);
+    let frames = frames(&[&[("", 0.9)], &[("", 0.8)]]);
+
+    let result = decode(&frames, Some(&lexicon));
+    assert_eq!(result.text, "");
+    assert!((result.confidence - 0.85).abs() < 1e-4);
+}
+
+#[test]
+fn corrects_to_the_closest_word() {
+    let lexicon = Lexicon::new(["攻击力", "防御力", "生命"]);
+    let text = |s: &str| TextWithConfidence {
+        text: String::from(s),
+        char_confidence: vec![0.5; s.chars().count()],
+        confidence: 0.5,
+    };
+
+    let corrected = lexicon.correct(text("攻击刀"));
+    assert_eq!(corrected.text, "攻击力");
+    assert_eq!(corrected.char_confidence, vec![0.5; 3]);
+    assert_eq!(corrected.confidence, 0.5);
+
+    // more than a third of the chars away
+    assert_eq!(lexicon.correct(text("攻刀")).text, "攻刀");
+    assert_eq!(lexicon.correct(text("生命")).text, "生命");
+    assert_eq!(lexicon.correct(text("")).text, "");
+}
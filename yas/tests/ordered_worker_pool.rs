@@ -0,0 +1,35 @@
+//! Results of the pool come in the order of the input, and a panicking worker doesn't lose its item
+
+use yas_core::worker_pool::OrderedWorkerPool;
+
+#[test]
+fn yields_the_results_in_order() {
+    let workers = (0..3).map(|_| |index: usize, item: u32| (index, item * 2)).collect();
+    let pool = OrderedWorkerPool::spawn(workers, 0..100_u32);
+
+    let results = pool.map(|r| r.unwrap()).collect::<Vec<_>>();
+    assert_eq!(results, (0..100).map(|i| (i as usize, i * 2)).collect::<Vec<_>>());
+}
+
+#[test]
+fn a_panicking_worker_yields_an_error_at_its_index() {
+    let workers = (0..2)
+        .map(|_| |index: usize, item: u32| {
+            if item == 5 {
+                panic!("item {}", item);
+            }
+            index
+        })
+        .collect();
+    let pool = OrderedWorkerPool::spawn(workers, 0..10_u32);
+
+    let results = pool.take_while(|r| r.is_ok()).map(|r| r.unwrap()).collect::<Vec<_>>();
+    assert_eq!(results, vec![0, 1, 2, 3, 4]);
+
+    let workers = vec![|_: usize, item: u32| if item == 2 { panic!("item {}", item) } else { item }];
+    let mut pool = OrderedWorkerPool::spawn(workers, 0..10_u32);
+    assert_eq!(pool.next().unwrap().unwrap(), 0);
+    assert_eq!(pool.next().unwrap().unwrap(), 1);
+    let error = pool.next().unwrap().unwrap_err();
+    assert!(error.to_string().contains("item 2"), "{}", error);
+}
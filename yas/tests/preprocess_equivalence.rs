@@ -0,0 +1,140 @@
+//! The row-major preprocessing has to produce exactly what the `ImageBuffer` based one does
+
+use image::{ImageBuffer, Luma, Rgb, RgbImage};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use yas_core::ocr::{pre_process, to_gray, Preprocessor, PREPROCESSED_LEN, PREPROCESSED_WIDTH};
+
+const SIZES: [(u32, u32); 16] = [
+    (1, 1), (2, 1), (1, 7), (7, 1), (2, 2), (13, 3),
+    (384, 32), (96, 8), (50, 10), (200, 20), (300, 45),
+    (500, 30), (1000, 10), (40, 200), (30, 400), (4000, 12),
+];
+
+/// Text-like image: strokes on a background, the strokes are darker than the background when `inverse`
+fn text_image(rng: &mut StdRng, width: u32, height: u32, inverse: bool) -> RgbImage {
+    let background: u8 = if inverse { rng.gen_range(180..=255) } else { rng.gen_range(0..80) };
+    let mut image = RgbImage::from_fn(width, height, |_, _| {
+        let v = background.saturating_add(rng.gen_range(0..10));
+        Rgb([v, v.saturating_sub(rng.gen_range(0..5)), v])
+    });
+
+    for _ in 0..rng.gen_range(1..8) {
+        let x = rng.gen_range(0..width);
+        let y = rng.gen_range(0..height);
+        let w = rng.gen_range(1..=(width - x).min(width / 4 + 1));
+        let h = rng.gen_range(1..=(height - y));
+        let color: [u8; 3] = if inverse {
+            [rng.gen_range(0..100), rng.gen_range(0..100), rng.gen_range(0..100)]
+        } else {
+            [rng.gen_range(150..=255), rng.gen_range(150..=255), rng.gen_range(150..=255)]
+        };
+        for j in y..y + h {
+            for i in x..x + w {
+                image.put_pixel(i, j, Rgb(color));
+            }
+        }
+    }
+
+    image
+}
+
+fn noise_image(rng: &mut StdRng, width: u32, height: u32) -> RgbImage {
+    RgbImage::from_fn(width, height, |_, _| Rgb(rng.gen()))
+}
+
+fn assert_same(expected: &ImageBuffer<Luma<f32>, Vec<f32>>, actual: &[f32], what: &str) {
+    assert_eq!(expected.dimensions(), (PREPROCESSED_WIDTH as u32, 32), "{}", what);
+    for (i, (e, a)) in expected.as_raw().iter().zip(actual.iter()).enumerate() {
+        assert_eq!(
+            e.to_bits(), a.to_bits(),
+            "{}: pixel ({}, {}) is {}, expected {}", what, i % PREPROCESSED_WIDTH, i / PREPROCESSED_WIDTH, a, e
+        );
+    }
+}
+
+fn check_rgb(preprocessor: &mut Preprocessor, image: &RgbImage, what: &str) {
+    let (expected, expected_non_mono) = pre_process(to_gray(image));
+
+    let mut out = vec![f32::NAN; PREPROCESSED_LEN];
+    let non_mono = preprocessor.pre_process_rgb(image.as_raw(), image.width() as usize, image.height() as usize, &mut out);
+
+    assert_eq!(expected_non_mono, non_mono, "{}", what);
+    if non_mono {
+        assert_same(&expected, &out, what);
+    }
+}
+
+fn check_gray(preprocessor: &mut Preprocessor, image: &ImageBuffer<Luma<f32>, Vec<f32>>, what: &str) {
+    let (expected, expected_non_mono) = pre_process(image.clone());
+
+    let mut out = vec![f32::NAN; PREPROCESSED_LEN];
+    let non_mono = preprocessor.pre_process_gray(image.as_raw(), image.width() as usize, image.height() as usize, &mut out);
+
+    assert_eq!(expected_non_mono, non_mono, "{}", what);
+    if non_mono {
+        assert_same(&expected, &out, what);
+    }
+}
+
+#[test]
+fn rgb_text_images() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut preprocessor = Preprocessor::new();
+
+    for &(width, height) in SIZES.iter() {
+        for round in 0..8 {
+            let inverse = round % 2 == 1;
+            let image = text_image(&mut rng, width, height, inverse);
+            check_rgb(&mut preprocessor, &image, &format!("text {}x{} #{}", width, height, round));
+        }
+    }
+}
+
+#[test]
+fn rgb_noise_images() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut preprocessor = Preprocessor::new();
+
+    for &(width, height) in SIZES.iter() {
+        let image = noise_image(&mut rng, width, height);
+        check_rgb(&mut preprocessor, &image, &format!("noise {}x{}", width, height));
+    }
+}
+
+#[test]
+fn random_sizes() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let mut preprocessor = Preprocessor::new();
+
+    for round in 0..200 {
+        let width = rng.gen_range(1..600);
+        let height = rng.gen_range(1..80);
+        let image = text_image(&mut rng, width, height, round % 3 == 0);
+        check_rgb(&mut preprocessor, &image, &format!("text {}x{} #{}", width, height, round));
+    }
+}
+
+#[test]
+fn single_color_images() {
+    let mut preprocessor = Preprocessor::new();
+
+    for &(width, height) in SIZES.iter() {
+        let image = RgbImage::from_pixel(width, height, Rgb([40, 200, 90]));
+        check_rgb(&mut preprocessor, &image, &format!("single color {}x{}", width, height));
+    }
+}
+
+#[test]
+fn f32_gray_images() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut preprocessor = Preprocessor::new();
+
+    for &(width, height) in SIZES.iter() {
+        let text = to_gray(&text_image(&mut rng, width, height, false));
+        check_gray(&mut preprocessor, &text, &format!("gray text {}x{}", width, height));
+
+        let noise = ImageBuffer::from_fn(width, height, |_, _| Luma([rng.gen_range(0.0..1.0_f32)]));
+        check_gray(&mut preprocessor, &noise, &format!("gray noise {}x{}", width, height));
+    }
+}
@@ -0,0 +1,60 @@
+//! Checkpoints are saved when a scan stops early, and removed once a resumed scan completes
+
+use std::fs;
+use std::path::PathBuf;
+
+use yas_core::scanner_controller::repository_layout::{GridScanProgress, ScanCheckpoint, ScanCheckpointConfig};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("yas_{}_{}.json", name, std::process::id()))
+}
+
+fn progress() -> GridScanProgress {
+    GridScanProgress {
+        item_count: 47,
+        row: 4,
+        col: 3,
+        avg_scroll_one_row: 10.0,
+        scrolled_rows: 2,
+    }
+}
+
+#[test]
+fn saves_when_the_scan_stops_early() {
+    let path = temp_path("checkpoint_saved");
+    let config = ScanCheckpointConfig { checkpoint: path.clone(), resume: None };
+
+    config.update(Some(progress()), &[1, 2, 3]);
+    let checkpoint = ScanCheckpoint::<i32>::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(checkpoint.results, vec![1, 2, 3]);
+    assert_eq!(checkpoint.progress.scanned_count(5), 23);
+}
+
+#[test]
+fn removes_the_resumed_checkpoint_when_the_scan_completes() {
+    let resumed = temp_path("checkpoint_resumed");
+    ScanCheckpoint { progress: progress(), results: vec![1] }.save(&resumed).unwrap();
+    let config = ScanCheckpointConfig { checkpoint: temp_path("checkpoint_unused"), resume: Some(resumed.clone()) };
+
+    let checkpoint = config.load::<i32>().unwrap().unwrap();
+    config.update::<i32>(None, &checkpoint.results);
+
+    assert!(!resumed.exists());
+    assert!(!config.checkpoint.exists());
+}
+
+#[test]
+fn overwrites_the_resumed_checkpoint_when_the_scan_stops_again() {
+    let path = temp_path("checkpoint_overwritten");
+    ScanCheckpoint { progress: progress(), results: vec![1] }.save(&path).unwrap();
+    let config = ScanCheckpointConfig { checkpoint: path.clone(), resume: Some(path.clone()) };
+
+    config.update(Some(GridScanProgress { row: 6, col: 0, ..progress() }), &[1, 2]);
+    let checkpoint = config.load::<i32>().unwrap().unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(checkpoint.results, vec![1, 2]);
+    assert_eq!(checkpoint.progress.scanned_count(5), 30);
+}
@@ -0,0 +1,104 @@
+//! Boxes of the DB post processing and their reading order, on hand-built probability maps
+
+use yas_core::ocr::{db_boxes, sort_text_boxes, DBPostProcessConfig, TextBox};
+use yas_core::positioning::Rect;
+
+const WIDTH: usize = 60;
+const HEIGHT: usize = 30;
+
+/// A map of zeros, with each `(left, top, width, height, probability)` region filled
+fn probability_map(regions: &[(usize, usize, usize, usize, f32)]) -> Vec<f32> {
+    let mut map = vec![0.0; WIDTH * HEIGHT];
+    for &(left, top, width, height, p) in regions {
+        for y in top..top + height {
+            map[y * WIDTH + left..y * WIDTH + left + width].fill(p);
+        }
+    }
+    map
+}
+
+fn boxes(regions: &[(usize, usize, usize, usize, f32)]) -> Vec<(Rect<f64>, f32)> {
+    db_boxes(&probability_map(regions), WIDTH, HEIGHT, &DBPostProcessConfig::default())
+}
+
+fn text_box(left: u32, top: u32, width: u32, height: u32) -> TextBox {
+    TextBox { rect: Rect::new(left, top, width, height), score: 0.9 }
+}
+
+#[test]
+fn expands_a_region_by_area_over_perimeter() {
+    let result = boxes(&[(10, 10, 20, 4, 0.9)]);
+
+    assert_eq!(result.len(), 1);
+    let (rect, score) = result[0];
+    // 20 * 4 * 1.5 / (2 * (20 + 4)) = 2.5 on each side
+    assert_eq!(rect, Rect::new(7.5, 7.5, 25.0, 9.0));
+    assert!((score - 0.9).abs() < 1e-4);
+}
+
+#[test]
+fn clamps_the_expansion_to_the_map() {
+    let result = boxes(&[(0, 0, 20, 4, 0.9)]);
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].0, Rect::new(0.0, 0.0, 22.5, 6.5));
+}
+
+#[test]
+fn drops_regions_shorter_than_min_size() {
+    assert!(boxes(&[(10, 10, 20, 2, 0.9)]).is_empty());
+    assert!(boxes(&[(10, 10, 2, 8, 0.9)]).is_empty());
+    assert_eq!(boxes(&[(10, 10, 20, 3, 0.9)]).len(), 1);
+}
+
+#[test]
+fn drops_regions_below_box_thresh() {
+    // above thresh, so a region, but not likely enough to be text
+    assert!(boxes(&[(10, 10, 20, 4, 0.5)]).is_empty());
+
+    // the score is the mean over the bounding box, a faint half lowers it below box_thresh
+    let result = boxes(&[(10, 10, 10, 4, 0.9), (20, 10, 10, 4, 0.4)]);
+    assert_eq!(result.len(), 1);
+    assert!((result[0].1 - 0.65).abs() < 1e-4);
+    assert!(boxes(&[(10, 10, 10, 4, 0.8), (20, 10, 10, 4, 0.35)]).is_empty());
+}
+
+#[test]
+fn connects_diagonal_neighbours() {
+    // half of the bounding box of two squares touching at a corner is empty
+    let config = DBPostProcessConfig { box_thresh: 0.4, ..Default::default() };
+    let count = |regions| db_boxes(&probability_map(regions), WIDTH, HEIGHT, &config).len();
+
+    // they are one region, apart by a pixel they are two
+    assert_eq!(count(&[(10, 10, 4, 4, 0.9), (14, 14, 4, 4, 0.9)]), 1);
+    assert_eq!(count(&[(10, 10, 4, 4, 0.9), (15, 14, 4, 4, 0.9)]), 2);
+}
+
+#[test]
+fn sorts_lines_top_to_bottom_and_boxes_left_to_right() {
+    let mut boxes = vec![
+        text_box(0, 40, 50, 20),
+        // slightly higher than the box on its left, still on the same line
+        text_box(200, 10, 50, 20),
+        text_box(20, 14, 50, 20),
+        text_box(100, 12, 50, 20),
+    ];
+    sort_text_boxes(&mut boxes);
+
+    let lefts = boxes.iter().map(|b| b.rect.left).collect::<Vec<_>>();
+    assert_eq!(lefts, vec![20, 100, 200, 0]);
+}
+
+#[test]
+fn boxes_apart_by_half_a_line_are_on_different_lines() {
+    let mut boxes = vec![text_box(100, 10, 50, 20), text_box(0, 20, 50, 20)];
+    sort_text_boxes(&mut boxes);
+
+    let lefts = boxes.iter().map(|b| b.rect.left).collect::<Vec<_>>();
+    assert_eq!(lefts, vec![100, 0]);
+
+    // the shorter box decides
+    let mut boxes = vec![text_box(100, 10, 50, 40), text_box(0, 16, 50, 10)];
+    sort_text_boxes(&mut boxes);
+    assert_eq!(boxes[0].rect.left, 100);
+}
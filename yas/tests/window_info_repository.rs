@@ -0,0 +1,112 @@
+//! Which recorded entry `get_auto_scale` derives a value from, for windows of sizes which are not recorded
+
+use yas_core::game_info::{Platform, ResolutionFamily, UI};
+use yas_core::positioning::{Pos, Rect, Size};
+use yas_core::window_info::{Anchor, AnchorX, AnchorY, WindowInfoRepository, WindowInfoSource, WindowInfoType};
+
+const KEY: &str = "panel_rect";
+
+fn repo(entries: &[(usize, usize, WindowInfoType)]) -> WindowInfoRepository {
+    let mut repo = WindowInfoRepository::new();
+    for &(width, height, value) in entries {
+        repo.add(KEY, Size::new(width, height), UI::Desktop, Platform::Windows, value);
+    }
+    repo
+}
+
+fn right_anchored(left: f64) -> WindowInfoType {
+    WindowInfoType::AnchoredRect {
+        anchor: Anchor { x: AnchorX::Right, y: AnchorY::Top },
+        rect: Rect::new(left, 100.0, 400.0, 600.0),
+    }
+}
+
+fn plain(left: f64) -> WindowInfoType {
+    WindowInfoType::Rect(Rect::new(left, 100.0, 400.0, 600.0))
+}
+
+fn get(repo: &WindowInfoRepository, width: usize, height: usize) -> Option<(Rect<f64>, WindowInfoSource)> {
+    repo.get_auto_scale_with_source(KEY, Size::new(width, height), UI::Desktop, Platform::Windows)
+}
+
+#[test]
+fn uses_the_exact_size() {
+    let repo = repo(&[(1600, 900, plain(1000.0)), (1920, 1080, plain(1200.0)), (2560, 1440, plain(1700.0))]);
+
+    let (rect, source) = get(&repo, 1920, 1080).unwrap();
+    assert_eq!(rect, Rect::new(1200.0, 100.0, 400.0, 600.0));
+    assert_eq!(source.size, Size::new(1920, 1080));
+    assert_eq!(source.scale_factor, 1.0);
+}
+
+#[test]
+fn scales_the_biggest_size_of_the_same_ratio() {
+    let repo = repo(&[(1280, 720, plain(800.0)), (1920, 1080, plain(1200.0)), (2100, 900, plain(1500.0))]);
+
+    let (rect, source) = get(&repo, 3840, 2160).unwrap();
+    assert_eq!(source.size, Size::new(1920, 1080));
+    assert_eq!(source.scale_factor, 2.0);
+    assert_eq!(rect, Rect::new(2400.0, 200.0, 800.0, 1200.0));
+}
+
+#[test]
+fn does_not_derive_plain_values_across_ratios() {
+    let repo = repo(&[(1920, 1080, plain(1200.0)), (3440, 1440, plain(2528.0))]);
+    assert!(get(&repo, 5120, 1440).is_none());
+}
+
+#[test]
+fn derives_anchored_values_from_the_closest_ratio() {
+    let repo = repo(&[(1920, 1080, right_anchored(1200.0)), (3440, 1440, right_anchored(2528.0))]);
+
+    // 32:9 is closer to 43:18 than to 16:9, the panel keeps its distance to the right edge
+    let (rect, source) = get(&repo, 5120, 1440).unwrap();
+    assert_eq!(source.size, Size::new(3440, 1440));
+    assert_eq!(source.scale_factor, 1.0);
+    assert_eq!(rect, Rect::new(5120.0 - (3440.0 - 2528.0), 100.0, 400.0, 600.0));
+}
+
+#[test]
+fn derives_anchored_values_from_the_biggest_size_of_the_closest_ratio() {
+    let repo = repo(&[(1720, 720, right_anchored(1264.0)), (3440, 1440, right_anchored(2528.0)), (1920, 1080, right_anchored(1200.0))]);
+
+    let (_, source) = get(&repo, 3840, 1080).unwrap();
+    assert_eq!(source.size, Size::new(3440, 1440));
+    assert_eq!(source.scale_factor, 0.75);
+}
+
+#[test]
+fn prefers_a_plain_value_of_the_same_ratio_to_an_anchored_one() {
+    let repo = repo(&[(2560, 1440, plain(1700.0)), (3440, 1440, right_anchored(2528.0))]);
+
+    let (_, source) = get(&repo, 1920, 1080).unwrap();
+    assert_eq!(source.size, Size::new(2560, 1440));
+}
+
+#[test]
+fn anchored_pos_is_mapped_to_its_anchor() {
+    let mut repo = WindowInfoRepository::new();
+    let value = WindowInfoType::AnchoredPos {
+        anchor: Anchor { x: AnchorX::Center, y: AnchorY::Bottom },
+        pos: Pos::new(960.0, 1000.0),
+    };
+    repo.add("pos", Size::new(1920, 1080), UI::Desktop, Platform::Windows, value);
+
+    let pos: Pos<f64> = repo.get_auto_scale("pos", Size::new(5120, 1440), UI::Desktop, Platform::Windows).unwrap();
+    assert_eq!(pos, Pos::new(2560.0, 1440.0 - 80.0 * (1440.0 / 1080.0)));
+}
+
+#[test]
+fn super_ultrawide_windows_are_supported() {
+    assert_eq!(ResolutionFamily::new(Size::new(5120, 1440)), Some(ResolutionFamily::Windows32x9));
+    assert_eq!(ResolutionFamily::new(Size::new(3840, 1080)), Some(ResolutionFamily::Windows32x9));
+    assert_eq!(ResolutionFamily::new(Size::new(3440, 1440)), Some(ResolutionFamily::Windows43x18));
+}
+
+#[test]
+fn other_windows_fall_back_to_the_closest_ratio() {
+    assert_eq!(ResolutionFamily::new(Size::new(2560, 1080)), None);
+    assert_eq!(ResolutionFamily::new_or_closest(Size::new(2560, 1080)), ResolutionFamily::Windows43x18);
+    assert_eq!(ResolutionFamily::new_or_closest(Size::new(1366, 768)), ResolutionFamily::Windows16x9);
+    assert_eq!(ResolutionFamily::new_or_closest(Size::new(1920, 1080)), ResolutionFamily::Windows16x9);
+}
@@ -0,0 +1,49 @@
+//! Needs an X server, so the tests are ignored by default.
+//! Run them with `xvfb-run cargo test -p yas_core --test x11_input -- --ignored`
+#![cfg(target_os = "linux")]
+
+use yas_core::system_control::linux::x11_input::{X11Input, BUTTON_RIGHT};
+use yas_core::system_control::{set_abort_key, AbortKey, GenericSystemControl, SystemControl};
+use yas_core::utils;
+
+#[test]
+#[ignore = "needs an X server, run with xvfb-run"]
+fn move_pointer() {
+    let mut control = GenericSystemControl::new();
+    let input = X11Input::new().unwrap();
+
+    control.mouse_move_to(10, 20).unwrap();
+    assert_eq!(input.pointer_position().unwrap(), (10, 20));
+
+    control.mouse_move_to(100, 50).unwrap();
+    control.mouse_click().unwrap();
+    control.mouse_scroll(2, false).unwrap();
+    assert_eq!(input.pointer_position().unwrap(), (100, 50));
+
+    // out of the range of the protocol, instead of wrapping around
+    assert!(control.mouse_move_to(40000, 50).is_err());
+    assert!(input.move_to(100, -40000).is_err());
+    assert_eq!(input.pointer_position().unwrap(), (100, 50));
+}
+
+#[test]
+#[ignore = "needs an X server, run with xvfb-run"]
+fn right_button_aborts() {
+    let input = X11Input::new().unwrap();
+    set_abort_key(None);
+    assert!(!utils::is_rmb_down());
+    assert!(!utils::is_abort_requested());
+
+    input.press(BUTTON_RIGHT).unwrap();
+    assert!(utils::is_rmb_down());
+    assert!(utils::is_abort_requested());
+
+    input.release(BUTTON_RIGHT).unwrap();
+    assert!(!utils::is_rmb_down());
+    assert!(!utils::is_abort_requested());
+
+    // no key is down
+    set_abort_key(Some(AbortKey::F12));
+    assert!(!utils::is_abort_requested());
+    set_abort_key(None);
+}
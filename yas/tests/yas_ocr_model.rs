@@ -0,0 +1,26 @@
+//! A broken model file or dictionary is reported when the model is loaded, e.g. one given by `--ocr-dict`
+
+use yas_core::ocr::YasOCRModel;
+
+fn load_error(model: &[u8], dict: &str) -> String {
+    match YasOCRModel::new(model, dict) {
+        Ok(_) => panic!("the model should not load"),
+        Err(e) => e.to_string(),
+    }
+}
+
+#[test]
+fn rejects_a_git_lfs_pointer() {
+    let pointer = b"version https://git-lfs.github.com/spec/v1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize 12345\n";
+    assert!(load_error(pointer, r#"{"0": "-"}"#).contains("Git LFS"));
+}
+
+#[test]
+fn rejects_a_malformed_dictionary() {
+    let model = b"not a model";
+
+    assert!(load_error(model, r#"["-", "a"]"#).contains("JSON 对象"));
+    assert!(load_error(model, r#"{"0": "-", "a": "b"}"#).contains("不是数字"));
+    assert!(load_error(model, r#"{"0": "-", "1": 2}"#).contains("不是字符串"));
+    assert!(load_error(model, r#"{"0": "-", "2": "b"}"#).contains("连续数字"));
+}